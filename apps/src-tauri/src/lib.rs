@@ -1,5 +1,14 @@
-use gpttools_core::rpc::types::JsonRpcRequest;
+use gpttools_core::rpc::client::{
+  result_error, AccountRpc, ApiKeyRpc, LoginRpc, RequestLogRpc, RpcClient, RpcClientError,
+  RpcResult, RpcTransport, ServiceRpc, UsageRpc,
+};
+use gpttools_core::rpc::params::{
+  AccountIdParams, AccountUpdateParams, ApiKeyCreateParams, ApiKeyUpdateModelParams, IdParams,
+  LoginCompleteParams, LoginIdParams, LoginStartParams, RequestLogListParams, UsageAccountParams,
+};
+use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse};
 use gpttools_core::storage::Storage;
+use serde::Serialize;
 use std::io::{Read, Write};
 use std::net::{TcpStream, ToSocketAddrs};
 use std::sync::Mutex;
@@ -10,46 +19,59 @@ use std::thread;
 
 #[tauri::command]
 async fn service_initialize(addr: Option<String>) -> Result<serde_json::Value, String> {
-  let v = tauri::async_runtime::spawn_blocking(move || rpc_call("initialize", addr, None))
-    .await
-    .map_err(|err| format!("initialize task failed: {err}"))??;
-  // 连接探测必须确认对端确实是 gpttools-service，避免端口被其他服务占用时误判“已连接”。
-  let server_name = v
-    .get("result")
-    .and_then(|r| r.get("server_name"))
-    .and_then(|s| s.as_str())
-    .unwrap_or("");
-  if server_name != "gpttools-service" {
-    let hint = if server_name.is_empty() {
+  let result = tauri::async_runtime::spawn_blocking(move || {
+    rpc_client(addr)?.initialize().map_err(|err| match err {
+      // 连接探测必须确认对端确实是 gpttools-service，避免端口被其他服务占用时误判“已连接”。
+      RpcClientError::Decode { .. } => {
+        format!("Port is in use or unexpected service responded ({err})")
+      }
+      err => err.to_string(),
+    })
+  })
+  .await
+  .map_err(|err| format!("initialize task failed: {err}"))??;
+  if result.server_name != "gpttools-service" {
+    let hint = if result.server_name.is_empty() {
       "missing server_name"
     } else {
-      server_name
+      result.server_name.as_str()
     };
     return Err(format!("Port is in use or unexpected service responded ({hint})"));
   }
-  Ok(v)
+  command_result(Ok(result))
 }
 
 #[tauri::command]
-fn service_start(app: tauri::AppHandle, addr: String) -> Result<(), String> {
+async fn service_start(app: tauri::AppHandle, addr: String) -> Result<(), String> {
   let addr = normalize_addr(&addr)?;
   log::info!("service_start requested addr={}", addr);
   // 中文注释：保存地址与回调地址，按需启动 service
   std::env::set_var("GPTTOOLS_SERVICE_ADDR", &addr);
-  stop_service();
-  spawn_service_with_addr(&app, &addr)
+  tauri::async_runtime::spawn_blocking(move || {
+    // 中文注释：重启要等旧实例排空在途请求再拉起新实例；不这样做新实例会清掉停服标记，旧实例上的长流被直接掐断。
+    stop_service();
+    wait_for_stopped_service();
+    spawn_service_with_addr(&app, &addr)
+  })
+  .await
+  .map_err(|err| format!("service start task failed: {err}"))?
 }
 
 #[tauri::command]
-fn service_stop() -> Result<(), String> {
-  // 中文注释：显式停止 service 进程
-  stop_service();
-  Ok(())
+fn service_stop() -> Result<serde_json::Value, String> {
+  // 中文注释：显式停止 service；返回排空进度，前端据此提示还有多少请求在途
+  let status = stop_service().unwrap_or_else(gpttools_service::drain_status);
+  serde_json::to_value(status).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn service_drain_status() -> Result<serde_json::Value, String> {
+  serde_json::to_value(gpttools_service::drain_status()).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
 fn service_account_list(addr: Option<String>) -> Result<serde_json::Value, String> {
-  rpc_call("account/list", addr, None)
+  command_result(rpc_client(addr)?.account_list())
 }
 
 #[tauri::command]
@@ -57,8 +79,7 @@ fn service_account_delete(
   addr: Option<String>,
   account_id: String,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "accountId": account_id });
-  rpc_call("account/delete", addr, Some(params))
+  command_result(rpc_client(addr)?.account_delete(&AccountIdParams { account_id }))
 }
 
 #[tauri::command]
@@ -67,8 +88,7 @@ fn service_account_update(
   account_id: String,
   sort: i64,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "accountId": account_id, "sort": sort });
-  rpc_call("account/update", addr, Some(params))
+  command_result(rpc_client(addr)?.account_update(&AccountUpdateParams { account_id, sort }))
 }
 
 #[tauri::command]
@@ -93,13 +113,12 @@ fn service_usage_read(
   addr: Option<String>,
   account_id: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = account_id.map(|id| serde_json::json!({ "accountId": id }));
-  rpc_call("account/usage/read", addr, params)
+  command_result(rpc_client(addr)?.usage_read(&UsageAccountParams { account_id }))
 }
 
 #[tauri::command]
 fn service_usage_list(addr: Option<String>) -> Result<serde_json::Value, String> {
-  rpc_call("account/usage/list", addr, None)
+  command_result(rpc_client(addr)?.usage_list())
 }
 
 #[tauri::command]
@@ -107,8 +126,7 @@ fn service_usage_refresh(
   addr: Option<String>,
   account_id: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = account_id.map(|id| serde_json::json!({ "accountId": id }));
-  rpc_call("account/usage/refresh", addr, params)
+  command_result(rpc_client(addr)?.usage_refresh(&UsageAccountParams { account_id }))
 }
 
 #[tauri::command]
@@ -117,13 +135,12 @@ fn service_requestlog_list(
   query: Option<String>,
   limit: Option<i64>,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "query": query, "limit": limit });
-  rpc_call("requestlog/list", addr, Some(params))
+  command_result(rpc_client(addr)?.requestlog_list(&RequestLogListParams { query, limit }))
 }
 
 #[tauri::command]
 fn service_requestlog_clear(addr: Option<String>) -> Result<serde_json::Value, String> {
-  rpc_call("requestlog/clear", addr, None)
+  command_result(rpc_client(addr)?.requestlog_clear())
 }
 
 #[tauri::command]
@@ -136,23 +153,21 @@ fn service_login_start(
   group_name: Option<String>,
   workspace_id: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({
-    "type": login_type,
-    "openBrowser": open_browser.unwrap_or(true),
-    "note": note,
-    "tags": tags,
-    "groupName": group_name,
-    "workspaceId": workspace_id
-  });
-  rpc_call("account/login/start", addr, Some(params))
+  let params = LoginStartParams {
+    login_type: Some(login_type),
+    open_browser: Some(open_browser.unwrap_or(true)),
+    note,
+    tags,
+    group_name,
+    workspace_id,
+    ..Default::default()
+  };
+  command_result(rpc_client(addr)?.login_start(&params))
 }
 
 #[tauri::command]
 fn service_login_status(addr: Option<String>, login_id: String) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({
-    "loginId": login_id
-  });
-  rpc_call("account/login/status", addr, Some(params))
+  command_result(rpc_client(addr)?.login_status(&LoginIdParams { login_id }))
 }
 
 #[tauri::command]
@@ -162,17 +177,17 @@ fn service_login_complete(
   code: String,
   redirect_uri: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({
-    "state": state,
-    "code": code,
-    "redirectUri": redirect_uri
-  });
-  rpc_call("account/login/complete", addr, Some(params))
+  let params = LoginCompleteParams {
+    state,
+    code,
+    redirect_uri,
+  };
+  command_result(rpc_client(addr)?.login_complete(&params))
 }
 
 #[tauri::command]
 fn service_apikey_list(addr: Option<String>) -> Result<serde_json::Value, String> {
-  rpc_call("apikey/list", addr, None)
+  command_result(rpc_client(addr)?.apikey_list())
 }
 
 #[tauri::command]
@@ -183,18 +198,18 @@ fn service_apikey_create(
   reasoning_effort: Option<String>,
   protocol_type: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({
-    "name": name,
-    "modelSlug": model_slug,
-    "reasoningEffort": reasoning_effort,
-    "protocolType": protocol_type,
-  });
-  rpc_call("apikey/create", addr, Some(params))
+  let params = ApiKeyCreateParams {
+    name,
+    model_slug,
+    reasoning_effort,
+    protocol_type,
+  };
+  command_result(rpc_client(addr)?.apikey_create(&params))
 }
 
 #[tauri::command]
 fn service_apikey_models(addr: Option<String>) -> Result<serde_json::Value, String> {
-  rpc_call("apikey/models", addr, None)
+  command_result(rpc_client(addr)?.apikey_models())
 }
 
 #[tauri::command]
@@ -205,31 +220,28 @@ fn service_apikey_update_model(
   reasoning_effort: Option<String>,
   protocol_type: Option<String>,
 ) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({
-    "id": key_id,
-    "modelSlug": model_slug,
-    "reasoningEffort": reasoning_effort,
-    "protocolType": protocol_type,
-  });
-  rpc_call("apikey/updateModel", addr, Some(params))
+  let params = ApiKeyUpdateModelParams {
+    id: key_id,
+    model_slug,
+    reasoning_effort,
+    protocol_type,
+  };
+  command_result(rpc_client(addr)?.apikey_update_model(&params))
 }
 
 #[tauri::command]
 fn service_apikey_delete(addr: Option<String>, key_id: String) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "id": key_id });
-  rpc_call("apikey/delete", addr, Some(params))
+  command_result(rpc_client(addr)?.apikey_delete(&IdParams { id: key_id }))
 }
 
 #[tauri::command]
 fn service_apikey_disable(addr: Option<String>, key_id: String) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "id": key_id });
-  rpc_call("apikey/disable", addr, Some(params))
+  command_result(rpc_client(addr)?.apikey_disable(&IdParams { id: key_id }))
 }
 
 #[tauri::command]
 fn service_apikey_enable(addr: Option<String>, key_id: String) -> Result<serde_json::Value, String> {
-  let params = serde_json::json!({ "id": key_id });
-  rpc_call("apikey/enable", addr, Some(params))
+  command_result(rpc_client(addr)?.apikey_enable(&IdParams { id: key_id }))
 }
 
 #[tauri::command]
@@ -279,6 +291,7 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
       service_start,
       service_stop,
+      service_drain_status,
       service_initialize,
       service_account_list,
       service_account_delete,
@@ -519,27 +532,21 @@ fn parse_http_body(buf: &str) -> Result<String, String> {
   }
 }
 
-fn rpc_call(
-  method: &str,
-  addr: Option<String>,
-  params: Option<serde_json::Value>,
-) -> Result<serde_json::Value, String> {
-  let addr = resolve_service_addr(addr)?;
-  log::debug!("rpc {} -> {}", method, addr);
-  for attempt in 0..=1 {
-    let mut stream = connect_with_timeout(&addr, Duration::from_millis(400)).map_err(|e| {
+/// 桌面端直连 service `/rpc` 的传输层：每次请求一条短连接，兼容 chunked 响应。
+struct SocketTransport {
+  addr: String,
+}
+
+impl SocketTransport {
+  fn exchange(&self, method: &str, json: &str) -> Result<String, String> {
+    let addr = self.addr.as_str();
+    let mut stream = connect_with_timeout(addr, Duration::from_millis(400)).map_err(|e| {
       log::warn!("rpc connect failed ({} -> {}): {}", method, addr, e);
       e
     })?;
     let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
     let _ = stream.set_write_timeout(Some(Duration::from_secs(10)));
 
-    let req = JsonRpcRequest {
-      id: 1,
-      method: method.to_string(),
-      params: params.clone(),
-    };
-    let json = serde_json::to_string(&req).map_err(|e| e.to_string())?;
     let rpc_token = gpttools_service::rpc_auth_token();
     let http = format!(
       "POST /rpc HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nX-Gpttools-Rpc-Token: {rpc_token}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
@@ -558,32 +565,73 @@ fn rpc_call(
       log::warn!("rpc read failed ({} -> {}): {}", method, addr, msg);
       msg
     })?;
-    let body = parse_http_body(&buf).map_err(|msg| {
+    parse_http_body(&buf).map_err(|msg| {
       log::warn!("rpc parse failed ({} -> {}): {}", method, addr, msg);
       msg
-    })?;
-    if body.trim().is_empty() {
-      // 中文注释：前置代理在启动切换窗口可能返回空包；这里短重试一次，避免 UI 直接报“连接失败”。
-      if attempt == 0 {
-        std::thread::sleep(Duration::from_millis(120));
-        continue;
+    })
+  }
+}
+
+impl RpcTransport for SocketTransport {
+  fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse> {
+    let method = request.method.as_str();
+    let addr = self.addr.as_str();
+    log::debug!("rpc {} -> {}", method, addr);
+    let json = serde_json::to_string(request).map_err(|e| RpcClientError::Transport(e.to_string()))?;
+    for attempt in 0..=1 {
+      let body = self.exchange(method, &json).map_err(RpcClientError::Transport)?;
+      if body.trim().is_empty() {
+        // 中文注释：前置代理在启动切换窗口可能返回空包；这里短重试一次，避免 UI 直接报“连接失败”。
+        if attempt == 0 {
+          std::thread::sleep(Duration::from_millis(120));
+          continue;
+        }
+        log::warn!("rpc empty response ({} -> {})", method, addr);
+        break;
       }
-      log::warn!("rpc empty response ({} -> {})", method, addr);
-      return Err("Empty response from service".to_string());
-    }
 
-    let v: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
-      let msg = e.to_string();
-      log::warn!("rpc json parse failed ({} -> {}): {}", method, addr, msg);
-      msg
-    })?;
-    if let Some(err) = v.get("error") {
-      log::warn!("rpc error ({} -> {}): {}", method, addr, err);
+      let v: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
+        let msg = e.to_string();
+        log::warn!("rpc json parse failed ({} -> {}): {}", method, addr, msg);
+        RpcClientError::Transport(msg)
+      })?;
+      // 中文注释：鉴权失败等 HTTP 层错误只有顶层 error、没有 result，按传输错误上报，不当成业务结果解码。
+      let Some(result) = v.get("result").cloned() else {
+        let err = v
+          .get("error")
+          .map(|err| err.as_str().map(str::to_string).unwrap_or_else(|| err.to_string()))
+          .unwrap_or_else(|| "missing result".to_string());
+        log::warn!("rpc error ({} -> {}): {}", method, addr, err);
+        return Err(RpcClientError::Transport(err));
+      };
+      if let Some(err) = result_error(&result) {
+        log::warn!("rpc error ({} -> {}): {}", method, addr, err);
+      }
+      return Ok(JsonRpcResponse {
+        id: v.get("id").and_then(|id| id.as_u64()).unwrap_or(request.id),
+        result,
+      });
     }
-    return Ok(v);
+
+    Err(RpcClientError::Transport("Empty response from service".to_string()))
   }
+}
+
+fn rpc_client(addr: Option<String>) -> Result<RpcClient<SocketTransport>, String> {
+  Ok(RpcClient::new(SocketTransport {
+    addr: resolve_service_addr(addr)?,
+  }))
+}
 
-  Err("Empty response from service".to_string())
+fn command_result<T: Serialize>(result: RpcResult<T>) -> Result<serde_json::Value, String> {
+  match result {
+    Ok(value) => serde_json::to_value(value).map_err(|err| err.to_string()),
+    // 中文注释：前端按 result 里的 ok/error 字段提示业务失败；这里还原成同样的结构，前端不必跟着改。
+    Err(RpcClientError::Rpc { message, .. }) => {
+      Ok(serde_json::json!({ "ok": false, "error": message }))
+    }
+    Err(err) => Err(err.to_string()),
+  }
 }
 
 fn normalize_host(value: &str) -> String {
@@ -608,6 +656,7 @@ struct ServiceRuntime {
 }
 
 static SERVICE_RUNTIME: OnceLock<Mutex<Option<ServiceRuntime>>> = OnceLock::new();
+static STOPPING_SERVICE: OnceLock<Mutex<Option<thread::JoinHandle<()>>>> = OnceLock::new();
 
 fn set_service_runtime(runtime: ServiceRuntime) {
   let slot = SERVICE_RUNTIME.get_or_init(|| Mutex::new(None));
@@ -625,13 +674,26 @@ fn take_service_runtime() -> Option<ServiceRuntime> {
   }
 }
 
-fn stop_service() {
-  if let Some(runtime) = take_service_runtime() {
-    log::info!("service stopping at {}", runtime.addr);
-    gpttools_service::request_shutdown(&runtime.addr);
-    thread::spawn(move || {
-      let _ = runtime.join.join();
-    });
+fn stop_service() -> Option<gpttools_service::DrainStatus> {
+  let runtime = take_service_runtime()?;
+  log::info!("service stopping at {}", runtime.addr);
+  let status = gpttools_service::request_shutdown(&runtime.addr);
+  log::info!(
+    "service draining {} in-flight requests",
+    status.in_flight_requests
+  );
+  let slot = STOPPING_SERVICE.get_or_init(|| Mutex::new(None));
+  if let Ok(mut guard) = slot.lock() {
+    *guard = Some(runtime.join);
+  }
+  Some(status)
+}
+
+fn wait_for_stopped_service() {
+  let slot = STOPPING_SERVICE.get_or_init(|| Mutex::new(None));
+  let join = slot.lock().ok().and_then(|mut guard| guard.take());
+  if let Some(join) = join {
+    let _ = join.join();
   }
 }
 
@@ -657,6 +719,8 @@ fn connect_with_timeout(addr: &str, timeout: Duration) -> Result<TcpStream, Stri
 #[cfg(test)]
 mod tests {
   use super::*;
+  use gpttools_core::rpc::client::RpcCall;
+  use gpttools_core::rpc::methods;
   use std::io::{Read, Write};
   use std::net::TcpListener;
   use std::time::Duration;
@@ -689,7 +753,7 @@ mod tests {
   }
 
   #[test]
-  fn rpc_call_tolerates_slow_response() {
+  fn rpc_client_tolerates_slow_response() {
     let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
     let addr = listener.local_addr().expect("addr");
     std::thread::spawn(move || {
@@ -707,12 +771,13 @@ mod tests {
       }
     });
 
-    let res = rpc_call("initialize", Some(addr.to_string()), None);
+    let client = rpc_client(Some(addr.to_string())).expect("client");
+    let res = client.call_value(methods::INITIALIZE, None);
     assert!(res.is_ok());
   }
 
   #[test]
-  fn rpc_call_handles_chunked_response() {
+  fn rpc_client_handles_chunked_response() {
     let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
     let addr = listener.local_addr().expect("addr");
     std::thread::spawn(move || {
@@ -734,11 +799,8 @@ mod tests {
       }
     });
 
-    let res = rpc_call("initialize", Some(addr.to_string()), None).expect("rpc_call");
-    let ok = res
-      .get("result")
-      .and_then(|v| v.get("ok"))
-      .and_then(|v| v.as_bool());
-    assert_eq!(ok, Some(true));
+    let client = rpc_client(Some(addr.to_string())).expect("client");
+    let res = client.call_value(methods::INITIALIZE, None).expect("rpc call");
+    assert_eq!(res.get("ok").and_then(|v| v.as_bool()), Some(true));
   }
 }
@@ -29,6 +29,10 @@ export async function serviceStop() {
   return invoke("service_stop", {});
 }
 
+export async function serviceDrainStatus() {
+  return invoke("service_drain_status", {});
+}
+
 export async function serviceInitialize() {
   return invoke("service_initialize", withAddr());
 }
@@ -55,6 +55,8 @@ function isExpectedInitializeResult(res) {
   return readServerNameFromInitialize(res) === "gpttools-service";
 }
 
+const DRAIN_POLL_MS = 500;
+
 // 初始化连接（不负责启动 service）
 const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
 
@@ -148,10 +150,29 @@ export function createConnectionService(deps) {
     return initializeService({ retries, delayMs, wait: waitFn, silent });
   }
 
+  async function waitForDrain(status) {
+    // 中文注释：停服会等在途请求（含流式响应）结束；轮询排空进度并提示剩余数量，不这样做界面会一直停在“停止中”看不出在等什么。
+    let current = status;
+    while (
+      current &&
+      current.draining &&
+      current.inFlightRequests > 0 &&
+      !current.deadlineExceeded &&
+      current.remainingMs !== 0 &&
+      typeof apiClient.serviceDrainStatus === "function"
+    ) {
+      const seconds = Math.ceil((current.remainingMs || 0) / 1000);
+      setStatusFn(`停止中...（等待 ${current.inFlightRequests} 个请求结束，最多 ${seconds} 秒）`, false);
+      await wait(DRAIN_POLL_MS);
+      current = await apiClient.serviceDrainStatus();
+    }
+  }
+
   async function stopService() {
     setStatusFn("停止中...", false);
     try {
-      await apiClient.serviceStop();
+      const status = await apiClient.serviceStop();
+      await waitForDrain(status);
     } catch (err) {
       setServiceHintFn(`停止失败：${String(err)}`, true);
     }
@@ -153,3 +153,43 @@ test("waitForConnection shows retry reason even when silent", async () => {
   assert.ok(hintCalls.some((item) => item[0] && item[0].includes("正在重试：")));
   assert.equal(hintCalls.some((item) => item[1] === true), false);
 });
+
+test("stopService waits for in-flight requests to drain", async () => {
+  const drainStatuses = [
+    { draining: true, inFlightRequests: 1, remainingMs: 29000, deadlineExceeded: false },
+    { draining: true, inFlightRequests: 0, remainingMs: 28500, deadlineExceeded: false },
+  ];
+  let drainPolls = 0;
+  const api = {
+    serviceInitialize: async () => ({ server_name: "gpttools-service", version: "test" }),
+    serviceStart: async () => {},
+    serviceStop: async () => ({
+      draining: true,
+      inFlightRequests: 2,
+      remainingMs: 30000,
+      deadlineExceeded: false,
+    }),
+    serviceDrainStatus: async () => {
+      const status = drainStatuses[drainPolls];
+      drainPolls += 1;
+      return status;
+    },
+  };
+  const state = { serviceConnected: true, serviceAddr: "localhost:5050" };
+  const statusCalls = [];
+
+  const service = createConnectionService({
+    api,
+    state,
+    setStatus: (message, ok) => statusCalls.push([message, ok]),
+    setServiceHint: () => {},
+    wait: async () => {},
+  });
+
+  await service.stopService();
+
+  assert.equal(drainPolls, 2);
+  assert.equal(state.serviceConnected, false);
+  assert.ok(statusCalls.some((item) => item[0].includes("等待 2 个请求结束")));
+  assert.deepEqual(statusCalls[statusCalls.length - 1], ["", false]);
+});
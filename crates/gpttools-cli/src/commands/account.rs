@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use gpttools_core::rpc::client::AccountRpc;
+use gpttools_core::rpc::params::{
+    AccountExportParams, AccountIdParams, AccountImportParams, AccountProxySetParams,
+    AccountUpdateParams,
+};
+
+use crate::output::{cell, done, emit, print_json, OutputFormat, Table};
+use crate::rpc_client::Client;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum AccountCommand {
+    /// List accounts
+    List,
+    /// Delete an account
+    Delete { account_id: String },
+    /// Change an account's sort order (lower is tried first)
+    Sort { account_id: String, sort: i64 },
+    /// Set or clear an account's upstream proxy
+    Proxy {
+        account_id: String,
+        /// http://, https://, socks5:// or socks5h:// URL; omit to go direct
+        proxy_url: Option<String>,
+    },
+    /// Import accounts from auth.json / token export files
+    Import {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long)]
+        group: Option<String>,
+        #[arg(long)]
+        tags: Option<String>,
+        /// Skip validating tokens against upstream
+        #[arg(long)]
+        no_validate: bool,
+    },
+    /// Export an encrypted backup bundle
+    Export {
+        #[arg(long, env = "GPTTOOLS_EXPORT_PASSPHRASE", hide_env_values = true)]
+        passphrase: String,
+        /// Write the bundle to a file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+pub(crate) fn run(
+    client: &Client,
+    format: OutputFormat,
+    cmd: AccountCommand,
+) -> Result<(), String> {
+    match cmd {
+        AccountCommand::List => {
+            let result = client.account_list()?;
+            emit(format, &result, |result| {
+                let mut table = Table::new(&["ID", "LABEL", "GROUP", "PLAN", "SORT", "PROXY"]);
+                for item in &result.items {
+                    table.push(vec![
+                        item.id.clone(),
+                        item.label.clone(),
+                        cell(item.group_name.as_deref()),
+                        cell(item.plan_type.as_deref()),
+                        item.sort.to_string(),
+                        cell(item.proxy_url.as_deref()),
+                    ]);
+                }
+                table
+            })
+        }
+        AccountCommand::Delete { account_id } => {
+            let result = client.account_delete(&AccountIdParams {
+                account_id: account_id.clone(),
+            })?;
+            done(format, &result, &format!("deleted {account_id}"))
+        }
+        AccountCommand::Sort { account_id, sort } => {
+            let result = client.account_update(&AccountUpdateParams {
+                account_id: account_id.clone(),
+                sort,
+            })?;
+            done(format, &result, &format!("{account_id} sort={sort}"))
+        }
+        AccountCommand::Proxy {
+            account_id,
+            proxy_url,
+        } => {
+            let result = client.account_proxy_set(&AccountProxySetParams {
+                account_id: account_id.clone(),
+                proxy_url,
+            })?;
+            let applied = result.proxy_url.as_deref().unwrap_or("direct");
+            done(format, &result, &format!("{account_id} proxy={applied}"))
+        }
+        AccountCommand::Import {
+            files,
+            group,
+            tags,
+            no_validate,
+        } => {
+            // 中文注释：文件在本地读取后以文本上传；服务端可能在另一台机器上，直接传路径读不到。
+            let mut contents = Vec::with_capacity(files.len());
+            for file in &files {
+                let text = std::fs::read_to_string(file)
+                    .map_err(|err| format!("read {}: {err}", file.display()))?;
+                contents.push(text.into());
+            }
+            let result = client.account_import(&AccountImportParams {
+                contents,
+                group_name: group,
+                tags,
+                validate: Some(!no_validate),
+                ..Default::default()
+            })?;
+            emit(format, &result, |result| {
+                let mut table = Table::new(&["SOURCE", "STATUS", "ACCOUNT", "ERROR"]);
+                for item in &result.items {
+                    table.push(vec![
+                        item.source.clone(),
+                        item.status.clone(),
+                        cell(item.account_id.as_deref()),
+                        cell(item.error.as_deref()),
+                    ]);
+                }
+                table
+            })?;
+            if format == OutputFormat::Table {
+                println!(
+                    "created {}, updated {}, skipped {}, failed {}",
+                    result.created, result.updated, result.skipped, result.failed
+                );
+            }
+            Ok(())
+        }
+        AccountCommand::Export { passphrase, out } => {
+            let result = client.account_export(&AccountExportParams { passphrase })?;
+            let Some(out) = out else {
+                return match format {
+                    OutputFormat::Json => print_json(&result),
+                    OutputFormat::Table => {
+                        println!("{}", result.bundle);
+                        Ok(())
+                    }
+                };
+            };
+            std::fs::write(&out, &result.bundle)
+                .map_err(|err| format!("write {}: {err}", out.display()))?;
+            eprintln!(
+                "exported {} accounts, {} tokens, {} api keys to {}",
+                result.account_count,
+                result.token_count,
+                result.api_key_count,
+                out.display()
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use clap::Subcommand;
+use gpttools_core::rpc::client::ApiKeyRpc;
+use gpttools_core::rpc::params::{ApiKeyCreateParams, IdParams};
+
+use crate::output::{cell, done, emit, format_ts, OutputFormat, Table};
+use crate::rpc_client::Client;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ApiKeyCommand {
+    /// List platform API keys
+    List,
+    /// Create a platform API key (the key is shown once)
+    Create {
+        #[arg(long)]
+        name: Option<String>,
+        /// Model slug to force for requests using this key
+        #[arg(long)]
+        model: Option<String>,
+        #[arg(long)]
+        reasoning_effort: Option<String>,
+        /// openai_compat or anthropic_native
+        #[arg(long)]
+        protocol: Option<String>,
+    },
+    /// Delete a platform API key
+    Delete { id: String },
+    /// Disable a platform API key
+    Disable { id: String },
+    /// Re-enable a platform API key
+    Enable { id: String },
+}
+
+pub(crate) fn run(client: &Client, format: OutputFormat, cmd: ApiKeyCommand) -> Result<(), String> {
+    match cmd {
+        ApiKeyCommand::List => {
+            let result = client.apikey_list()?;
+            emit(format, &result, |result| {
+                let mut table = Table::new(&[
+                    "ID",
+                    "NAME",
+                    "MODEL",
+                    "PROTOCOL",
+                    "STATUS",
+                    "CREATED",
+                    "LAST USED",
+                ]);
+                for item in &result.items {
+                    table.push(vec![
+                        item.id.clone(),
+                        cell(item.name.as_deref()),
+                        cell(item.model_slug.as_deref()),
+                        item.protocol_type.clone(),
+                        item.status.clone(),
+                        format_ts(item.created_at),
+                        cell(item.last_used_at.map(format_ts)),
+                    ]);
+                }
+                table
+            })
+        }
+        ApiKeyCommand::Create {
+            name,
+            model,
+            reasoning_effort,
+            protocol,
+        } => {
+            let result = client.apikey_create(&ApiKeyCreateParams {
+                name,
+                model_slug: model,
+                reasoning_effort,
+                protocol_type: protocol,
+            })?;
+            emit(format, &result, |result| {
+                let mut table = Table::new(&["ID", "KEY"]);
+                table.push(vec![result.id.clone(), result.key.clone()]);
+                table
+            })
+        }
+        ApiKeyCommand::Delete { id } => {
+            let result = client.apikey_delete(&IdParams { id: id.clone() })?;
+            done(format, &result, &format!("deleted {id}"))
+        }
+        ApiKeyCommand::Disable { id } => {
+            let result = client.apikey_disable(&IdParams { id: id.clone() })?;
+            done(format, &result, &format!("disabled {id}"))
+        }
+        ApiKeyCommand::Enable { id } => {
+            let result = client.apikey_enable(&IdParams { id: id.clone() })?;
+            done(format, &result, &format!("enabled {id}"))
+        }
+    }
+}
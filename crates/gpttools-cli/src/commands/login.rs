@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use clap::Subcommand;
+use gpttools_core::rpc::client::LoginRpc;
+use gpttools_core::rpc::params::{LoginCompleteParams, LoginIdParams, LoginStartParams};
+use gpttools_core::rpc::types::LoginStartResult;
+
+use crate::output::{done, print_json, OutputFormat};
+use crate::rpc_client::Client;
+
+const DEFAULT_POLL_SECS: u64 = 5;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum LoginCommand {
+    /// Start a login session and print the URL (or device code) to open
+    Start {
+        /// Use the device-code flow (no local browser or callback needed)
+        #[arg(long)]
+        device: bool,
+        /// Open a browser on the service host (browser flow only)
+        #[arg(long)]
+        open_browser: bool,
+        #[arg(long)]
+        note: Option<String>,
+        #[arg(long)]
+        tags: Option<String>,
+        #[arg(long)]
+        group: Option<String>,
+        #[arg(long)]
+        workspace: Option<String>,
+        /// Poll until the login finishes
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Show a login session's status
+    Status { login_id: String },
+    /// Finish a browser login with the code/state from the callback URL
+    Complete {
+        #[arg(long)]
+        state: String,
+        #[arg(long)]
+        code: String,
+        #[arg(long)]
+        redirect_uri: Option<String>,
+    },
+    /// Cancel a pending login session
+    Cancel { login_id: String },
+}
+
+pub(crate) fn run(client: &Client, format: OutputFormat, cmd: LoginCommand) -> Result<(), String> {
+    match cmd {
+        LoginCommand::Start {
+            device,
+            open_browser,
+            note,
+            tags,
+            group,
+            workspace,
+            wait,
+        } => {
+            let result = client.login_start(&LoginStartParams {
+                login_type: Some(if device { "device" } else { "chatgpt" }.to_string()),
+                // 中文注释：CLI 多跑在无桌面的服务器上，默认不让服务端去拉起浏览器。
+                open_browser: Some(open_browser && !device),
+                note,
+                tags,
+                group_name: group,
+                workspace_id: workspace,
+                ..Default::default()
+            })?;
+            match format {
+                OutputFormat::Json => print_json(&result)?,
+                OutputFormat::Table => print_login_start(&result),
+            }
+            if !wait {
+                return Ok(());
+            }
+            let interval = result
+                .device
+                .as_ref()
+                .and_then(|device| device.interval_secs)
+                .unwrap_or(DEFAULT_POLL_SECS)
+                .max(1);
+            wait_for_login(
+                client,
+                format,
+                &result.login_id,
+                Duration::from_secs(interval),
+            )
+        }
+        LoginCommand::Status { login_id } => {
+            let result = client.login_status(&LoginIdParams {
+                login_id: login_id.clone(),
+            })?;
+            let message = match result.error.as_deref() {
+                Some(error) => format!("{login_id}: {} ({error})", result.status),
+                None => format!("{login_id}: {}", result.status),
+            };
+            done(format, &result, &message)
+        }
+        LoginCommand::Complete {
+            state,
+            code,
+            redirect_uri,
+        } => {
+            let result = client.login_complete(&LoginCompleteParams {
+                state,
+                code,
+                redirect_uri,
+            })?;
+            done(format, &result, "login completed")
+        }
+        LoginCommand::Cancel { login_id } => {
+            let result = client.login_cancel(&LoginIdParams {
+                login_id: login_id.clone(),
+            })?;
+            done(format, &result, &format!("cancelled {login_id}"))
+        }
+    }
+}
+
+fn print_login_start(result: &LoginStartResult) {
+    println!("login id: {}", result.login_id);
+    match result.device.as_ref() {
+        Some(device) => {
+            println!("open:     {}", device.verification_url);
+            if let Some(code) = device.user_code.as_deref() {
+                println!("code:     {code}");
+            }
+        }
+        None => println!("open:     {}", result.auth_url),
+    }
+    if let Some(warning) = result.warning.as_deref() {
+        println!("warning:  {warning}");
+    }
+}
+
+fn wait_for_login(
+    client: &Client,
+    format: OutputFormat,
+    login_id: &str,
+    interval: Duration,
+) -> Result<(), String> {
+    // 轮询会话状态直到离开 pending
+    let params = LoginIdParams {
+        login_id: login_id.to_string(),
+    };
+    loop {
+        let result = client.login_status(&params)?;
+        match result.status.as_str() {
+            "pending" => std::thread::sleep(interval),
+            "success" => return done(format, &result, "login succeeded"),
+            other => {
+                let reason = result.error.as_deref().unwrap_or("no detail");
+                return Err(format!(
+                    "login {login_id} ended with status {other}: {reason}"
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use clap::Subcommand;
+use gpttools_core::rpc::client::RequestLogRpc;
+use gpttools_core::rpc::params::RequestLogListParams;
+
+use crate::output::{cell, emit, format_ts, OutputFormat, Table};
+use crate::rpc_client::Client;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum RequestLogCommand {
+    /// Search request logs (newest first)
+    Search {
+        /// Match path, model, key id or error; supports the service's field:value filters
+        query: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+}
+
+pub(crate) fn run(
+    client: &Client,
+    format: OutputFormat,
+    cmd: RequestLogCommand,
+) -> Result<(), String> {
+    match cmd {
+        RequestLogCommand::Search { query, limit } => {
+            let result = client.requestlog_list(&RequestLogListParams {
+                query,
+                limit: Some(limit),
+            })?;
+            emit(format, &result, |result| {
+                let mut table =
+                    Table::new(&["TIME", "KEY", "METHOD", "PATH", "MODEL", "STATUS", "ERROR"]);
+                for item in &result.items {
+                    table.push(vec![
+                        format_ts(item.created_at),
+                        cell(item.key_id.as_deref()),
+                        item.method.clone(),
+                        item.request_path.clone(),
+                        cell(item.model.as_deref()),
+                        cell(item.status_code),
+                        cell(item.error.as_deref()),
+                    ]);
+                }
+                table
+            })
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use gpttools_core::rpc::client::ServiceRpc;
+
+use crate::output::{cell, emit, OutputFormat, Table};
+use crate::rpc_client::Client;
+
+pub(crate) fn run(client: &Client, format: OutputFormat) -> Result<(), String> {
+    // 服务握手 + 存储迁移状态，用于确认远端确实是 gpttools-service 且库结构可用
+    let info = client.initialize()?;
+    if info.server_name != "gpttools-service" {
+        return Err(format!(
+            "unexpected service responded: {}",
+            info.server_name
+        ));
+    }
+    let storage = client.storage_info()?;
+    let value = serde_json::json!({
+        "serverName": info.server_name,
+        "version": info.version,
+        "storage": storage,
+    });
+    emit(format, &value, |_| {
+        let mut table = Table::new(&["FIELD", "VALUE"]);
+        table.push(vec!["server".to_string(), info.server_name.clone()]);
+        table.push(vec!["version".to_string(), info.version.clone()]);
+        table.push(vec!["database".to_string(), cell(storage.path.as_deref())]);
+        table.push(vec!["journal".to_string(), storage.journal_mode.clone()]);
+        table.push(vec![
+            "schema".to_string(),
+            format!(
+                "{} (latest {})",
+                cell(storage.current_version.as_deref()),
+                storage.latest_version
+            ),
+        ]);
+        table.push(vec![
+            "pending".to_string(),
+            if storage.pending.is_empty() {
+                "-".to_string()
+            } else {
+                storage.pending.join(", ")
+            },
+        ]);
+        table
+    })
+}
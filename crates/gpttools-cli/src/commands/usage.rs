@@ -0,0 +1,59 @@
+use clap::Subcommand;
+use gpttools_core::rpc::client::UsageRpc;
+use gpttools_core::rpc::params::UsageAccountParams;
+use gpttools_core::rpc::types::UsageSnapshotResult;
+
+use crate::output::{cell, done, emit, format_ts, OutputFormat, Table};
+use crate::rpc_client::Client;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum UsageCommand {
+    /// Show the latest usage snapshot of every account
+    List,
+    /// Fetch fresh usage from upstream (all accounts if none given)
+    Refresh { account_id: Option<String> },
+}
+
+fn percent(value: Option<f64>) -> String {
+    cell(value.map(|v| format!("{v:.0}%")))
+}
+
+fn usage_table(items: &[UsageSnapshotResult]) -> Table {
+    let mut table = Table::new(&[
+        "ACCOUNT",
+        "PLAN",
+        "USED",
+        "RESETS",
+        "2ND USED",
+        "2ND RESETS",
+        "AVAILABILITY",
+    ]);
+    for item in items {
+        table.push(vec![
+            cell(item.account_id.as_deref()),
+            cell(item.plan_type.as_deref()),
+            percent(item.used_percent),
+            cell(item.resets_at.map(format_ts)),
+            percent(item.secondary_used_percent),
+            cell(item.secondary_resets_at.map(format_ts)),
+            cell(item.availability.as_deref()),
+        ]);
+    }
+    table
+}
+
+pub(crate) fn run(client: &Client, format: OutputFormat, cmd: UsageCommand) -> Result<(), String> {
+    match cmd {
+        UsageCommand::List => {
+            let result = client.usage_list()?;
+            emit(format, &result, |result| usage_table(&result.items))
+        }
+        UsageCommand::Refresh { account_id } => {
+            let result = client.usage_refresh(&UsageAccountParams {
+                account_id: account_id.clone(),
+            })?;
+            let target = account_id.as_deref().unwrap_or("all accounts");
+            done(format, &result, &format!("usage refreshed for {target}"))
+        }
+    }
+}
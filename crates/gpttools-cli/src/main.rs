@@ -0,0 +1,103 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+#[path = "commands/account.rs"]
+mod account;
+#[path = "commands/apikey.rs"]
+mod apikey;
+#[path = "commands/login.rs"]
+mod login;
+mod output;
+#[path = "commands/requestlog.rs"]
+mod requestlog;
+mod rpc_client;
+#[path = "commands/status.rs"]
+mod status;
+#[path = "commands/usage.rs"]
+mod usage;
+
+use output::OutputFormat;
+use rpc_client::{Client, HttpTransport, DEFAULT_ADDR};
+
+/// gpttools-service 命令行管理工具。
+#[derive(Debug, Parser)]
+#[command(
+    name = "gpttools",
+    version,
+    about = "Administer a running gpttools-service over RPC"
+)]
+struct Cli {
+    /// Service address (port, host:port or http(s) URL)
+    #[arg(long, global = true, env = "GPTTOOLS_SERVICE_ADDR", default_value = DEFAULT_ADDR)]
+    addr: String,
+    /// RPC token: the service token or a named admin token (gta_...)
+    #[arg(
+        long,
+        global = true,
+        env = "GPTTOOLS_RPC_TOKEN",
+        hide_env_values = true
+    )]
+    token: Option<String>,
+    /// Print raw JSON results instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    /// Request timeout in seconds
+    #[arg(long, global = true, default_value_t = 30)]
+    timeout: u64,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Show service version and storage schema status
+    Status,
+    /// Manage accounts
+    #[command(subcommand)]
+    Account(account::AccountCommand),
+    /// Add accounts via browser or device-code login
+    #[command(subcommand)]
+    Login(login::LoginCommand),
+    /// Manage platform API keys
+    #[command(subcommand)]
+    Apikey(apikey::ApiKeyCommand),
+    /// Read or refresh account usage
+    #[command(subcommand)]
+    Usage(usage::UsageCommand),
+    /// Search request logs
+    #[command(subcommand, visible_alias = "requestlog")]
+    Logs(requestlog::RequestLogCommand),
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let format = if cli.json {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Table
+    };
+    let client = Client::new(HttpTransport::new(
+        &cli.addr,
+        cli.token.as_deref().unwrap_or(""),
+        Duration::from_secs(cli.timeout.max(1)),
+    )?);
+    match cli.command {
+        Command::Status => status::run(&client, format),
+        Command::Account(cmd) => account::run(&client, format, cmd),
+        Command::Login(cmd) => login::run(&client, format, cmd),
+        Command::Apikey(cmd) => apikey::run(&client, format, cmd),
+        Command::Usage(cmd) => usage::run(&client, format, cmd),
+        Command::Logs(cmd) => requestlog::run(&client, format, cmd),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use serde::Serialize;
+
+/// 命令结果的输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// 按列对齐的纯文本表格。
+pub(crate) struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub(crate) fn new(headers: &[&'static str]) -> Self {
+        Self {
+            headers: headers.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (idx, cell) in row.iter().enumerate() {
+                if let Some(width) = widths.get_mut(idx) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
+        let mut out = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| h.to_string()).collect();
+        push_line(&mut out, &headers, &widths);
+        for row in &self.rows {
+            push_line(&mut out, row, &widths);
+        }
+        out
+    }
+}
+
+fn push_line(out: &mut String, cells: &[String], widths: &[usize]) {
+    let mut line = String::new();
+    for (idx, cell) in cells.iter().enumerate() {
+        if idx > 0 {
+            line.push_str("  ");
+        }
+        line.push_str(cell);
+        let pad = widths
+            .get(idx)
+            .copied()
+            .unwrap_or(0)
+            .saturating_sub(cell.chars().count());
+        line.extend(std::iter::repeat_n(' ', pad));
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+pub(crate) fn cell<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub(crate) fn format_ts(ts: i64) -> String {
+    // Unix 秒转 UTC `YYYY-MM-DD HH:MM:SS`，避免为表格输出引入时间库
+    let days = ts.div_euclid(86_400);
+    let secs = ts.rem_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+pub(crate) fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    println!("{text}");
+    Ok(())
+}
+
+/// JSON 模式原样输出结果，表格模式交给调用方渲染。
+pub(crate) fn emit<T: Serialize>(
+    format: OutputFormat,
+    value: &T,
+    table: impl FnOnce(&T) -> Table,
+) -> Result<(), String> {
+    match format {
+        OutputFormat::Json => print_json(value),
+        OutputFormat::Table => {
+            print!("{}", table(value).render());
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn done<T: Serialize>(
+    format: OutputFormat,
+    result: &T,
+    message: &str,
+) -> Result<(), String> {
+    // 变更类命令：JSON 模式输出原始结果，表格模式只打一行摘要
+    match format {
+        OutputFormat::Json => print_json(result),
+        OutputFormat::Table => {
+            println!("{message}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cell, format_ts, Table};
+
+    #[test]
+    fn table_pads_columns_to_widest_cell() {
+        let mut table = Table::new(&["ID", "STATUS"]);
+        table.push(vec!["gk_1".to_string(), "active".to_string()]);
+        table.push(vec!["gk_long_id".to_string(), cell::<String>(None)]);
+        assert_eq!(
+            table.render(),
+            "ID          STATUS\ngk_1        active\ngk_long_id  -\n"
+        );
+    }
+
+    #[test]
+    fn format_ts_renders_utc_datetime() {
+        assert_eq!(format_ts(0), "1970-01-01 00:00:00");
+        assert_eq!(format_ts(1_709_251_199), "2024-02-29 23:59:59");
+    }
+}
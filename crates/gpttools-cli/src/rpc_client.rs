@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use gpttools_core::rpc::client::{RpcClient, RpcClientError, RpcResult, RpcTransport};
+use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse};
+use reqwest::StatusCode;
+use serde_json::Value;
+
+pub(crate) const DEFAULT_ADDR: &str = "localhost:48760";
+const RPC_TOKEN_HEADER: &str = "X-Gpttools-Rpc-Token";
+
+pub(crate) type Client = RpcClient<HttpTransport>;
+
+/// 通过 HTTP 调用 service 的 `/rpc` 入口。
+pub(crate) struct HttpTransport {
+    endpoint: String,
+    token: String,
+    http: reqwest::blocking::Client,
+}
+
+pub(crate) fn rpc_endpoint(addr: &str) -> Result<String, String> {
+    // 把 `48760`、`host:port`、`http(s)://host:port/...` 统一成 RPC 地址
+    let trimmed = addr.trim();
+    if trimmed.is_empty() {
+        return Err("addr is empty".to_string());
+    }
+    let (scheme, rest) = if let Some(rest) = trimmed.strip_prefix("https://") {
+        ("https", rest)
+    } else if let Some(rest) = trimmed.strip_prefix("http://") {
+        ("http", rest)
+    } else {
+        ("http", trimmed)
+    };
+    let host = rest.split('/').next().unwrap_or(rest);
+    if host.is_empty() {
+        return Err(format!("invalid addr: {addr}"));
+    }
+    // 中文注释：只写端口时按本机处理，与桌面端地址输入框的习惯一致。
+    let host = if host.bytes().all(|b| b.is_ascii_digit()) {
+        format!("localhost:{host}")
+    } else {
+        host.to_string()
+    };
+    Ok(format!("{scheme}://{host}/rpc"))
+}
+
+impl HttpTransport {
+    pub(crate) fn new(addr: &str, token: &str, timeout: Duration) -> Result<Self, String> {
+        let token = token.trim();
+        if token.is_empty() {
+            return Err("missing rpc token (use --token or GPTTOOLS_RPC_TOKEN)".to_string());
+        }
+        let http = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| format!("build http client failed: {err}"))?;
+        Ok(Self {
+            endpoint: rpc_endpoint(addr)?,
+            token: token.to_string(),
+            http,
+        })
+    }
+}
+
+impl RpcTransport for HttpTransport {
+    fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse> {
+        // 鉴权失败与 HTTP 错误统一转成带方法名的传输错误
+        let method = request.method.as_str();
+        let fail = |message: String| RpcClientError::Transport(format!("{method}: {message}"));
+        let resp = self
+            .http
+            .post(&self.endpoint)
+            .header(RPC_TOKEN_HEADER, &self.token)
+            .json(request)
+            .send()
+            .map_err(|err| fail(format!("cannot reach service at {}: {err}", self.endpoint)))?;
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        match status {
+            StatusCode::UNAUTHORIZED => {
+                return Err(fail("unauthorized (check --token)".to_string()))
+            }
+            StatusCode::FORBIDDEN => {
+                let required = serde_json::from_str::<Value>(&body).ok().and_then(|v| {
+                    v.get("requiredScope")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                });
+                return Err(fail(match required {
+                    Some(scope) => format!("token lacks scope {scope}"),
+                    None => format!("forbidden: {}", body.trim()),
+                }));
+            }
+            status if !status.is_success() => {
+                return Err(fail(format!("http {status}: {}", body.trim())));
+            }
+            _ => {}
+        }
+        serde_json::from_str(&body).map_err(|err| fail(format!("invalid rpc response: {err}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rpc_endpoint;
+
+    #[test]
+    fn rpc_endpoint_accepts_port_host_and_url_forms() {
+        assert_eq!(rpc_endpoint("48760").unwrap(), "http://localhost:48760/rpc");
+        assert_eq!(
+            rpc_endpoint("127.0.0.1:5000").unwrap(),
+            "http://127.0.0.1:5000/rpc"
+        );
+        assert_eq!(
+            rpc_endpoint("https://box.lan:48760/rpc").unwrap(),
+            "https://box.lan:48760/rpc"
+        );
+        assert!(rpc_endpoint("  ").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::process::{Command, Output};
+
+fn run_cli(token: &str, args: &[&str]) -> Output {
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let output = Command::new(env!("CARGO_BIN_EXE_gpttools"))
+        .args(["--addr", server.addr.as_str(), "--token", token])
+        .args(args)
+        .output()
+        .expect("run gpttools");
+    server.join();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn cli_manages_api_keys_and_reports_rpc_errors() {
+    let dir = std::env::temp_dir().join(format!("gpttools-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    std::env::set_var("GPTTOOLS_DB_PATH", dir.join("gpttools.db"));
+    let token = gpttools_service::rpc_auth_token();
+
+    let output = run_cli(token, &["apikey", "create", "--name", "ci"]);
+    assert!(output.status.success(), "{output:?}");
+    let text = stdout(&output);
+    assert!(text.starts_with("ID"), "{text}");
+    assert!(text.contains("KEY"), "{text}");
+
+    let output = run_cli(token, &["--json", "apikey", "list"]);
+    assert!(output.status.success(), "{output:?}");
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    let items = value["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["name"], "ci");
+    let key_id = items[0]["id"].as_str().expect("id").to_string();
+
+    let output = run_cli(token, &["apikey", "disable", &key_id]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(stdout(&output).trim(), format!("disabled {key_id}"));
+
+    let output = run_cli(token, &["account", "list"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(stdout(&output).starts_with("ID  LABEL"));
+
+    let output = run_cli(token, &["--json", "status"]);
+    assert!(output.status.success(), "{output:?}");
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    assert_eq!(value["serverName"], "gpttools-service");
+    assert!(value["storage"]["pending"]
+        .as_array()
+        .expect("pending")
+        .is_empty());
+
+    let output = run_cli(token, &["logs", "search", "status:500", "--limit", "5"]);
+    assert!(output.status.success(), "{output:?}");
+
+    // 业务失败与鉴权失败都以非零退出码和 stderr 报告
+    let output = run_cli(
+        token,
+        &["account", "proxy", "acc-missing", "ftp://proxy:21"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("account/proxy/set"));
+
+    let output = run_cli("wrong-token", &["account", "list"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unauthorized"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
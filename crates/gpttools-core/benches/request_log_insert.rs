@@ -0,0 +1,71 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use gpttools_core::storage::{now_ts, RequestLog, Storage};
+use std::path::PathBuf;
+
+const LOGS_PER_ITER: usize = 64;
+
+fn bench_db_path(name: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-bench-{}-{name}", std::process::id()));
+    let _ = std::fs::create_dir_all(&dir);
+    let path = dir.join("gpttools.db");
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn sample_logs() -> Vec<RequestLog> {
+    (0..LOGS_PER_ITER)
+        .map(|idx| RequestLog {
+            key_id: Some(format!("gk_bench_{}", idx % 8)),
+            request_path: "/v1/responses".to_string(),
+            method: "POST".to_string(),
+            model: Some("gpt-5.1-codex".to_string()),
+            reasoning_effort: Some("medium".to_string()),
+            upstream_url: Some("https://chatgpt.com/backend-api/codex/responses".to_string()),
+            status_code: Some(200),
+            error: None,
+            created_at: now_ts(),
+        })
+        .collect()
+}
+
+fn request_log_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("request_log_insert");
+
+    let single_path = bench_db_path("single");
+    let single = Storage::open(&single_path).expect("open db");
+    single.init().expect("init schema");
+    group.bench_function("row_per_commit", |b| {
+        b.iter_batched(
+            sample_logs,
+            |logs| {
+                for log in &logs {
+                    single.insert_request_log(log).expect("insert log");
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let batch_path = bench_db_path("batched");
+    let mut batched = Storage::open(&batch_path).expect("open db");
+    batched.init().expect("init schema");
+    group.bench_function("batched_commit", |b| {
+        b.iter_batched(
+            sample_logs,
+            |logs| batched.insert_request_logs(&logs).expect("insert logs"),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+    drop(single);
+    drop(batched);
+    for path in [single_path, batch_path] {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+criterion_group!(benches, request_log_insert);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+CREATE INDEX IF NOT EXISTS idx_events_account_id_id
+  ON events(account_id, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_events_type_id
+  ON events(type, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_events_created_at
+  ON events(created_at DESC);
@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN plan_type TEXT;
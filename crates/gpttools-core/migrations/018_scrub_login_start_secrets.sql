@@ -0,0 +1,10 @@
+UPDATE events
+SET message = json_remove(message, '$.code_verifier')
+WHERE type = 'login_start'
+  AND json_valid(message)
+  AND json_extract(message, '$.code_verifier') IS NOT NULL;
+
+UPDATE events
+SET message = '[REDACTED]'
+WHERE type = 'login_start'
+  AND message LIKE '%code_verifier%';
@@ -0,0 +1 @@
+ALTER TABLE login_sessions ADD COLUMN reauth_account_id TEXT;
@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS idx_tokens_refresh_token
+  ON tokens(refresh_token);
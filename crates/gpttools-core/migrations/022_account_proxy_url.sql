@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN proxy_url TEXT;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS admin_tokens (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  token_hash TEXT NOT NULL UNIQUE,
+  scope TEXT NOT NULL CHECK (scope IN ('read_only', 'key_management', 'account_management', 'full')),
+  created_at INTEGER NOT NULL,
+  last_used_at INTEGER
+);
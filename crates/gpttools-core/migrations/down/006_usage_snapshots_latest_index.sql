@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_usage_snapshots_account_captured_id;
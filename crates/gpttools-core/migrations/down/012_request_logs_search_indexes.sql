@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_request_logs_status_code_created_at;
+
+DROP INDEX IF EXISTS idx_request_logs_method_created_at;
+
+DROP INDEX IF EXISTS idx_request_logs_key_id_created_at;
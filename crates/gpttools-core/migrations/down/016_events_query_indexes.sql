@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_events_account_id_id;
+
+DROP INDEX IF EXISTS idx_events_type_id;
+
+DROP INDEX IF EXISTS idx_events_created_at;
@@ -0,0 +1 @@
+ALTER TABLE login_sessions DROP COLUMN reauth_account_id;
@@ -0,0 +1 @@
+ALTER TABLE login_sessions DROP COLUMN workspace_selection;
@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod redact;
 pub mod rpc;
 pub mod storage;
 pub mod usage;
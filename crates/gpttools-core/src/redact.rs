@@ -0,0 +1,212 @@
+pub const REDACTED: &str = "[REDACTED]";
+
+// 中文注释：键名按小写匹配；code 只在 query/kv 形式（code=）下视为授权码，
+// JSON 错误体里的 "code" 多是错误码，误脱敏会让排障信息全部丢失。
+const SENSITIVE_KEYS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "api_key_access_token",
+    "code_verifier",
+    "client_secret",
+    "session_token",
+    "password",
+    "passphrase",
+    "api_key",
+    "apikey",
+    "token",
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-gpttools-rpc-token",
+];
+const KV_ONLY_KEYS: &[&str] = &["code"];
+const LINE_VALUE_KEYS: &[&str] = &["cookie", "set-cookie"];
+
+/// 脱敏文本中的 token、verifier、cookie 与认证头，供事件、日志写入前统一调用。
+pub fn redact_secrets(text: &str) -> String {
+    let keyed = redact_keyed_values(text);
+    redact_bare_credentials(&keyed)
+}
+
+/// 可选字段版本，便于直接包裹 `Option<&str>`。
+pub fn redact_optional(text: Option<&str>) -> Option<String> {
+    text.map(redact_secrets)
+}
+
+fn is_key_char(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || ch == b'_' || ch == b'-'
+}
+
+fn is_value_end(ch: u8) -> bool {
+    ch.is_ascii_whitespace() || matches!(ch, b'&' | b',' | b';' | b'"' | b'\'' | b'}' | b']' | b')')
+}
+
+fn redact_keyed_values(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let lower = text.to_ascii_lowercase();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let keys = SENSITIVE_KEYS
+        .iter()
+        .map(|key| (*key, false))
+        .chain(KV_ONLY_KEYS.iter().map(|key| (*key, true)));
+    for (key, kv_only) in keys {
+        let mut search_from = 0;
+        while let Some(found) = lower[search_from..].find(key) {
+            let start = search_from + found;
+            let end = start + key.len();
+            search_from = end;
+            if start > 0 && is_key_char(bytes[start - 1]) {
+                continue;
+            }
+            if end < bytes.len() && is_key_char(bytes[end]) {
+                continue;
+            }
+            if let Some(range) = value_range(bytes, start, end, kv_only, LINE_VALUE_KEYS.contains(&key))
+            {
+                ranges.push(range);
+            }
+        }
+    }
+    apply_ranges(text, ranges)
+}
+
+fn value_range(
+    bytes: &[u8],
+    key_start: usize,
+    key_end: usize,
+    kv_only: bool,
+    line_value: bool,
+) -> Option<(usize, usize)> {
+    let quoted_key = key_start > 0 && bytes[key_start - 1] == b'"';
+    let mut idx = key_end;
+    if quoted_key {
+        if bytes.get(idx) != Some(&b'"') {
+            return None;
+        }
+        idx += 1;
+    }
+    while idx < bytes.len() && bytes[idx] == b' ' {
+        idx += 1;
+    }
+    match bytes.get(idx) {
+        Some(b'=') => {}
+        Some(b':') if !kv_only => {}
+        _ => return None,
+    }
+    idx += 1;
+    while idx < bytes.len() && bytes[idx] == b' ' {
+        idx += 1;
+    }
+    if idx >= bytes.len() {
+        return None;
+    }
+    if bytes[idx] == b'"' {
+        let value_start = idx + 1;
+        let mut cursor = value_start;
+        while cursor < bytes.len() {
+            match bytes[cursor] {
+                b'\\' => cursor += 2,
+                b'"' => break,
+                _ => cursor += 1,
+            }
+        }
+        let value_end = cursor.min(bytes.len());
+        return (value_end > value_start).then_some((value_start, value_end));
+    }
+    // 中文注释：JSON 里的 null/数字/对象不是凭据，保持原样，避免把结构信息也抹掉。
+    if quoted_key && !bytes[idx].is_ascii_alphabetic() {
+        return None;
+    }
+    let value_start = idx;
+    let mut cursor = idx;
+    if line_value {
+        while cursor < bytes.len() && !matches!(bytes[cursor], b'\r' | b'\n' | b'"') {
+            cursor += 1;
+        }
+    } else {
+        let rest = &bytes[idx..];
+        for scheme in ["bearer ", "basic "] {
+            if rest.len() > scheme.len() && rest[..scheme.len()].eq_ignore_ascii_case(scheme.as_bytes())
+            {
+                cursor += scheme.len();
+                break;
+            }
+        }
+        while cursor < bytes.len() && !is_value_end(bytes[cursor]) {
+            cursor += 1;
+        }
+    }
+    let value = &bytes[value_start..cursor];
+    if value.is_empty() || value.eq_ignore_ascii_case(b"null") {
+        return None;
+    }
+    Some((value_start, cursor))
+}
+
+fn apply_ranges(text: &str, mut ranges: Vec<(usize, usize)>) -> String {
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+    ranges.sort_unstable();
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end) in ranges {
+        if end <= cursor {
+            continue;
+        }
+        let start = start.max(cursor);
+        out.push_str(&text[cursor..start]);
+        out.push_str(REDACTED);
+        cursor = end;
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+fn is_token_char(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, b'_' | b'-' | b'.' | b'~' | b'+' | b'/' | b'=')
+}
+
+fn redact_bare_credentials(text: &str) -> String {
+    // 中文注释：兜底处理没有键名的凭据（Bearer 头、JWT、sk- 平台 Key），例如上游错误体原样回显的请求头。
+    let bytes = text.as_bytes();
+    let lower = text.to_ascii_lowercase();
+    let mut ranges = Vec::new();
+
+    let mut search_from = 0;
+    while let Some(found) = lower[search_from..].find("bearer ") {
+        let value_start = search_from + found + "bearer ".len();
+        let mut cursor = value_start;
+        while cursor < bytes.len() && is_token_char(bytes[cursor]) {
+            cursor += 1;
+        }
+        if cursor > value_start {
+            ranges.push((value_start, cursor));
+        }
+        search_from = value_start;
+    }
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let at_boundary = idx == 0 || !is_token_char(bytes[idx - 1]);
+        if at_boundary && (bytes[idx..].starts_with(b"eyJ") || bytes[idx..].starts_with(b"sk-")) {
+            let mut cursor = idx;
+            while cursor < bytes.len() && is_token_char(bytes[cursor]) {
+                cursor += 1;
+            }
+            let token = &bytes[idx..cursor];
+            let is_jwt = token.starts_with(b"eyJ") && token.iter().filter(|b| **b == b'.').count() >= 2;
+            let is_secret_key = token.starts_with(b"sk-") && token.len() >= 20;
+            if is_jwt || is_secret_key {
+                ranges.push((idx, cursor));
+            }
+            idx = cursor.max(idx + 1);
+            continue;
+        }
+        idx += 1;
+    }
+    apply_ranges(text, ranges)
+}
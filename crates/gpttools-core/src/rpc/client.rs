@@ -0,0 +1,313 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::methods;
+use super::params::{
+    AccountExportParams, AccountIdParams, AccountImportParams, AccountProxySetParams,
+    AccountReauthParams, AccountRestoreParams, AccountUpdateParams, AccountWorkspaceAddParams,
+    AdminTokenCreateParams, ApiKeyCreateParams, ApiKeyUpdateModelParams, EventListParams, IdParams,
+    LoginCompleteParams, LoginIdParams, LoginStartParams, RequestLogListParams, UsageAccountParams,
+    UsageHistoryParams,
+};
+use super::types::{
+    AccountExportResult, AccountImportResult, AccountListResult, AccountProxySetResult,
+    AccountRestoreResult, AccountWorkspaceListResult, AdminTokenCreateResult, AdminTokenListResult,
+    ApiKeyCreateResult, ApiKeyListResult, ApiKeyModelListResult, EventListResult, InitializeResult,
+    JsonRpcRequest, JsonRpcResponse, LoginStartResult, LoginStatusResult, OkResult,
+    RequestLogListResult, StorageInfoResult, UsageHistoryResult, UsageListResult, UsageReadResult,
+};
+
+/// 客户端调用失败的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcClientError {
+    /// 请求没有拿到 RPC 响应（连接失败、鉴权失败、HTTP 错误等），文本由传输层给出。
+    Transport(String),
+    /// 服务端在 result 里返回了业务错误。
+    Rpc { method: String, message: String },
+    /// result 与方法约定的结构对不上。
+    Decode { method: String, message: String },
+}
+
+impl fmt::Display for RpcClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(message) => f.write_str(message),
+            Self::Rpc { method, message } => write!(f, "{method}: {message}"),
+            Self::Decode { method, message } => {
+                write!(f, "{method}: unexpected result: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RpcClientError {}
+
+// 中文注释：服务端与桌面端都用 `Result<_, String>` 传错误，提供转换后调用方可以直接用 `?`。
+impl From<RpcClientError> for String {
+    fn from(err: RpcClientError) -> Self {
+        err.to_string()
+    }
+}
+
+pub type RpcResult<T> = Result<T, RpcClientError>;
+
+/// 把一次 JSON-RPC 请求送到服务端并取回响应；HTTP、进程内调用或测试桩都可以实现。
+pub trait RpcTransport {
+    fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse>;
+}
+
+impl<T: RpcTransport + ?Sized> RpcTransport for &T {
+    fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse> {
+        (**self).send(request)
+    }
+}
+
+impl<T: RpcTransport + ?Sized> RpcTransport for Box<T> {
+    fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse> {
+        (**self).send(request)
+    }
+}
+
+/// 按方法名发请求并解码结果；各方法分组 trait 都建立在它之上。
+pub trait RpcCall {
+    /// 发送请求并原样返回 result，不做业务错误判断。
+    fn call_value(&self, method: &str, params: Option<Value>) -> RpcResult<Value>;
+
+    fn call<R: DeserializeOwned>(&self, method: &str, params: Option<Value>) -> RpcResult<R> {
+        let result = self.call_value(method, params)?;
+        if let Some(message) = result_error(&result) {
+            return Err(RpcClientError::Rpc {
+                method: method.to_string(),
+                message,
+            });
+        }
+        decode(method, result)
+    }
+}
+
+fn decode<R: DeserializeOwned>(method: &str, result: Value) -> RpcResult<R> {
+    serde_json::from_value(result).map_err(|err| RpcClientError::Decode {
+        method: method.to_string(),
+        message: err.to_string(),
+    })
+}
+
+pub struct RpcClient<T> {
+    transport: T,
+    next_id: AtomicU64,
+}
+
+impl<T: RpcTransport> RpcClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+pub fn result_error(result: &Value) -> Option<String> {
+    // 业务失败以 `{ "error": .. }` 或 `{ "ok": false }` 形式放在 result 里
+    if let Some(err) = result.get("error").filter(|v| !v.is_null()) {
+        return Some(match err.as_str() {
+            Some(text) => text.to_string(),
+            None => err.to_string(),
+        });
+    }
+    if result.get("ok").and_then(Value::as_bool) == Some(false) {
+        return Some("request failed".to_string());
+    }
+    None
+}
+
+impl<T: RpcTransport> RpcCall for RpcClient<T> {
+    fn call_value(&self, method: &str, params: Option<Value>) -> RpcResult<Value> {
+        let request = JsonRpcRequest {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method: method.to_string(),
+            params,
+        };
+        Ok(self.transport.send(&request)?.result)
+    }
+}
+
+fn to_params<P: Serialize>(params: &P) -> Option<Value> {
+    // 参数结构体都是纯数据，序列化不会失败
+    serde_json::to_value(params).ok()
+}
+
+pub trait ServiceRpc: RpcCall {
+    fn initialize(&self) -> RpcResult<InitializeResult> {
+        self.call(methods::INITIALIZE, None)
+    }
+
+    fn storage_info(&self) -> RpcResult<StorageInfoResult> {
+        self.call(methods::STORAGE_INFO, None)
+    }
+
+    fn events_list(&self, params: &EventListParams) -> RpcResult<EventListResult> {
+        self.call(methods::EVENTS_LIST, to_params(params))
+    }
+}
+
+pub trait AccountRpc: RpcCall {
+    fn account_list(&self) -> RpcResult<AccountListResult> {
+        self.call(methods::ACCOUNT_LIST, None)
+    }
+
+    fn account_delete(&self, params: &AccountIdParams) -> RpcResult<OkResult> {
+        self.call(methods::ACCOUNT_DELETE, to_params(params))
+    }
+
+    fn account_update(&self, params: &AccountUpdateParams) -> RpcResult<OkResult> {
+        self.call(methods::ACCOUNT_UPDATE, to_params(params))
+    }
+
+    fn account_proxy_set(
+        &self,
+        params: &AccountProxySetParams,
+    ) -> RpcResult<AccountProxySetResult> {
+        self.call(methods::ACCOUNT_PROXY_SET, to_params(params))
+    }
+
+    fn account_import(&self, params: &AccountImportParams) -> RpcResult<AccountImportResult> {
+        self.call(methods::ACCOUNT_IMPORT, to_params(params))
+    }
+
+    fn account_export(&self, params: &AccountExportParams) -> RpcResult<AccountExportResult> {
+        self.call(methods::ACCOUNT_EXPORT, to_params(params))
+    }
+
+    fn account_restore(&self, params: &AccountRestoreParams) -> RpcResult<AccountRestoreResult> {
+        self.call(methods::ACCOUNT_RESTORE, to_params(params))
+    }
+
+    fn account_workspace_list(
+        &self,
+        params: &AccountIdParams,
+    ) -> RpcResult<AccountWorkspaceListResult> {
+        self.call(methods::ACCOUNT_WORKSPACE_LIST, to_params(params))
+    }
+
+    fn account_workspace_add(
+        &self,
+        params: &AccountWorkspaceAddParams,
+    ) -> RpcResult<AccountWorkspaceListResult> {
+        self.call(methods::ACCOUNT_WORKSPACE_ADD, to_params(params))
+    }
+}
+
+pub trait LoginRpc: RpcCall {
+    fn login_start(&self, params: &LoginStartParams) -> RpcResult<LoginStartResult> {
+        self.call(methods::ACCOUNT_LOGIN_START, to_params(params))
+    }
+
+    fn login_status(&self, params: &LoginIdParams) -> RpcResult<LoginStatusResult> {
+        // 中文注释：登录失败时 status=failed 且带 error 字段，这是正常的查询结果；按业务错误处理会让调用方拿不到状态。
+        let result = self.call_value(methods::ACCOUNT_LOGIN_STATUS, to_params(params))?;
+        decode(methods::ACCOUNT_LOGIN_STATUS, result)
+    }
+
+    fn login_cancel(&self, params: &LoginIdParams) -> RpcResult<OkResult> {
+        self.call(methods::ACCOUNT_LOGIN_CANCEL, to_params(params))
+    }
+
+    fn login_complete(&self, params: &LoginCompleteParams) -> RpcResult<OkResult> {
+        self.call(methods::ACCOUNT_LOGIN_COMPLETE, to_params(params))
+    }
+
+    fn account_reauth(&self, params: &AccountReauthParams) -> RpcResult<LoginStartResult> {
+        self.call(methods::ACCOUNT_REAUTH, to_params(params))
+    }
+}
+
+pub trait UsageRpc: RpcCall {
+    fn usage_read(&self, params: &UsageAccountParams) -> RpcResult<UsageReadResult> {
+        self.call(methods::ACCOUNT_USAGE_READ, to_params(params))
+    }
+
+    fn usage_list(&self) -> RpcResult<UsageListResult> {
+        self.call(methods::ACCOUNT_USAGE_LIST, None)
+    }
+
+    fn usage_history(&self, params: &UsageHistoryParams) -> RpcResult<UsageHistoryResult> {
+        self.call(methods::ACCOUNT_USAGE_HISTORY, to_params(params))
+    }
+
+    fn usage_refresh(&self, params: &UsageAccountParams) -> RpcResult<OkResult> {
+        self.call(methods::ACCOUNT_USAGE_REFRESH, to_params(params))
+    }
+}
+
+pub trait ApiKeyRpc: RpcCall {
+    fn apikey_list(&self) -> RpcResult<ApiKeyListResult> {
+        self.call(methods::APIKEY_LIST, None)
+    }
+
+    fn apikey_create(&self, params: &ApiKeyCreateParams) -> RpcResult<ApiKeyCreateResult> {
+        self.call(methods::APIKEY_CREATE, to_params(params))
+    }
+
+    fn apikey_models(&self) -> RpcResult<ApiKeyModelListResult> {
+        self.call(methods::APIKEY_MODELS, None)
+    }
+
+    fn apikey_update_model(&self, params: &ApiKeyUpdateModelParams) -> RpcResult<OkResult> {
+        self.call(methods::APIKEY_UPDATE_MODEL, to_params(params))
+    }
+
+    fn apikey_delete(&self, params: &IdParams) -> RpcResult<OkResult> {
+        self.call(methods::APIKEY_DELETE, to_params(params))
+    }
+
+    fn apikey_disable(&self, params: &IdParams) -> RpcResult<OkResult> {
+        self.call(methods::APIKEY_DISABLE, to_params(params))
+    }
+
+    fn apikey_enable(&self, params: &IdParams) -> RpcResult<OkResult> {
+        self.call(methods::APIKEY_ENABLE, to_params(params))
+    }
+}
+
+pub trait RequestLogRpc: RpcCall {
+    fn requestlog_list(&self, params: &RequestLogListParams) -> RpcResult<RequestLogListResult> {
+        self.call(methods::REQUESTLOG_LIST, to_params(params))
+    }
+
+    fn requestlog_clear(&self) -> RpcResult<OkResult> {
+        self.call(methods::REQUESTLOG_CLEAR, None)
+    }
+}
+
+pub trait AdminTokenRpc: RpcCall {
+    fn admin_token_list(&self) -> RpcResult<AdminTokenListResult> {
+        self.call(methods::ADMIN_TOKEN_LIST, None)
+    }
+
+    fn admin_token_create(
+        &self,
+        params: &AdminTokenCreateParams,
+    ) -> RpcResult<AdminTokenCreateResult> {
+        self.call(methods::ADMIN_TOKEN_CREATE, to_params(params))
+    }
+
+    fn admin_token_revoke(&self, params: &IdParams) -> RpcResult<OkResult> {
+        self.call(methods::ADMIN_TOKEN_REVOKE, to_params(params))
+    }
+}
+
+impl<C: RpcCall + ?Sized> ServiceRpc for C {}
+impl<C: RpcCall + ?Sized> AccountRpc for C {}
+impl<C: RpcCall + ?Sized> LoginRpc for C {}
+impl<C: RpcCall + ?Sized> UsageRpc for C {}
+impl<C: RpcCall + ?Sized> ApiKeyRpc for C {}
+impl<C: RpcCall + ?Sized> RequestLogRpc for C {}
+impl<C: RpcCall + ?Sized> AdminTokenRpc for C {}
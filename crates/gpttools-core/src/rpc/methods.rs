@@ -0,0 +1,79 @@
+// 中文注释：RPC 方法名只在这里定义，服务端分发、权限表与各客户端都引用同一组常量；各处手写字符串迟早会对不上。
+
+pub const INITIALIZE: &str = "initialize";
+
+pub const ACCOUNT_LIST: &str = "account/list";
+pub const ACCOUNT_DELETE: &str = "account/delete";
+pub const ACCOUNT_UPDATE: &str = "account/update";
+pub const ACCOUNT_PROXY_SET: &str = "account/proxy/set";
+pub const ACCOUNT_IMPORT: &str = "account/import";
+pub const ACCOUNT_EXPORT: &str = "account/export";
+pub const ACCOUNT_RESTORE: &str = "account/restore";
+pub const ACCOUNT_WORKSPACE_LIST: &str = "account/workspace/list";
+pub const ACCOUNT_WORKSPACE_ADD: &str = "account/workspace/add";
+
+pub const ACCOUNT_LOGIN_START: &str = "account/login/start";
+pub const ACCOUNT_LOGIN_STATUS: &str = "account/login/status";
+pub const ACCOUNT_LOGIN_CANCEL: &str = "account/login/cancel";
+pub const ACCOUNT_LOGIN_COMPLETE: &str = "account/login/complete";
+pub const ACCOUNT_REAUTH: &str = "account/reauth";
+
+pub const ACCOUNT_USAGE_READ: &str = "account/usage/read";
+pub const ACCOUNT_USAGE_LIST: &str = "account/usage/list";
+pub const ACCOUNT_USAGE_HISTORY: &str = "account/usage/history";
+pub const ACCOUNT_USAGE_REFRESH: &str = "account/usage/refresh";
+
+pub const APIKEY_LIST: &str = "apikey/list";
+pub const APIKEY_CREATE: &str = "apikey/create";
+pub const APIKEY_MODELS: &str = "apikey/models";
+pub const APIKEY_UPDATE_MODEL: &str = "apikey/updateModel";
+pub const APIKEY_DELETE: &str = "apikey/delete";
+pub const APIKEY_DISABLE: &str = "apikey/disable";
+pub const APIKEY_ENABLE: &str = "apikey/enable";
+
+pub const REQUESTLOG_LIST: &str = "requestlog/list";
+pub const REQUESTLOG_CLEAR: &str = "requestlog/clear";
+
+pub const EVENTS_LIST: &str = "events/list";
+pub const STORAGE_INFO: &str = "storage/info";
+
+pub const ADMIN_TOKEN_LIST: &str = "admin/token/list";
+pub const ADMIN_TOKEN_CREATE: &str = "admin/token/create";
+pub const ADMIN_TOKEN_REVOKE: &str = "admin/token/revoke";
+
+/// 服务端处理的全部方法，用于分发/权限覆盖检查。
+pub const ALL: &[&str] = &[
+    INITIALIZE,
+    ACCOUNT_LIST,
+    ACCOUNT_DELETE,
+    ACCOUNT_UPDATE,
+    ACCOUNT_PROXY_SET,
+    ACCOUNT_IMPORT,
+    ACCOUNT_EXPORT,
+    ACCOUNT_RESTORE,
+    ACCOUNT_WORKSPACE_LIST,
+    ACCOUNT_WORKSPACE_ADD,
+    ACCOUNT_LOGIN_START,
+    ACCOUNT_LOGIN_STATUS,
+    ACCOUNT_LOGIN_CANCEL,
+    ACCOUNT_LOGIN_COMPLETE,
+    ACCOUNT_REAUTH,
+    ACCOUNT_USAGE_READ,
+    ACCOUNT_USAGE_LIST,
+    ACCOUNT_USAGE_HISTORY,
+    ACCOUNT_USAGE_REFRESH,
+    APIKEY_LIST,
+    APIKEY_CREATE,
+    APIKEY_MODELS,
+    APIKEY_UPDATE_MODEL,
+    APIKEY_DELETE,
+    APIKEY_DISABLE,
+    APIKEY_ENABLE,
+    REQUESTLOG_LIST,
+    REQUESTLOG_CLEAR,
+    EVENTS_LIST,
+    STORAGE_INFO,
+    ADMIN_TOKEN_LIST,
+    ADMIN_TOKEN_CREATE,
+    ADMIN_TOKEN_REVOKE,
+];
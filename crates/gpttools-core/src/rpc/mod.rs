@@ -1 +1,4 @@
+pub mod client;
+pub mod methods;
+pub mod params;
 pub mod types;
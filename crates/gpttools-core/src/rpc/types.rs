@@ -26,7 +26,11 @@ pub struct AccountSummary {
     pub id: String,
     pub label: String,
     pub group_name: Option<String>,
+    pub plan_type: Option<String>,
     pub sort: i64,
+    /// 账号出站代理（密码已脱敏）；直连账号不输出该字段。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +38,69 @@ pub struct AccountListResult {
     pub items: Vec<AccountSummary>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountImportItem {
+    pub source: String,
+    pub account_id: Option<String>,
+    pub label: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountImportResult {
+    pub items: Vec<AccountImportItem>,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountWorkspaceItem {
+    pub workspace_id: String,
+    pub name: Option<String>,
+    pub plan_type: Option<String>,
+    pub is_personal: bool,
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountWorkspaceListResult {
+    pub items: Vec<AccountWorkspaceItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountExportResult {
+    pub bundle: String,
+    pub account_count: usize,
+    pub token_count: usize,
+    pub api_key_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRestoreConflict {
+    pub kind: String,
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRestoreResult {
+    pub mode: String,
+    pub accounts_restored: usize,
+    pub tokens_restored: usize,
+    pub api_keys_restored: usize,
+    pub conflicts: Vec<AccountRestoreConflict>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceAuthInfo {
@@ -41,6 +108,12 @@ pub struct DeviceAuthInfo {
     pub token_url: String,
     pub verification_url: String,
     pub redirect_uri: String,
+    #[serde(default)]
+    pub user_code: Option<String>,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,6 +130,42 @@ pub struct LoginStartResult {
     pub device: Option<DeviceAuthInfo>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginDeviceProgress {
+    pub user_code: String,
+    pub verification_url: String,
+    pub interval_secs: u64,
+    pub expires_at: i64,
+    pub last_polled_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginStatusResult {
+    /// `pending`、`success`、`failed`、`expired`、`cancelled` 或 `unknown`。
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<LoginDeviceProgress>,
+}
+
+/// 只回报成败的变更类方法结果。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OkResult {
+    pub ok: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProxySetResult {
+    pub ok: bool,
+    pub proxy_url: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageSnapshotResult {
@@ -68,6 +177,12 @@ pub struct UsageSnapshotResult {
     pub secondary_window_minutes: Option<i64>,
     pub secondary_resets_at: Option<i64>,
     pub credits_json: Option<String>,
+    pub has_credits: Option<bool>,
+    pub credits_unlimited: Option<bool>,
+    pub credits_balance: Option<f64>,
+    pub plan_type: Option<String>,
+    pub availability: Option<String>,
+    pub availability_reason: Option<String>,
     pub captured_at: Option<i64>,
 }
 
@@ -81,6 +196,38 @@ pub struct UsageListResult {
     pub items: Vec<UsageSnapshotResult>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageHistoryPoint {
+    pub captured_at: i64,
+    pub used_percent: Option<f64>,
+    pub secondary_used_percent: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageWindowTrend {
+    pub used_percent: Option<f64>,
+    pub resets_at: Option<i64>,
+    pub burn_rate_per_hour: Option<f64>,
+    pub projected_exhausted_at: Option<i64>,
+    pub exhausts_before_reset: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageHistoryItem {
+    pub account_id: String,
+    pub points: Vec<UsageHistoryPoint>,
+    pub primary: UsageWindowTrend,
+    pub secondary: UsageWindowTrend,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageHistoryResult {
+    pub items: Vec<UsageHistoryItem>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiKeySummary {
@@ -110,6 +257,30 @@ pub struct ApiKeyCreateResult {
     pub key: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminTokenSummary {
+    pub id: String,
+    pub name: String,
+    pub scope: String,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminTokenListResult {
+    pub items: Vec<AdminTokenSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminTokenCreateResult {
+    pub id: String,
+    pub name: String,
+    pub scope: String,
+    pub token: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelOption {
@@ -141,6 +312,50 @@ pub struct RequestLogListResult {
     pub items: Vec<RequestLogSummary>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSummary {
+    pub id: i64,
+    pub account_id: Option<String>,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventListResult {
+    pub items: Vec<EventSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageMigrationItem {
+    pub version: String,
+    pub applied_at: i64,
+    pub reversible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageInfoResult {
+    pub path: Option<String>,
+    pub journal_mode: String,
+    pub current_version: Option<String>,
+    pub latest_version: String,
+    pub applied: Vec<StorageMigrationItem>,
+    pub pending: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRollbackResult {
+    /// 按回滚顺序（新到旧）列出被撤销的迁移。
+    pub reverted: Vec<String>,
+    pub current_version: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::AccountSummary;
@@ -151,17 +366,27 @@ mod tests {
             id: "acc-1".to_string(),
             label: "主账号".to_string(),
             group_name: Some("TEAM".to_string()),
+            plan_type: Some("pro".to_string()),
             sort: 10,
+            proxy_url: None,
         };
 
         let value = serde_json::to_value(summary).expect("serialize account summary");
         let obj = value.as_object().expect("account summary object");
 
-        for key in ["id", "label", "groupName", "sort"] {
+        for key in ["id", "label", "groupName", "planType", "sort"] {
             assert!(obj.contains_key(key), "missing key: {key}");
         }
 
-        for key in ["workspaceId", "workspaceName", "note", "tags", "status", "updatedAt"] {
+        for key in [
+            "workspaceId",
+            "workspaceName",
+            "note",
+            "tags",
+            "status",
+            "updatedAt",
+            "proxyUrl",
+        ] {
             assert!(!obj.contains_key(key), "unexpected key: {key}");
         }
     }
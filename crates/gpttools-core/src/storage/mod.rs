@@ -1,10 +1,23 @@
-use rusqlite::{Connection, Result};
+use crate::redact::{redact_optional, redact_secrets};
+use rusqlite::{Connection, OptionalExtension, Result};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::time::Duration;
 
 mod request_log_query;
 
+static POOL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 本进程内账号池（accounts/tokens/usage_snapshots）的写入代数，上层缓存据此判断是否失效。
+pub fn pool_generation() -> u64 {
+    POOL_GENERATION.load(Ordering::Acquire)
+}
+
+fn mark_pool_changed() {
+    POOL_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
 #[derive(Debug, Clone)]
 pub struct Account {
     pub id: String,
@@ -13,6 +26,8 @@ pub struct Account {
     pub chatgpt_account_id: Option<String>,
     pub workspace_id: Option<String>,
     pub group_name: Option<String>,
+    pub plan_type: Option<String>,
+    pub proxy_url: Option<String>,
     pub sort: i64,
     pub status: String,
     pub created_at: i64,
@@ -39,10 +54,32 @@ pub struct LoginSession {
     pub note: Option<String>,
     pub tags: Option<String>,
     pub group_name: Option<String>,
+    pub reauth_account_id: Option<String>,
+    pub workspace_selection: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
+impl LoginSession {
+    /// 以 login_id 作为 OAuth state 的待完成会话，其余可选字段为空。
+    pub fn pending(login_id: &str, code_verifier: &str, created_at: i64) -> Self {
+        Self {
+            login_id: login_id.to_string(),
+            code_verifier: code_verifier.to_string(),
+            state: login_id.to_string(),
+            status: "pending".to_string(),
+            error: None,
+            note: None,
+            tags: None,
+            group_name: None,
+            reauth_account_id: None,
+            workspace_selection: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UsageSnapshotRecord {
     pub account_id: String,
@@ -64,6 +101,24 @@ pub struct Event {
     pub created_at: i64,
 }
 
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    pub id: i64,
+    pub account_id: Option<String>,
+    pub event_type: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub account_id: Option<String>,
+    pub event_type: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct RequestLog {
     pub key_id: Option<String>,
@@ -94,6 +149,208 @@ pub struct ApiKey {
     pub last_used_at: Option<i64>,
 }
 
+#[derive(Debug, Clone)]
+pub struct AdminToken {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub scope: String,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMigrationRecord {
+    pub version: String,
+    pub applied_at: i64,
+    pub reversible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaInfo {
+    pub path: Option<String>,
+    pub journal_mode: String,
+    pub current_version: Option<String>,
+    pub latest_version: String,
+    pub applied: Vec<SchemaMigrationRecord>,
+    pub pending: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+fn schema_error(message: String) -> rusqlite::Error {
+    // 中文注释：沿用 SqliteFailure 承载提示，调用方直接 to_string 就能拿到完整原因，不需要额外错误类型。
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+        Some(message),
+    )
+}
+
+struct Migration {
+    version: &'static str,
+    up: &'static str,
+    down: Option<&'static str>,
+    compat: Option<fn(&Storage) -> Result<()>>,
+}
+
+/// 按版本顺序登记的全部迁移；新增迁移只能追加到末尾，版本号即数据库 schema 版本。
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: "001_init",
+        up: include_str!("../../migrations/001_init.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "002_login_sessions",
+        up: include_str!("../../migrations/002_login_sessions.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "003_api_keys",
+        up: include_str!("../../migrations/003_api_keys.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "004_api_key_model",
+        up: include_str!("../../migrations/004_api_key_model.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_api_key_model_column()),
+    },
+    Migration {
+        version: "005_request_logs",
+        up: include_str!("../../migrations/005_request_logs.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_request_logs_table()),
+    },
+    Migration {
+        version: "006_usage_snapshots_latest_index",
+        up: include_str!("../../migrations/006_usage_snapshots_latest_index.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/006_usage_snapshots_latest_index.sql"
+        )),
+        compat: None,
+    },
+    Migration {
+        version: "007_usage_secondary_columns",
+        up: include_str!("../../migrations/007_usage_secondary_columns.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_usage_secondary_columns()),
+    },
+    Migration {
+        version: "008_token_api_key_access_token",
+        up: include_str!("../../migrations/008_token_api_key_access_token.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_token_api_key_column()),
+    },
+    Migration {
+        version: "009_api_key_reasoning_effort",
+        up: include_str!("../../migrations/009_api_key_reasoning_effort.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_api_key_reasoning_column()),
+    },
+    Migration {
+        version: "010_request_log_reasoning_effort",
+        up: include_str!("../../migrations/010_request_log_reasoning_effort.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_request_log_reasoning_column()),
+    },
+    // 中文注释：先走 SQL 迁移，遇到历史库重复列冲突再回退 compat；不这样写会导致老库和新库长期两套机制并存。
+    Migration {
+        version: "011_account_meta_columns",
+        up: include_str!("../../migrations/011_account_meta_columns.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_account_meta_columns()),
+    },
+    Migration {
+        version: "012_request_logs_search_indexes",
+        up: include_str!("../../migrations/012_request_logs_search_indexes.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/012_request_logs_search_indexes.sql"
+        )),
+        compat: None,
+    },
+    Migration {
+        version: "013_drop_accounts_note_tags",
+        up: include_str!("../../migrations/013_drop_accounts_note_tags.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "014_drop_accounts_workspace_name",
+        up: include_str!("../../migrations/014_drop_accounts_workspace_name.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "015_api_key_profiles",
+        up: include_str!("../../migrations/015_api_key_profiles.sql"),
+        down: None,
+        compat: Some(|s| s.ensure_api_key_profiles_table()),
+    },
+    Migration {
+        version: "016_events_query_indexes",
+        up: include_str!("../../migrations/016_events_query_indexes.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/016_events_query_indexes.sql"
+        )),
+        compat: None,
+    },
+    Migration {
+        version: "017_account_plan_type",
+        up: include_str!("../../migrations/017_account_plan_type.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/017_account_plan_type.sql"
+        )),
+        compat: Some(|s| s.ensure_column("accounts", "plan_type", "TEXT")),
+    },
+    // 中文注释：旧版本把 PKCE code_verifier 明文写进了 login_start 事件，这里一次性清洗存量数据。
+    Migration {
+        version: "018_scrub_login_start_secrets",
+        up: include_str!("../../migrations/018_scrub_login_start_secrets.sql"),
+        down: None,
+        compat: None,
+    },
+    Migration {
+        version: "019_login_session_reauth_account",
+        up: include_str!("../../migrations/019_login_session_reauth_account.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/019_login_session_reauth_account.sql"
+        )),
+        compat: Some(|s| s.ensure_column("login_sessions", "reauth_account_id", "TEXT")),
+    },
+    Migration {
+        version: "020_login_session_workspace_selection",
+        up: include_str!("../../migrations/020_login_session_workspace_selection.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/020_login_session_workspace_selection.sql"
+        )),
+        compat: Some(|s| s.ensure_column("login_sessions", "workspace_selection", "TEXT")),
+    },
+    Migration {
+        version: "021_tokens_refresh_token_index",
+        up: include_str!("../../migrations/021_tokens_refresh_token_index.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/021_tokens_refresh_token_index.sql"
+        )),
+        compat: None,
+    },
+    Migration {
+        version: "022_account_proxy_url",
+        up: include_str!("../../migrations/022_account_proxy_url.sql"),
+        down: Some(include_str!(
+            "../../migrations/down/022_account_proxy_url.sql"
+        )),
+        compat: Some(|s| s.ensure_column("accounts", "proxy_url", "TEXT")),
+    },
+    Migration {
+        version: "023_admin_tokens",
+        up: include_str!("../../migrations/023_admin_tokens.sql"),
+        down: Some(include_str!("../../migrations/down/023_admin_tokens.sql")),
+        compat: None,
+    },
+];
 
 #[derive(Debug)]
 pub struct Storage {
@@ -105,9 +362,22 @@ impl Storage {
         let conn = Connection::open(path)?;
         // 中文注释：并发写入时给 SQLite 一点等待时间，避免瞬时 lock 导致请求直接失败。
         conn.busy_timeout(Duration::from_millis(3000))?;
+        // 中文注释：WAL 让网关读请求不再被请求日志/用量写入阻塞；个别文件系统不支持 WAL 时退回默认日志模式继续可用。
+        let _ = conn.query_row("PRAGMA journal_mode = WAL", [], |row| {
+            row.get::<_, String>(0)
+        });
+        // 中文注释：WAL 下 NORMAL 只在断电时可能丢最后几笔提交，不会损坏库；FULL 会让每次提交都 fsync，高并发下写延迟翻倍。
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.pragma_update(None, "temp_store", "MEMORY")?;
+        conn.pragma_update(None, "cache_size", -8000)?;
         Ok(Self { conn })
     }
 
+    /// 数据库文件路径；内存库返回 None，调用方不应为其做跨连接缓存。
+    pub fn path(&self) -> Option<&str> {
+        self.conn.path().filter(|path| !path.is_empty())
+    }
+
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
         conn.busy_timeout(Duration::from_millis(3000))?;
@@ -116,79 +386,23 @@ impl Storage {
 
     pub fn init(&self) -> Result<()> {
         self.ensure_migrations_table()?;
+        self.check_schema_version()?;
+        self.backup_before_migrations()?;
 
-        self.apply_sql_migration("001_init", include_str!("../../migrations/001_init.sql"))?;
-        self.apply_sql_migration(
-            "002_login_sessions",
-            include_str!("../../migrations/002_login_sessions.sql"),
-        )?;
-        self.apply_sql_migration(
-            "003_api_keys",
-            include_str!("../../migrations/003_api_keys.sql"),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "004_api_key_model",
-            include_str!("../../migrations/004_api_key_model.sql"),
-            |s| s.ensure_api_key_model_column(),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "005_request_logs",
-            include_str!("../../migrations/005_request_logs.sql"),
-            |s| s.ensure_request_logs_table(),
-        )?;
-        self.apply_sql_migration(
-            "006_usage_snapshots_latest_index",
-            include_str!("../../migrations/006_usage_snapshots_latest_index.sql"),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "007_usage_secondary_columns",
-            include_str!("../../migrations/007_usage_secondary_columns.sql"),
-            |s| s.ensure_usage_secondary_columns(),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "008_token_api_key_access_token",
-            include_str!("../../migrations/008_token_api_key_access_token.sql"),
-            |s| s.ensure_token_api_key_column(),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "009_api_key_reasoning_effort",
-            include_str!("../../migrations/009_api_key_reasoning_effort.sql"),
-            |s| s.ensure_api_key_reasoning_column(),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "010_request_log_reasoning_effort",
-            include_str!("../../migrations/010_request_log_reasoning_effort.sql"),
-            |s| s.ensure_request_log_reasoning_column(),
-        )?;
-
-        // 中文注释：先走 SQL 迁移，遇到历史库重复列冲突再回退 compat；不这样写会导致老库和新库长期两套机制并存。
-        self.apply_sql_or_compat_migration(
-            "011_account_meta_columns",
-            include_str!("../../migrations/011_account_meta_columns.sql"),
-            |s| s.ensure_account_meta_columns(),
-        )?;
-        self.apply_sql_migration(
-            "012_request_logs_search_indexes",
-            include_str!("../../migrations/012_request_logs_search_indexes.sql"),
-        )?;
-        self.apply_sql_migration(
-            "013_drop_accounts_note_tags",
-            include_str!("../../migrations/013_drop_accounts_note_tags.sql"),
-        )?;
-        self.apply_sql_migration(
-            "014_drop_accounts_workspace_name",
-            include_str!("../../migrations/014_drop_accounts_workspace_name.sql"),
-        )?;
-        self.apply_sql_or_compat_migration(
-            "015_api_key_profiles",
-            include_str!("../../migrations/015_api_key_profiles.sql"),
-            |s| s.ensure_api_key_profiles_table(),
-        )
+        for migration in MIGRATIONS {
+            match migration.compat {
+                Some(compat) => {
+                    self.apply_sql_or_compat_migration(migration.version, migration.up, compat)?
+                }
+                None => self.apply_sql_migration(migration.version, migration.up)?,
+            }
+        }
+        Ok(())
     }
 
     pub fn insert_account(&self, account: &Account) -> Result<()> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO accounts (id, label, issuer, chatgpt_account_id, workspace_id, group_name, sort, status, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT OR REPLACE INTO accounts (id, label, issuer, chatgpt_account_id, workspace_id, group_name, plan_type, proxy_url, sort, status, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             (
                 &account.id,
                 &account.label,
@@ -196,12 +410,15 @@ impl Storage {
                 &account.chatgpt_account_id,
                 &account.workspace_id,
                 &account.group_name,
+                &account.plan_type,
+                &account.proxy_url,
                 account.sort,
                 &account.status,
                 account.created_at,
                 account.updated_at,
             ),
         )?;
+        mark_pool_changed();
         Ok(())
     }
 
@@ -217,9 +434,38 @@ impl Storage {
                 token.last_refresh,
             ),
         )?;
+        mark_pool_changed();
         Ok(())
     }
 
+    /// 把刷新后的 token 同步给仍持有旧 refresh_token 的其他条目，返回同步的条目数。
+    pub fn propagate_refreshed_token(
+        &self,
+        token: &Token,
+        previous_refresh_token: &str,
+    ) -> Result<usize> {
+        // 中文注释：同一身份的多个 workspace 条目共享一套 token；刷新轮换 refresh_token 后必须同步到兄弟条目，
+        // 否则其余条目手里的旧 refresh_token 已被上游作废，下次刷新会整体失效。
+        if previous_refresh_token.is_empty() || previous_refresh_token == token.refresh_token {
+            return Ok(0);
+        }
+        let changed = self.conn.execute(
+            "UPDATE tokens SET id_token = ?1, access_token = ?2, refresh_token = ?3, last_refresh = ?4 WHERE refresh_token = ?5 AND account_id != ?6",
+            (
+                &token.id_token,
+                &token.access_token,
+                &token.refresh_token,
+                token.last_refresh,
+                previous_refresh_token,
+                &token.account_id,
+            ),
+        )?;
+        if changed > 0 {
+            mark_pool_changed();
+        }
+        Ok(changed)
+    }
+
     pub fn account_count(&self) -> Result<i64> {
         self.conn.query_row("SELECT COUNT(1) FROM accounts", [], |row| row.get(0))
     }
@@ -243,6 +489,7 @@ impl Storage {
                 snap.captured_at,
             ),
         )?;
+        mark_pool_changed();
         Ok(())
     }
 
@@ -268,9 +515,48 @@ impl Storage {
         }
     }
 
+    pub fn list_usage_snapshots(
+        &self,
+        account_id: Option<&str>,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<UsageSnapshotRecord>> {
+        // 中文注释：历史序列按账号 + 时间正序返回，调用方可以直接顺序计算趋势而无需再排序。
+        // limit 按账号分别生效（各取最近 N 条）；全局截断会让刷新频繁的账号把其它账号的历史挤掉。
+        let normalized_limit = if limit <= 0 { 2000 } else { limit.min(20000) };
+        let mut stmt = self.conn.prepare(
+            "SELECT account_id, used_percent, window_minutes, resets_at, secondary_used_percent, secondary_window_minutes, secondary_resets_at, credits_json, captured_at
+             FROM (
+                SELECT id, account_id, used_percent, window_minutes, resets_at, secondary_used_percent, secondary_window_minutes, secondary_resets_at, credits_json, captured_at,
+                       ROW_NUMBER() OVER (PARTITION BY account_id ORDER BY captured_at DESC, id DESC) AS rn
+                FROM usage_snapshots
+                WHERE (?1 IS NULL OR account_id = ?1)
+                  AND captured_at >= ?2
+             )
+             WHERE rn <= ?3
+             ORDER BY account_id ASC, captured_at ASC, id ASC",
+        )?;
+        let mut rows = stmt.query((account_id, since, normalized_limit))?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(UsageSnapshotRecord {
+                account_id: row.get(0)?,
+                used_percent: row.get(1)?,
+                window_minutes: row.get(2)?,
+                resets_at: row.get(3)?,
+                secondary_used_percent: row.get(4)?,
+                secondary_window_minutes: row.get(5)?,
+                secondary_resets_at: row.get(6)?,
+                credits_json: row.get(7)?,
+                captured_at: row.get(8)?,
+            });
+        }
+        Ok(out)
+    }
+
     pub fn list_accounts(&self) -> Result<Vec<Account>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, label, issuer, chatgpt_account_id, workspace_id, group_name, sort, status, created_at, updated_at FROM accounts ORDER BY sort ASC, updated_at DESC",
+            "SELECT id, label, issuer, chatgpt_account_id, workspace_id, group_name, plan_type, proxy_url, sort, status, created_at, updated_at FROM accounts ORDER BY sort ASC, updated_at DESC",
         )?;
         let mut rows = stmt.query([])?;
         let mut out = Vec::new();
@@ -282,10 +568,12 @@ impl Storage {
                 chatgpt_account_id: row.get(3)?,
                 workspace_id: row.get(4)?,
                 group_name: row.get(5)?,
-                sort: row.get(6)?,
-                status: row.get(7)?,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
+                plan_type: row.get(6)?,
+                proxy_url: row.get(7)?,
+                sort: row.get(8)?,
+                status: row.get(9)?,
+                created_at: row.get(10)?,
+                updated_at: row.get(11)?,
             });
         }
         Ok(out)
@@ -310,11 +598,100 @@ impl Storage {
         Ok(out)
     }
 
+    pub fn find_account(&self, account_id: &str) -> Result<Option<Account>> {
+        self.conn
+            .query_row(
+                "SELECT id, label, issuer, chatgpt_account_id, workspace_id, group_name, plan_type, proxy_url, sort, status, created_at, updated_at FROM accounts WHERE id = ?1",
+                [account_id],
+                |row| {
+                    Ok(Account {
+                        id: row.get(0)?,
+                        label: row.get(1)?,
+                        issuer: row.get(2)?,
+                        chatgpt_account_id: row.get(3)?,
+                        workspace_id: row.get(4)?,
+                        group_name: row.get(5)?,
+                        plan_type: row.get(6)?,
+                        proxy_url: row.get(7)?,
+                        sort: row.get(8)?,
+                        status: row.get(9)?,
+                        created_at: row.get(10)?,
+                        updated_at: row.get(11)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    pub fn find_token(&self, account_id: &str) -> Result<Option<Token>> {
+        self.conn
+            .query_row(
+                "SELECT account_id, id_token, access_token, refresh_token, api_key_access_token, last_refresh FROM tokens WHERE account_id = ?1",
+                [account_id],
+                |row| {
+                    Ok(Token {
+                        account_id: row.get(0)?,
+                        id_token: row.get(1)?,
+                        access_token: row.get(2)?,
+                        refresh_token: row.get(3)?,
+                        api_key_access_token: row.get(4)?,
+                        last_refresh: row.get(5)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    pub fn latest_usage_snapshot_for_account(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<UsageSnapshotRecord>> {
+        // 中文注释：按 (account_id, captured_at, id) 复合索引取单条，避免故障切换时为一个账号扫描全表窗口函数。
+        self.conn
+            .query_row(
+                "SELECT account_id, used_percent, window_minutes, resets_at, secondary_used_percent, secondary_window_minutes, secondary_resets_at, credits_json, captured_at FROM usage_snapshots WHERE account_id = ?1 ORDER BY captured_at DESC, id DESC LIMIT 1",
+                [account_id],
+                |row| {
+                    Ok(UsageSnapshotRecord {
+                        account_id: row.get(0)?,
+                        used_percent: row.get(1)?,
+                        window_minutes: row.get(2)?,
+                        resets_at: row.get(3)?,
+                        secondary_used_percent: row.get(4)?,
+                        secondary_window_minutes: row.get(5)?,
+                        secondary_resets_at: row.get(6)?,
+                        credits_json: row.get(7)?,
+                        captured_at: row.get(8)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
     pub fn update_account_sort(&self, account_id: &str, sort: i64) -> Result<()> {
         self.conn.execute(
             "UPDATE accounts SET sort = ?1, updated_at = ?2 WHERE id = ?3",
             (sort, now_ts(), account_id),
         )?;
+        mark_pool_changed();
+        Ok(())
+    }
+
+    pub fn update_account_proxy_url(&self, account_id: &str, proxy_url: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE accounts SET proxy_url = ?1, updated_at = ?2 WHERE id = ?3",
+            (proxy_url, now_ts(), account_id),
+        )?;
+        mark_pool_changed();
+        Ok(())
+    }
+
+    pub fn update_account_plan_type(&self, account_id: &str, plan_type: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE accounts SET plan_type = ?1, updated_at = ?2 WHERE id = ?3",
+            (plan_type, now_ts(), account_id),
+        )?;
+        mark_pool_changed();
         Ok(())
     }
 
@@ -323,6 +700,7 @@ impl Storage {
             "UPDATE accounts SET status = ?1, updated_at = ?2 WHERE id = ?3",
             (status, now_ts(), account_id),
         )?;
+        mark_pool_changed();
         Ok(())
     }
 
@@ -584,13 +962,86 @@ impl Storage {
         Ok(())
     }
 
+    pub fn insert_admin_token(&self, token: &AdminToken) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO admin_tokens (id, name, token_hash, scope, created_at, last_used_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                &token.id,
+                &token.name,
+                &token.token_hash,
+                &token.scope,
+                token.created_at,
+                &token.last_used_at,
+            ),
+        )?;
+        Ok(())
+    }
+
+    pub fn list_admin_tokens(&self) -> Result<Vec<AdminToken>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, token_hash, scope, created_at, last_used_at
+             FROM admin_tokens
+             ORDER BY created_at DESC, id",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(AdminToken {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                token_hash: row.get(2)?,
+                scope: row.get(3)?,
+                created_at: row.get(4)?,
+                last_used_at: row.get(5)?,
+            });
+        }
+        Ok(out)
+    }
+
+    pub fn find_admin_token_by_hash(&self, token_hash: &str) -> Result<Option<AdminToken>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, token_hash, scope, created_at, last_used_at
+             FROM admin_tokens
+             WHERE token_hash = ?1
+             LIMIT 1",
+        )?;
+        let mut rows = stmt.query([token_hash])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(AdminToken {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                token_hash: row.get(2)?,
+                scope: row.get(3)?,
+                created_at: row.get(4)?,
+                last_used_at: row.get(5)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn update_admin_token_last_used(&self, token_id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE admin_tokens SET last_used_at = ?1 WHERE id = ?2",
+            (now_ts(), token_id),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_admin_token(&self, token_id: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM admin_tokens WHERE id = ?1", [token_id])?;
+        Ok(removed > 0)
+    }
+
     pub fn insert_event(&self, event: &Event) -> Result<()> {
         self.conn.execute(
             "INSERT INTO events (account_id, type, message, created_at) VALUES (?1, ?2, ?3, ?4)",
             (
                 &event.account_id,
                 &event.event_type,
-                &event.message,
+                redact_secrets(&event.message),
                 event.created_at,
             ),
         )?;
@@ -602,19 +1053,43 @@ impl Storage {
             "INSERT INTO request_logs (key_id, request_path, method, model, reasoning_effort, upstream_url, status_code, error, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             (
                 &log.key_id,
-                &log.request_path,
+                redact_secrets(&log.request_path),
                 &log.method,
                 &log.model,
                 &log.reasoning_effort,
-                &log.upstream_url,
+                redact_optional(log.upstream_url.as_deref()),
                 log.status_code,
-                &log.error,
+                redact_optional(log.error.as_deref()),
                 log.created_at,
             ),
         )?;
         Ok(())
     }
 
+    pub fn insert_request_logs(&mut self, logs: &[RequestLog]) -> Result<()> {
+        // 中文注释：批量日志放进同一事务，一次提交只触发一次 WAL 同步；逐条提交在高并发流式请求下会成为写锁热点。
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO request_logs (key_id, request_path, method, model, reasoning_effort, upstream_url, status_code, error, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for log in logs {
+                stmt.execute((
+                    &log.key_id,
+                    redact_secrets(&log.request_path),
+                    &log.method,
+                    &log.model,
+                    &log.reasoning_effort,
+                    redact_optional(log.upstream_url.as_deref()),
+                    log.status_code,
+                    redact_optional(log.error.as_deref()),
+                    log.created_at,
+                ))?;
+            }
+        }
+        tx.commit()
+    }
+
     pub fn list_request_logs(&self, query: Option<&str>, limit: i64) -> Result<Vec<RequestLog>> {
         let normalized_limit = if limit <= 0 { 200 } else { limit.min(1000) };
         let mut out = Vec::new();
@@ -755,6 +1230,57 @@ impl Storage {
         self.conn.query_row("SELECT COUNT(1) FROM events", [], |row| row.get(0))
     }
 
+    pub fn list_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        self.query_events(query, None)
+    }
+
+    pub fn list_events_after(&self, after_id: i64, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        // 中文注释：增量拉取按 id 正序返回，推送端才能按发生顺序下发并记住游标。
+        self.query_events(query, Some(after_id))
+    }
+
+    pub fn latest_event_id(&self) -> Result<i64> {
+        self.conn
+            .query_row("SELECT IFNULL(MAX(id), 0) FROM events", [], |row| row.get(0))
+    }
+
+    fn query_events(&self, query: &EventQuery, after_id: Option<i64>) -> Result<Vec<EventRecord>> {
+        let normalized_limit = if query.limit <= 0 { 200 } else { query.limit.min(1000) };
+        let order = if after_id.is_some() { "ASC" } else { "DESC" };
+        // 中文注释：可选过滤统一用 “?n IS NULL OR ...” 绑定，避免按组合拼接多份 SQL。
+        let sql = format!(
+            "SELECT id, account_id, type, message, created_at
+             FROM events
+             WHERE (?1 IS NULL OR account_id = ?1)
+               AND (?2 IS NULL OR type = ?2)
+               AND (?3 IS NULL OR created_at >= ?3)
+               AND (?4 IS NULL OR created_at <= ?4)
+               AND (?5 IS NULL OR id > ?5)
+             ORDER BY id {order}
+             LIMIT ?6"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows = stmt.query((
+            &query.account_id,
+            &query.event_type,
+            query.since,
+            query.until,
+            after_id,
+            normalized_limit,
+        ))?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(EventRecord {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                event_type: row.get(2)?,
+                message: row.get(3)?,
+                created_at: row.get(4)?,
+            });
+        }
+        Ok(out)
+    }
+
     pub fn delete_account(&mut self, account_id: &str) -> Result<()> {
         let tx = self.conn.transaction()?;
         tx.execute("DELETE FROM tokens WHERE account_id = ?1", [account_id])?;
@@ -765,9 +1291,30 @@ impl Storage {
         tx.execute("DELETE FROM events WHERE account_id = ?1", [account_id])?;
         tx.execute("DELETE FROM accounts WHERE id = ?1", [account_id])?;
         tx.commit()?;
+        mark_pool_changed();
         Ok(())
     }
 
+    /// 清空整个账号池（账号、token、用量快照、账号事件与平台 Key）。
+    ///
+    /// 本身不开事务，需要和后续写入一起原子生效时由调用方包在 [`Storage::begin_transaction`] 里。
+    pub fn clear_account_pool(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "DELETE FROM tokens WHERE account_id IN (SELECT id FROM accounts);
+             DELETE FROM usage_snapshots WHERE account_id IN (SELECT id FROM accounts);
+             DELETE FROM events WHERE account_id IN (SELECT id FROM accounts);
+             DELETE FROM accounts;
+             DELETE FROM api_keys;",
+        )?;
+        mark_pool_changed();
+        Ok(())
+    }
+
+    /// 开启事务；提交前经同一个 `Storage` 做的读写都在事务内，未提交就丢弃会整体回滚。
+    pub fn begin_transaction(&self) -> Result<rusqlite::Transaction<'_>> {
+        self.conn.unchecked_transaction()
+    }
+
     pub fn latest_usage_snapshots_by_account(&self) -> Result<Vec<UsageSnapshotRecord>> {
         // 中文注释：窗口函数 + 复合索引可稳定处理“同 captured_at 并发写入”场景；
         // 不这样做会依赖复杂子查询拼接，后续维护和优化都更难。
@@ -824,7 +1371,7 @@ impl Storage {
 
     pub fn insert_login_session(&self, session: &LoginSession) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO login_sessions (login_id, code_verifier, state, status, error, note, tags, group_name, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO login_sessions (login_id, code_verifier, state, status, error, note, tags, group_name, reauth_account_id, workspace_selection, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             (
                 &session.login_id,
                 &session.code_verifier,
@@ -834,6 +1381,8 @@ impl Storage {
                 &session.note,
                 &session.tags,
                 &session.group_name,
+                &session.reauth_account_id,
+                &session.workspace_selection,
                 session.created_at,
                 session.updated_at,
             ),
@@ -843,7 +1392,7 @@ impl Storage {
 
     pub fn get_login_session(&self, login_id: &str) -> Result<Option<LoginSession>> {
         let mut stmt = self.conn.prepare(
-            "SELECT login_id, code_verifier, state, status, error, note, tags, group_name, created_at, updated_at FROM login_sessions WHERE login_id = ?1",
+            "SELECT login_id, code_verifier, state, status, error, note, tags, group_name, reauth_account_id, workspace_selection, created_at, updated_at FROM login_sessions WHERE login_id = ?1",
         )?;
         let mut rows = stmt.query([login_id])?;
         if let Some(row) = rows.next()? {
@@ -856,8 +1405,10 @@ impl Storage {
                 note: row.get(5)?,
                 tags: row.get(6)?,
                 group_name: row.get(7)?,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
+                reauth_account_id: row.get(8)?,
+                workspace_selection: row.get(9)?,
+                created_at: row.get(10)?,
+                updated_at: row.get(11)?,
             }))
         } else {
             Ok(None)
@@ -877,6 +1428,37 @@ impl Storage {
         Ok(())
     }
 
+    pub fn transition_login_session_status(
+        &self,
+        login_id: &str,
+        from_status: &str,
+        to_status: &str,
+        error: Option<&str>,
+    ) -> Result<bool> {
+        // 中文注释：只在状态仍为 from_status 时更新；取消/过期与回调并发时不这样做会把已成功的会话改回失败。
+        let changed = self.conn.execute(
+            "UPDATE login_sessions SET status = ?1, error = ?2, updated_at = ?3 WHERE login_id = ?4 AND status = ?5",
+            (to_status, error, now_ts(), login_id, from_status),
+        )?;
+        Ok(changed > 0)
+    }
+
+    pub fn expire_login_sessions(&self, created_before: i64) -> Result<usize> {
+        let changed = self.conn.execute(
+            "UPDATE login_sessions SET status = 'expired', error = 'login session expired', updated_at = ?1 WHERE status = 'pending' AND created_at < ?2",
+            (now_ts(), created_before),
+        )?;
+        Ok(changed)
+    }
+
+    pub fn delete_login_sessions_before(&self, updated_before: i64) -> Result<usize> {
+        let deleted = self.conn.execute(
+            "DELETE FROM login_sessions WHERE status != 'pending' AND updated_at < ?1",
+            [updated_before],
+        )?;
+        Ok(deleted)
+    }
+
     fn ensure_token_api_key_column(&self) -> Result<()> {
         if self.has_column("tokens", "api_key_access_token")? {
             return Ok(());
@@ -1019,6 +1601,148 @@ impl Storage {
         Ok(())
     }
 
+    /// 数据库里出现本版本不认识的编号迁移，说明它被更新的版本写过，拒绝继续使用以免旧代码写坏新表结构。
+    pub fn check_schema_version(&self) -> Result<()> {
+        self.ensure_migrations_table()?;
+        let unknown = self.unknown_migrations()?;
+        if let Some(newest) = unknown.last() {
+            return Err(schema_error(format!(
+                "database schema {} is newer than this build supports (latest {}); upgrade gpttools or restore a backup",
+                newest,
+                latest_schema_version()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn schema_info(&self) -> Result<SchemaInfo> {
+        self.ensure_migrations_table()?;
+        let journal_mode: String = self
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        let applied = self.applied_migrations()?;
+        let pending = MIGRATIONS
+            .iter()
+            .filter(|migration| {
+                !applied
+                    .iter()
+                    .any(|(version, _)| version == migration.version)
+            })
+            .map(|migration| migration.version.to_string())
+            .collect();
+        let current_version = applied
+            .iter()
+            .filter(|(version, _)| is_numbered_migration(version))
+            .map(|(version, _)| version.clone())
+            .max();
+        Ok(SchemaInfo {
+            path: self.path().map(|path| path.to_string()),
+            journal_mode,
+            current_version,
+            latest_version: latest_schema_version().to_string(),
+            applied: applied
+                .iter()
+                .map(|(version, applied_at)| SchemaMigrationRecord {
+                    version: version.clone(),
+                    applied_at: *applied_at,
+                    reversible: find_migration(version)
+                        .map(|migration| migration.down.is_some())
+                        .unwrap_or(false),
+                })
+                .collect(),
+            pending,
+            unknown: self.unknown_migrations()?,
+        })
+    }
+
+    /// 依次执行 target 之后已应用迁移的 down 脚本，返回被回滚的版本；任一迁移不可逆则整体拒绝，不做半截回滚。
+    pub fn rollback_migrations(&self, target: &str) -> Result<Vec<String>> {
+        self.check_schema_version()?;
+        let Some(target_index) = MIGRATIONS.iter().position(|m| m.version == target) else {
+            return Err(schema_error(format!(
+                "unknown migration version: {}",
+                target
+            )));
+        };
+        let mut to_revert = Vec::new();
+        for migration in MIGRATIONS[target_index + 1..].iter().rev() {
+            if !self.has_migration(migration.version)? {
+                continue;
+            }
+            if migration.down.is_none() {
+                return Err(schema_error(format!(
+                    "migration {} cannot be rolled back",
+                    migration.version
+                )));
+            }
+            to_revert.push(migration);
+        }
+        self.backup_database(&format!("pre-rollback-{}", target))?;
+
+        let mut reverted = Vec::with_capacity(to_revert.len());
+        for migration in to_revert {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration.down.unwrap_or_default())?;
+            tx.execute(
+                "DELETE FROM schema_migrations WHERE version = ?1",
+                [migration.version],
+            )?;
+            tx.commit()?;
+            reverted.push(migration.version.to_string());
+        }
+        mark_pool_changed();
+        Ok(reverted)
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT version, applied_at FROM schema_migrations ORDER BY version")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    fn unknown_migrations(&self) -> Result<Vec<String>> {
+        // 中文注释：只看 NNN_ 编号的迁移；早期 ensure_* 留下的 compat_* 标记不是 schema 版本，不能当成新版本误报。
+        Ok(self
+            .applied_migrations()?
+            .into_iter()
+            .map(|(version, _)| version)
+            .filter(|version| is_numbered_migration(version) && find_migration(version).is_none())
+            .collect())
+    }
+
+    fn backup_before_migrations(&self) -> Result<Option<String>> {
+        let Some(first_pending) = MIGRATIONS
+            .iter()
+            .find(|migration| !self.has_migration(migration.version).unwrap_or(false))
+        else {
+            return Ok(None);
+        };
+        // 中文注释：全新空库没有可保护的数据，跳过备份；否则每次首次启动都会多出一个空备份文件。
+        let user_tables: i64 = self.conn.query_row(
+            "SELECT COUNT(1) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'schema_migrations'",
+            [],
+            |row| row.get(0),
+        )?;
+        if user_tables == 0 {
+            return Ok(None);
+        }
+        let prefix = first_pending.version.split('_').next().unwrap_or_default();
+        self.backup_database(&format!("pre-{}", prefix))
+    }
+
+    fn backup_database(&self, tag: &str) -> Result<Option<String>> {
+        let Some(path) = self.path() else {
+            return Ok(None);
+        };
+        let backup_path = format!("{}.{}.bak", path, tag);
+        // 中文注释：VACUUM INTO 目标已存在会直接失败；同一迁移点重复启动只保留最近一次迁移前的快照。
+        let _ = std::fs::remove_file(&backup_path);
+        self.conn.execute("VACUUM INTO ?1", [&backup_path])?;
+        Ok(Some(backup_path))
+    }
+
     fn ensure_migrations_table(&self) -> Result<()> {
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS schema_migrations (
@@ -1085,12 +1809,31 @@ impl Storage {
             _ => false,
         }
     }
-
 }
 #[cfg(test)]
 #[path = "../../tests/storage/migration_tests.rs"]
 mod migration_tests;
 
+pub fn latest_schema_version() -> &'static str {
+    MIGRATIONS
+        .last()
+        .map(|migration| migration.version)
+        .unwrap_or_default()
+}
+
+fn find_migration(version: &str) -> Option<&'static Migration> {
+    MIGRATIONS
+        .iter()
+        .find(|migration| migration.version == version)
+}
+
+fn is_numbered_migration(version: &str) -> bool {
+    version
+        .split_once('_')
+        .map(|(prefix, _)| !prefix.is_empty() && prefix.bytes().all(|b| b.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
 pub fn now_ts() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -9,6 +9,31 @@ pub struct UsageSnapshot {
     pub secondary_window_minutes: Option<i64>,
     pub secondary_resets_at: Option<i64>,
     pub credits_json: Option<String>,
+    pub plan_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageCredits {
+    pub has_credits: Option<bool>,
+    pub unlimited: Option<bool>,
+    pub balance: Option<f64>,
+}
+
+pub fn parse_usage_credits(credits_json: &str) -> Option<UsageCredits> {
+    let value: Value = serde_json::from_str(credits_json).ok()?;
+    if !value.is_object() {
+        return None;
+    }
+    // 上游 balance 可能是字符串（"12.50"）也可能是数字
+    let balance = value.get("balance").and_then(|v| {
+        v.as_f64()
+            .or_else(|| v.as_str().and_then(|s| s.trim().parse::<f64>().ok()))
+    });
+    Some(UsageCredits {
+        has_credits: value.get("has_credits").and_then(Value::as_bool),
+        unlimited: value.get("unlimited").and_then(Value::as_bool),
+        balance,
+    })
 }
 
 pub fn normalize_base_url(base_url: &str) -> String {
@@ -54,6 +79,10 @@ pub fn parse_usage_snapshot(value: &Value) -> UsageSnapshot {
     let credits_json = value
         .get("credits")
         .and_then(|v| if v.is_null() { None } else { Some(v.to_string()) });
+    let plan_type = value
+        .get("plan_type")
+        .and_then(Value::as_str)
+        .map(|v| v.to_string());
 
     UsageSnapshot {
         used_percent,
@@ -63,5 +92,6 @@ pub fn parse_usage_snapshot(value: &Value) -> UsageSnapshot {
         secondary_window_minutes,
         secondary_resets_at,
         credits_json,
+        plan_type,
     }
 }
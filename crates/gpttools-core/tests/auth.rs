@@ -41,3 +41,11 @@ fn parse_id_token_claims_extracts_email_and_sub() {
     assert_eq!(claims.sub, "user-1");
     assert_eq!(claims.email.as_deref(), Some("test@example.com"));
 }
+
+#[test]
+fn parse_jwt_expiry_reads_exp_claim() {
+    // payload: {"sub":"user-1","exp":1700000000}
+    let token = "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiJ1c2VyLTEiLCJleHAiOjE3MDAwMDAwMDB9.sig";
+    assert_eq!(gpttools_core::auth::parse_jwt_expiry(token), Some(1_700_000_000));
+    assert_eq!(gpttools_core::auth::parse_jwt_expiry("not-a-jwt"), None);
+}
@@ -0,0 +1,32 @@
+use gpttools_core::redact::{redact_secrets, REDACTED};
+
+#[test]
+fn redact_strips_json_token_fields() {
+    let input = r#"{"login_id":"login-1","code_verifier":"abc123","access_token": "tok","expires_in":3600,"error":{"code":"refresh_token_reused"}}"#;
+    let output = redact_secrets(input);
+    assert!(!output.contains("abc123"));
+    assert!(!output.contains("\"tok\""));
+    assert!(output.contains("\"login_id\":\"login-1\""));
+    assert!(output.contains("\"expires_in\":3600"));
+    // JSON 错误码不属于凭据，需要保留用于排障
+    assert!(output.contains("refresh_token_reused"));
+}
+
+#[test]
+fn redact_strips_headers_query_and_bare_credentials() {
+    let input = "Authorization: Bearer abc.def Cookie: a=1; b=2\nurl=/cb?code=xyz&state=s1 key sk-abcdefghijklmnopqrstuvwx jwt eyJhbGciOi.eyJzdWIi.sig";
+    let output = redact_secrets(input);
+    assert_eq!(
+        output,
+        format!(
+            "Authorization: {REDACTED} Cookie: {REDACTED}\nurl=/cb?code={REDACTED}&state=s1 key {REDACTED} jwt {REDACTED}"
+        )
+    );
+}
+
+#[test]
+fn redact_keeps_plain_messages_untouched() {
+    let input = "refresh token failed with status 400 Bad Request (invalid_grant) status_code=400";
+    assert_eq!(redact_secrets(input), input);
+    assert_eq!(redact_secrets("用量刷新失败：令牌过期"), "用量刷新失败：令牌过期");
+}
@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use gpttools_core::rpc::client::{
+    AccountRpc, ApiKeyRpc, LoginRpc, RpcClient, RpcClientError, RpcResult, RpcTransport, UsageRpc,
+};
+use gpttools_core::rpc::methods;
+use gpttools_core::rpc::params::{
+    AccountProxySetParams, ApiKeyCreateParams, LoginIdParams, LoginStartParams, UsageAccountParams,
+};
+use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse};
+use serde_json::{json, Value};
+
+#[derive(Default)]
+struct StubTransport {
+    sent: RefCell<Vec<JsonRpcRequest>>,
+    replies: RefCell<Vec<Value>>,
+}
+
+impl StubTransport {
+    fn reply(self, result: Value) -> Self {
+        self.replies.borrow_mut().push(result);
+        self
+    }
+}
+
+impl RpcTransport for StubTransport {
+    fn send(&self, request: &JsonRpcRequest) -> RpcResult<JsonRpcResponse> {
+        self.sent.borrow_mut().push(JsonRpcRequest {
+            id: request.id,
+            method: request.method.clone(),
+            params: request.params.clone(),
+        });
+        let result = self.replies.borrow_mut().remove(0);
+        Ok(JsonRpcResponse {
+            id: request.id,
+            result,
+        })
+    }
+}
+
+#[test]
+fn typed_calls_use_shared_method_names_and_omit_unset_params() {
+    let transport = StubTransport::default()
+        .reply(json!({ "id": "gk_1", "key": "sk-test" }))
+        .reply(json!({ "ok": true }))
+        .reply(json!({ "items": [] }));
+    let client = RpcClient::new(transport);
+
+    let created = client
+        .apikey_create(&ApiKeyCreateParams {
+            name: Some("ci".to_string()),
+            ..Default::default()
+        })
+        .expect("create");
+    assert_eq!(created.key, "sk-test");
+    client
+        .usage_refresh(&UsageAccountParams::default())
+        .expect("refresh");
+    assert!(client.account_list().expect("list").items.is_empty());
+
+    let sent = client.transport().sent.borrow();
+    assert_eq!(sent[0].method, methods::APIKEY_CREATE);
+    assert_eq!(sent[0].params, Some(json!({ "name": "ci" })));
+    assert_eq!(sent[1].method, "account/usage/refresh");
+    assert_eq!(sent[1].params, Some(json!({})));
+    assert_eq!(sent[2].params, None);
+    assert!(sent[0].id < sent[1].id && sent[1].id < sent[2].id);
+}
+
+#[test]
+fn login_start_uses_wire_field_names() {
+    let transport = StubTransport::default().reply(json!({
+        "authUrl": "https://auth.example/device",
+        "loginId": "login-1",
+        "loginType": "device",
+        "issuer": "https://auth.example",
+        "clientId": "client",
+        "redirectUri": "https://auth.example/cb",
+        "device": null
+    }));
+    let client = RpcClient::new(transport);
+    let result = client
+        .login_start(&LoginStartParams {
+            login_type: Some("device".to_string()),
+            open_browser: Some(false),
+            group_name: Some("team".to_string()),
+            ..Default::default()
+        })
+        .expect("login start");
+    assert_eq!(result.login_id, "login-1");
+    let sent = client.transport().sent.borrow();
+    assert_eq!(
+        sent[0].params,
+        Some(json!({ "type": "device", "openBrowser": false, "groupName": "team" }))
+    );
+}
+
+#[test]
+fn business_errors_and_shape_mismatches_are_reported_per_method() {
+    let transport = StubTransport::default()
+        .reply(json!({ "ok": false, "error": "account not found" }))
+        .reply(json!({ "unexpected": true }))
+        .reply(json!({ "status": "failed", "error": "denied", "updatedAt": 10 }));
+    let client = RpcClient::new(transport);
+
+    let err = client
+        .account_proxy_set(&AccountProxySetParams {
+            account_id: "acc-x".to_string(),
+            proxy_url: None,
+        })
+        .expect_err("business error");
+    assert_eq!(
+        err,
+        RpcClientError::Rpc {
+            method: methods::ACCOUNT_PROXY_SET.to_string(),
+            message: "account not found".to_string(),
+        }
+    );
+    assert!(matches!(
+        client.apikey_list(),
+        Err(RpcClientError::Decode { .. })
+    ));
+
+    // 登录失败是正常的状态查询结果，不应当成调用失败
+    let status = client
+        .login_status(&LoginIdParams {
+            login_id: "login-1".to_string(),
+        })
+        .expect("status");
+    assert_eq!(status.status, "failed");
+    assert_eq!(status.error.as_deref(), Some("denied"));
+}
+
+#[test]
+fn method_list_has_no_duplicates() {
+    let unique: HashSet<&str> = methods::ALL.iter().copied().collect();
+    assert_eq!(unique.len(), methods::ALL.len());
+}
@@ -63,6 +63,39 @@ fn init_tracks_schema_migrations_and_is_idempotent() {
         )
         .expect("count 015 migration");
     assert_eq!(applied_015, 1);
+    let applied_016: i64 = storage
+        .conn
+        .query_row(
+            "SELECT COUNT(1) FROM schema_migrations WHERE version = '016_events_query_indexes'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("count 016 migration");
+    assert_eq!(applied_016, 1);
+    let applied_018: i64 = storage
+        .conn
+        .query_row(
+            "SELECT COUNT(1) FROM schema_migrations WHERE version = '018_scrub_login_start_secrets'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("count 018 migration");
+    assert_eq!(applied_018, 1);
+    assert!(storage
+        .has_column("login_sessions", "reauth_account_id")
+        .expect("check login_sessions.reauth_account_id"));
+    assert!(storage
+        .has_column("login_sessions", "workspace_selection")
+        .expect("check login_sessions.workspace_selection"));
+    let refresh_token_index: i64 = storage
+        .conn
+        .query_row(
+            "SELECT COUNT(1) FROM sqlite_master WHERE type = 'index' AND name = 'idx_tokens_refresh_token'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("count tokens refresh index");
+    assert_eq!(refresh_token_index, 1);
 
     assert!(!storage.has_column("accounts", "note").expect("check accounts.note"));
     assert!(!storage.has_column("accounts", "tags").expect("check accounts.tags"));
@@ -244,3 +277,175 @@ fn api_key_profile_migration_backfills_existing_keys() {
     assert_eq!(profile_row.5, None);
 }
 
+#[test]
+fn scrub_migration_removes_login_start_code_verifier() {
+    let storage = Storage::open_in_memory().expect("open in memory");
+    storage.init().expect("init");
+    // 模拟旧版本直接写库的明文事件（绕过 insert_event 的脱敏）
+    storage
+        .conn
+        .execute_batch(
+            "INSERT INTO events (account_id, type, message, created_at)
+             VALUES (NULL, 'login_start', '{\"login_id\":\"login-1\",\"code_verifier\":\"secret-verifier\"}', 1);
+             INSERT INTO events (account_id, type, message, created_at)
+             VALUES (NULL, 'login_start', 'login_id=login-2 code_verifier=secret-verifier', 2);
+             DELETE FROM schema_migrations WHERE version = '018_scrub_login_start_secrets';",
+        )
+        .expect("seed legacy events");
+
+    storage.init().expect("re-run migrations");
+
+    let messages: Vec<String> = storage
+        .conn
+        .prepare("SELECT message FROM events WHERE type = 'login_start' ORDER BY id")
+        .expect("prepare")
+        .query_map([], |row| row.get(0))
+        .expect("query")
+        .collect::<std::result::Result<_, _>>()
+        .expect("collect");
+    assert_eq!(messages[0], "{\"login_id\":\"login-1\"}");
+    assert_eq!(messages[1], "[REDACTED]");
+}
+
+#[test]
+fn init_refuses_database_written_by_newer_schema() {
+    let storage = Storage::open_in_memory().expect("open in memory");
+    storage.init().expect("init");
+    storage
+        .conn
+        .execute(
+            "INSERT INTO schema_migrations (version, applied_at) VALUES ('compat_account_meta_columns', 1)",
+            [],
+        )
+        .expect("insert legacy compat marker");
+    storage
+        .init()
+        .expect("legacy compat marker is not a schema version");
+
+    storage
+        .conn
+        .execute(
+            "INSERT INTO schema_migrations (version, applied_at) VALUES ('999_future_schema', 1)",
+            [],
+        )
+        .expect("insert future migration");
+    let err = storage.init().expect_err("newer schema must be rejected");
+    assert!(err.to_string().contains("999_future_schema"), "{err}");
+    assert!(err.to_string().contains("newer than this build"), "{err}");
+
+    let info = storage.schema_info().expect("schema info");
+    assert_eq!(info.unknown, vec!["999_future_schema".to_string()]);
+    assert!(storage
+        .rollback_migrations("018_scrub_login_start_secrets")
+        .is_err());
+}
+
+#[test]
+fn rollback_reverts_reversible_migrations_and_init_reapplies_them() {
+    let storage = Storage::open_in_memory().expect("open in memory");
+    storage.init().expect("init");
+
+    let reverted = storage
+        .rollback_migrations("018_scrub_login_start_secrets")
+        .expect("rollback to 018");
+    assert_eq!(
+        reverted,
+        vec![
+            "023_admin_tokens".to_string(),
+            "022_account_proxy_url".to_string(),
+            "021_tokens_refresh_token_index".to_string(),
+            "020_login_session_workspace_selection".to_string(),
+            "019_login_session_reauth_account".to_string(),
+        ]
+    );
+    assert!(!storage
+        .has_column("login_sessions", "reauth_account_id")
+        .expect("check column"));
+    assert!(!storage
+        .has_column("accounts", "proxy_url")
+        .expect("check column"));
+    assert!(!storage
+        .has_column("admin_tokens", "token_hash")
+        .expect("check table"));
+    let info = storage.schema_info().expect("schema info");
+    assert_eq!(
+        info.current_version.as_deref(),
+        Some("018_scrub_login_start_secrets")
+    );
+    assert_eq!(info.pending.len(), 5);
+
+    // 013/014 删列不可逆，回滚跨过它们时必须整体拒绝且不动任何迁移
+    let err = storage
+        .rollback_migrations("012_request_logs_search_indexes")
+        .expect_err("irreversible migration blocks rollback");
+    assert!(
+        err.to_string().contains("018_scrub_login_start_secrets"),
+        "{err}"
+    );
+    assert!(storage
+        .has_migration("017_account_plan_type")
+        .expect("017 still applied"));
+    assert!(storage.rollback_migrations("000_missing").is_err());
+
+    storage.init().expect("re-apply migrations");
+    assert!(storage
+        .has_column("login_sessions", "reauth_account_id")
+        .expect("check column"));
+    assert!(storage
+        .has_column("accounts", "proxy_url")
+        .expect("check column"));
+    let info = storage.schema_info().expect("schema info");
+    assert!(info.pending.is_empty());
+    assert_eq!(
+        info.current_version.as_deref(),
+        Some(super::latest_schema_version())
+    );
+    assert!(info
+        .applied
+        .iter()
+        .any(|item| item.version == "021_tokens_refresh_token_index" && item.reversible));
+    assert!(info
+        .applied
+        .iter()
+        .any(|item| item.version == "013_drop_accounts_note_tags" && !item.reversible));
+}
+
+#[test]
+fn init_backs_up_existing_file_database_before_pending_migrations() {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-migration-backup-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let db_path = dir.join("gpttools.db");
+    let backup_path = dir.join("gpttools.db.pre-021.bak");
+
+    let storage = Storage::open(&db_path).expect("open file db");
+    storage.init().expect("fresh init");
+    assert!(
+        std::fs::read_dir(&dir)
+            .expect("read dir")
+            .flatten()
+            .all(|entry| !entry.file_name().to_string_lossy().ends_with(".bak")),
+        "fresh database should not be backed up"
+    );
+
+    storage
+        .rollback_migrations("020_login_session_workspace_selection")
+        .expect("rollback 021");
+    assert!(dir
+        .join("gpttools.db.pre-rollback-020_login_session_workspace_selection.bak")
+        .exists());
+    storage.init().expect("re-apply 021");
+    assert!(backup_path.exists());
+
+    let backup = Storage::open(&backup_path).expect("open backup");
+    assert!(!backup
+        .has_migration("021_tokens_refresh_token_index")
+        .expect("backup predates 021"));
+    assert!(backup
+        .has_migration("020_login_session_workspace_selection")
+        .expect("backup keeps 020"));
+    drop(backup);
+    drop(storage);
+    let _ = std::fs::remove_dir_all(&dir);
+}
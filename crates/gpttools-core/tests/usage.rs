@@ -1,9 +1,10 @@
-use gpttools_core::usage::{parse_usage_snapshot, usage_endpoint};
+use gpttools_core::usage::{parse_usage_credits, parse_usage_snapshot, usage_endpoint};
 use serde_json::json;
 
 #[test]
 fn usage_snapshot_parsed() {
     let payload = json!({
+        "plan_type": "pro",
         "rate_limit": {
             "primary_window": {
                 "used_percent": 25.0,
@@ -27,7 +28,23 @@ fn usage_snapshot_parsed() {
     assert_eq!(snap.secondary_window_minutes, Some(2));
     assert_eq!(snap.secondary_resets_at, Some(1730947260));
     assert!(snap.credits_json.as_ref().unwrap().contains("balance"));
+    assert_eq!(snap.plan_type.as_deref(), Some("pro"));
 
     let url = usage_endpoint("https://chatgpt.com");
     assert_eq!(url, "https://chatgpt.com/backend-api/wham/usage");
 }
+
+#[test]
+fn usage_credits_parse_string_and_numeric_balance() {
+    let credits = parse_usage_credits(r#"{"has_credits":true,"unlimited":false,"balance":"12.50"}"#)
+        .expect("credits");
+    assert_eq!(credits.has_credits, Some(true));
+    assert_eq!(credits.unlimited, Some(false));
+    assert_eq!(credits.balance, Some(12.5));
+
+    let credits = parse_usage_credits(r#"{"balance":3}"#).expect("numeric credits");
+    assert_eq!(credits.balance, Some(3.0));
+    assert_eq!(credits.has_credits, None);
+
+    assert!(parse_usage_credits("null").is_none());
+}
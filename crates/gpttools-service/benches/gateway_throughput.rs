@@ -0,0 +1,227 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use gpttools_core::storage::{now_ts, Account, ApiKey, Storage, Token};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POOL_ACCOUNTS: usize = 16;
+const STREAM_EVENTS: usize = 8;
+const PLATFORM_KEY: &str = "pk_bench_gateway_throughput";
+
+fn hash_platform_key(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn read_request_head(stream: &mut TcpStream) {
+    // 读完请求头与请求体，避免上游提前关连接导致网关把流式响应判成失败
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let read = match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        raw.extend_from_slice(&buf[..read]);
+        let Some(header_end) = raw.windows(4).position(|w| w == b"\r\n\r\n") else {
+            continue;
+        };
+        let head = String::from_utf8_lossy(&raw[..header_end]).to_ascii_lowercase();
+        let content_length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        while raw.len() < header_end + 4 + content_length {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(read) => raw.extend_from_slice(&buf[..read]),
+            }
+        }
+        return;
+    }
+}
+
+fn serve_streaming_response(mut stream: TcpStream) {
+    read_request_head(&mut stream);
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if stream.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+    for idx in 0..STREAM_EVENTS {
+        let event = format!(
+            "event: response.output_text.delta\ndata: {{\"type\":\"response.output_text.delta\",\"delta\":\"chunk-{idx} \"}}\n\n"
+        );
+        if stream.write_all(event.as_bytes()).is_err() {
+            return;
+        }
+        let _ = stream.flush();
+        // 中文注释：模拟上游逐 token 下发的节奏；一次性写完测不出长连接占用网关工作线程的开销。
+        thread::sleep(Duration::from_millis(2));
+    }
+    let done = "event: response.completed\ndata: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_bench\",\"usage\":{\"input_tokens\":8,\"output_tokens\":8}}}\n\n";
+    let _ = stream.write_all(done.as_bytes());
+    let _ = stream.flush();
+}
+
+fn start_mock_upstream() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock upstream");
+    let addr = listener.local_addr().expect("mock upstream addr");
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || serve_streaming_response(stream));
+        }
+    });
+    format!("http://{addr}/backend-api/codex")
+}
+
+fn seed_storage(db_path: &PathBuf) {
+    let storage = Storage::open(db_path).expect("open bench db");
+    storage.init().expect("init bench db");
+    let now = now_ts();
+    for idx in 0..POOL_ACCOUNTS {
+        storage
+            .insert_account(&Account {
+                id: format!("acc_bench_{idx}"),
+                label: format!("bench-{idx}"),
+                issuer: "https://auth.openai.com".to_string(),
+                chatgpt_account_id: Some(format!("chatgpt_bench_{idx}")),
+                workspace_id: None,
+                group_name: None,
+                plan_type: None,
+                proxy_url: None,
+                sort: idx as i64,
+                status: "active".to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .expect("insert account");
+        storage
+            .insert_token(&Token {
+                account_id: format!("acc_bench_{idx}"),
+                id_token: String::new(),
+                access_token: format!("access_bench_{idx}"),
+                refresh_token: String::new(),
+                api_key_access_token: Some(format!("api_access_bench_{idx}")),
+                last_refresh: now,
+            })
+            .expect("insert token");
+    }
+    storage
+        .insert_api_key(&ApiKey {
+            id: "gk_bench_gateway".to_string(),
+            name: Some("bench".to_string()),
+            model_slug: None,
+            reasoning_effort: None,
+            client_type: "codex".to_string(),
+            protocol_type: "openai_compat".to_string(),
+            auth_scheme: "authorization_bearer".to_string(),
+            upstream_base_url: None,
+            static_headers_json: None,
+            key_hash: hash_platform_key(PLATFORM_KEY),
+            status: "active".to_string(),
+            created_at: now,
+            last_used_at: None,
+        })
+        .expect("insert api key");
+}
+
+fn check_health(addr: &str) -> bool {
+    let Ok(mut stream) = TcpStream::connect(addr) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(500)));
+    let request = format!("GET /health HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+    let mut buf = String::new();
+    let _ = stream.read_to_string(&mut buf);
+    buf.starts_with("HTTP/1.1 200")
+}
+
+fn start_gateway() -> String {
+    let probe = TcpListener::bind("127.0.0.1:0").expect("bind probe port");
+    let port = probe.local_addr().expect("probe addr").port();
+    drop(probe);
+    let addr = format!("localhost:{port}");
+    let addr_for_thread = addr.clone();
+    thread::spawn(move || {
+        let _ = gpttools_service::start_server(&addr_for_thread);
+    });
+    for _ in 0..200 {
+        if check_health(&addr) {
+            return addr;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!("gateway start timeout");
+}
+
+fn stream_one_request(addr: &str) {
+    let body = r#"{"model":"gpt-5.3-codex","input":"hello","stream":true}"#;
+    let mut stream = TcpStream::connect(addr).expect("connect gateway");
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let request = format!(
+        "POST /v1/responses HTTP/1.1\r\nHost: {addr}\r\nAuthorization: Bearer {PLATFORM_KEY}\r\nContent-Type: application/json\r\nAccept: text/event-stream\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).expect("write request");
+    let mut response = Vec::new();
+    if let Err(err) = stream.read_to_end(&mut response) {
+        panic!("read gateway response failed: {err}");
+    }
+    assert!(
+        response.starts_with(b"HTTP/1.1 200"),
+        "gateway response: {}",
+        String::from_utf8_lossy(&response)
+    );
+}
+
+fn gateway_throughput(c: &mut Criterion) {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-bench-gateway-{}", std::process::id()));
+    let _ = std::fs::create_dir_all(&dir);
+    let db_path = dir.join("gpttools.db");
+    let _ = std::fs::remove_file(&db_path);
+    seed_storage(&db_path);
+
+    std::env::set_var("GPTTOOLS_DB_PATH", &db_path);
+    std::env::set_var("GPTTOOLS_DISABLE_POLLING", "1");
+    std::env::set_var("GPTTOOLS_UPSTREAM_BASE_URL", start_mock_upstream());
+    let addr = start_gateway();
+    stream_one_request(&addr);
+
+    let mut group = c.benchmark_group("gateway_streaming");
+    group.sample_size(10);
+    for concurrency in [1usize, 8, 32] {
+        group.throughput(Throughput::Elements(concurrency as u64));
+        group.bench_function(format!("concurrent_{concurrency}"), |b| {
+            b.iter_custom(|iters| {
+                let started = Instant::now();
+                for _ in 0..iters {
+                    let workers: Vec<_> = (0..concurrency)
+                        .map(|_| {
+                            let addr = addr.clone();
+                            thread::spawn(move || stream_one_request(&addr))
+                        })
+                        .collect();
+                    for worker in workers {
+                        worker.join().expect("join worker");
+                    }
+                }
+                started.elapsed()
+            })
+        });
+    }
+    group.finish();
+
+    gpttools_service::request_shutdown(&addr);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, gateway_throughput);
+criterion_main!(benches);
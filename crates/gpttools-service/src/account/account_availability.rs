@@ -1,43 +1,125 @@
 use gpttools_core::storage::UsageSnapshotRecord;
 
+const DEFAULT_SOFT_LIMIT_PERCENT: f64 = 100.0;
+const MIN_SOFT_LIMIT_PERCENT: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum Availability {
     Available,
+    // 用量字段缺失：无法判断额度，但账号本身可能正常，仍允许路由
+    Unknown(&'static str),
+    // 超过软阈值：不再接新请求，仅在无其他候选时兜底
+    SoftLimited(&'static str),
     Unavailable(&'static str),
 }
 
-pub(crate) fn evaluate_snapshot(snap: &UsageSnapshotRecord) -> Availability {
-    let primary_missing = snap.used_percent.is_none() || snap.window_minutes.is_none();
-    let secondary_missing =
-        snap.secondary_used_percent.is_none() || snap.secondary_window_minutes.is_none();
-    if primary_missing {
-        return Availability::Unavailable("usage_missing_primary");
+impl Availability {
+    pub(crate) fn is_routable(&self) -> bool {
+        matches!(self, Availability::Available | Availability::Unknown(_))
     }
-    if secondary_missing {
-        return Availability::Unavailable("usage_missing_secondary");
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Availability::Available => "available",
+            Availability::Unknown(_) => "unknown",
+            Availability::SoftLimited(_) => "soft_limited",
+            Availability::Unavailable(_) => "exhausted",
+        }
+    }
+
+    pub(crate) fn reason(&self) -> Option<&'static str> {
+        match self {
+            Availability::Available => None,
+            Availability::Unknown(reason)
+            | Availability::SoftLimited(reason)
+            | Availability::Unavailable(reason) => Some(reason),
+        }
     }
-    if let Some(value) = snap.used_percent {
-        if value >= 100.0 {
-            return Availability::Unavailable("usage_exhausted_primary");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct UsageThresholds {
+    pub(crate) primary_percent: f64,
+    pub(crate) secondary_percent: f64,
+}
+
+impl Default for UsageThresholds {
+    fn default() -> Self {
+        Self {
+            primary_percent: DEFAULT_SOFT_LIMIT_PERCENT,
+            secondary_percent: DEFAULT_SOFT_LIMIT_PERCENT,
         }
     }
-    if let Some(value) = snap.secondary_used_percent {
-        if value >= 100.0 {
-            return Availability::Unavailable("usage_exhausted_secondary");
+}
+
+fn parse_soft_limit_percent(raw: Option<&str>) -> f64 {
+    // 中文注释：阈值夹在 [1, 100]；填错或超过 100 时按默认处理，避免把所有账号误判为超限。
+    raw.and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite())
+        .map(|value| value.clamp(MIN_SOFT_LIMIT_PERCENT, DEFAULT_SOFT_LIMIT_PERCENT))
+        .unwrap_or(DEFAULT_SOFT_LIMIT_PERCENT)
+}
+
+impl UsageThresholds {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            primary_percent: parse_soft_limit_percent(
+                std::env::var("GPTTOOLS_USAGE_SOFT_LIMIT_PRIMARY_PERCENT")
+                    .ok()
+                    .as_deref(),
+            ),
+            secondary_percent: parse_soft_limit_percent(
+                std::env::var("GPTTOOLS_USAGE_SOFT_LIMIT_SECONDARY_PERCENT")
+                    .ok()
+                    .as_deref(),
+            ),
         }
     }
-    Availability::Available
 }
 
-pub(crate) fn is_available(snap: Option<&UsageSnapshotRecord>) -> bool {
-    match snap {
-        None => true,
-        Some(record) => matches!(evaluate_snapshot(record), Availability::Available),
+pub(crate) fn evaluate_snapshot(snap: &UsageSnapshotRecord) -> Availability {
+    evaluate_snapshot_with(snap, &UsageThresholds::from_env())
+}
+
+pub(crate) fn evaluate_snapshot_with(
+    snap: &UsageSnapshotRecord,
+    thresholds: &UsageThresholds,
+) -> Availability {
+    // 中文注释：先判断已知的耗尽，再判断软阈值，最后才看字段缺失；
+    // 否则一侧窗口缺失会掩盖另一侧已经耗尽的事实。
+    if snap.used_percent.is_some_and(|value| value >= 100.0) {
+        return Availability::Unavailable("usage_exhausted_primary");
     }
+    if snap.secondary_used_percent.is_some_and(|value| value >= 100.0) {
+        return Availability::Unavailable("usage_exhausted_secondary");
+    }
+    if snap
+        .used_percent
+        .is_some_and(|value| value >= thresholds.primary_percent)
+    {
+        return Availability::SoftLimited("usage_soft_limit_primary");
+    }
+    if snap
+        .secondary_used_percent
+        .is_some_and(|value| value >= thresholds.secondary_percent)
+    {
+        return Availability::SoftLimited("usage_soft_limit_secondary");
+    }
+    let primary_missing = snap.used_percent.is_none() || snap.window_minutes.is_none();
+    let secondary_missing =
+        snap.secondary_used_percent.is_none() || snap.secondary_window_minutes.is_none();
+    if primary_missing {
+        return Availability::Unknown("usage_missing_primary");
+    }
+    if secondary_missing {
+        return Availability::Unknown("usage_missing_secondary");
+    }
+    Availability::Available
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{evaluate_snapshot, Availability};
+    use super::{evaluate_snapshot_with, parse_soft_limit_percent, Availability, UsageThresholds};
     use gpttools_core::storage::UsageSnapshotRecord;
 
     fn snap(
@@ -60,29 +142,64 @@ mod tests {
     }
 
     #[test]
-    fn availability_marks_missing_primary_unavailable() {
+    fn availability_marks_missing_primary_unknown() {
         let record = snap(None, Some(300), Some(10.0), Some(10080));
-        assert!(matches!(
-            evaluate_snapshot(&record),
-            Availability::Unavailable(_)
-        ));
+        let availability = evaluate_snapshot_with(&record, &UsageThresholds::default());
+        assert_eq!(availability, Availability::Unknown("usage_missing_primary"));
+        assert!(availability.is_routable());
     }
 
     #[test]
     fn availability_marks_exhausted_secondary_unavailable() {
         let record = snap(Some(10.0), Some(300), Some(100.0), Some(10080));
         assert!(matches!(
-            evaluate_snapshot(&record),
+            evaluate_snapshot_with(&record, &UsageThresholds::default()),
             Availability::Unavailable(_)
         ));
     }
 
+    #[test]
+    fn availability_prefers_exhausted_over_missing() {
+        let record = snap(Some(100.0), Some(300), None, None);
+        assert_eq!(
+            evaluate_snapshot_with(&record, &UsageThresholds::default()),
+            Availability::Unavailable("usage_exhausted_primary")
+        );
+    }
+
+    #[test]
+    fn availability_applies_soft_thresholds() {
+        let thresholds = UsageThresholds {
+            primary_percent: 95.0,
+            secondary_percent: 90.0,
+        };
+        let record = snap(Some(96.0), Some(300), Some(10.0), Some(10080));
+        let availability = evaluate_snapshot_with(&record, &thresholds);
+        assert_eq!(availability, Availability::SoftLimited("usage_soft_limit_primary"));
+        assert!(!availability.is_routable());
+
+        let record = snap(Some(10.0), Some(300), Some(91.0), Some(10080));
+        assert_eq!(
+            evaluate_snapshot_with(&record, &thresholds),
+            Availability::SoftLimited("usage_soft_limit_secondary")
+        );
+    }
+
     #[test]
     fn availability_marks_ok_available() {
         let record = snap(Some(10.0), Some(300), Some(20.0), Some(10080));
         assert!(matches!(
-            evaluate_snapshot(&record),
+            evaluate_snapshot_with(&record, &UsageThresholds::default()),
             Availability::Available
         ));
     }
+
+    #[test]
+    fn soft_limit_percent_is_clamped() {
+        assert_eq!(parse_soft_limit_percent(Some("95")), 95.0);
+        assert_eq!(parse_soft_limit_percent(Some("150")), 100.0);
+        assert_eq!(parse_soft_limit_percent(Some("0")), 1.0);
+        assert_eq!(parse_soft_limit_percent(Some("abc")), 100.0);
+        assert_eq!(parse_soft_limit_percent(None), 100.0);
+    }
 }
@@ -0,0 +1,599 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use gpttools_core::rpc::types::{
+    AccountExportResult, AccountRestoreConflict, AccountRestoreResult,
+};
+use gpttools_core::storage::{now_ts, Account, ApiKey, Event, Storage, Token};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::storage_helpers::open_storage;
+
+const BACKUP_FORMAT: &str = "gpttools-backup";
+const BACKUP_VERSION: u32 = 1;
+const MIN_PASSPHRASE_CHARS: usize = 8;
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+const KDF_PARALLELISM: u32 = 1;
+// 中文注释：KDF 参数来自备份文件本身，不设上限的话一个伪造的包就能让恢复吃掉几十 GB 内存或长时间占满 CPU。
+const MAX_KDF_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 10;
+const MAX_KDF_PARALLELISM: u32 = 4;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupEnvelope {
+    format: String,
+    version: u32,
+    kdf: String,
+    kdf_memory_kib: u32,
+    kdf_iterations: u32,
+    kdf_parallelism: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+    created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupPayload {
+    version: u32,
+    exported_at: i64,
+    accounts: Vec<BackupAccount>,
+    tokens: Vec<BackupToken>,
+    api_keys: Vec<BackupApiKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupAccount {
+    id: String,
+    label: String,
+    issuer: String,
+    chatgpt_account_id: Option<String>,
+    workspace_id: Option<String>,
+    group_name: Option<String>,
+    plan_type: Option<String>,
+    proxy_url: Option<String>,
+    sort: i64,
+    status: String,
+    created_at: i64,
+    updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupToken {
+    account_id: String,
+    id_token: String,
+    access_token: String,
+    refresh_token: String,
+    api_key_access_token: Option<String>,
+    last_refresh: i64,
+}
+
+// 中文注释：平台 Key 只有哈希落库，备份也只带哈希；恢复后原明文 Key 继续可用，但无法从备份反推出明文。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupApiKey {
+    id: String,
+    name: Option<String>,
+    model_slug: Option<String>,
+    reasoning_effort: Option<String>,
+    client_type: String,
+    protocol_type: String,
+    auth_scheme: String,
+    upstream_base_url: Option<String>,
+    static_headers_json: Option<String>,
+    key_hash: String,
+    status: String,
+    created_at: i64,
+    last_used_at: Option<i64>,
+}
+
+pub(crate) fn export_accounts(passphrase: &str) -> Result<AccountExportResult, String> {
+    // 导出账号池为口令加密的备份包
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let result = export_from_storage(&storage, passphrase)?;
+    let _ = storage.insert_event(&Event {
+        account_id: None,
+        event_type: "account_export".to_string(),
+        message: format!(
+            "accounts={} tokens={} api_keys={}",
+            result.account_count, result.token_count, result.api_key_count
+        ),
+        created_at: now_ts(),
+    });
+    Ok(result)
+}
+
+pub(crate) fn restore_accounts(
+    bundle: &str,
+    passphrase: &str,
+    mode: &str,
+) -> Result<AccountRestoreResult, String> {
+    // 从备份包恢复账号池
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let result = restore_into_storage(&storage, bundle, passphrase, mode)?;
+    let _ = storage.insert_event(&Event {
+        account_id: None,
+        event_type: "account_restore".to_string(),
+        message: format!(
+            "mode={} accounts={} tokens={} api_keys={} conflicts={}",
+            result.mode,
+            result.accounts_restored,
+            result.tokens_restored,
+            result.api_keys_restored,
+            result.conflicts.len()
+        ),
+        created_at: now_ts(),
+    });
+    Ok(result)
+}
+
+pub(crate) fn export_from_storage(
+    storage: &Storage,
+    passphrase: &str,
+) -> Result<AccountExportResult, String> {
+    validate_passphrase(passphrase)?;
+    let accounts = storage.list_accounts().map_err(|e| e.to_string())?;
+    let tokens = storage.list_tokens().map_err(|e| e.to_string())?;
+    let api_keys = storage.list_api_keys().map_err(|e| e.to_string())?;
+    let payload = BackupPayload {
+        version: BACKUP_VERSION,
+        exported_at: now_ts(),
+        accounts: accounts.into_iter().map(BackupAccount::from).collect(),
+        tokens: tokens.into_iter().map(BackupToken::from).collect(),
+        api_keys: api_keys.into_iter().map(BackupApiKey::from).collect(),
+    };
+    let plaintext = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+    let envelope = seal(&plaintext, passphrase)?;
+    Ok(AccountExportResult {
+        bundle: serde_json::to_string(&envelope).map_err(|e| e.to_string())?,
+        account_count: payload.accounts.len(),
+        token_count: payload.tokens.len(),
+        api_key_count: payload.api_keys.len(),
+    })
+}
+
+pub(crate) fn restore_into_storage(
+    storage: &Storage,
+    bundle: &str,
+    passphrase: &str,
+    mode: &str,
+) -> Result<AccountRestoreResult, String> {
+    let replace = match mode.trim().to_ascii_lowercase().as_str() {
+        "" | "merge" => false,
+        "replace" => true,
+        other => return Err(format!("unsupported restore mode: {other}")),
+    };
+    let envelope: BackupEnvelope =
+        serde_json::from_str(bundle).map_err(|e| format!("invalid backup bundle: {e}"))?;
+    // 中文注释：先完成解密与反序列化再动库；replace 模式若在解密失败前清空数据，会直接丢掉整个账号池。
+    let plaintext = open(&envelope, passphrase)?;
+    let payload: BackupPayload =
+        serde_json::from_slice(&plaintext).map_err(|e| format!("invalid backup payload: {e}"))?;
+    if payload.version > BACKUP_VERSION {
+        return Err(format!(
+            "backup version {} is newer than supported {}",
+            payload.version, BACKUP_VERSION
+        ));
+    }
+
+    let mut result = AccountRestoreResult {
+        mode: if replace { "replace" } else { "merge" }.to_string(),
+        accounts_restored: 0,
+        tokens_restored: 0,
+        api_keys_restored: 0,
+        conflicts: Vec::new(),
+    };
+
+    // 中文注释：清空与写入放在同一个事务里；中途任何一步失败都整体回滚，不会留下被清空一半的账号池。
+    let tx = storage.begin_transaction().map_err(|e| e.to_string())?;
+    if replace {
+        storage.clear_account_pool().map_err(|e| e.to_string())?;
+    }
+
+    let existing_accounts: HashSet<String> = storage
+        .list_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    let mut restored_accounts = HashSet::new();
+    for account in payload.accounts {
+        if existing_accounts.contains(&account.id) {
+            result.conflicts.push(conflict(
+                "account",
+                &account.id,
+                "account already exists; kept local copy",
+            ));
+            continue;
+        }
+        let account_id = account.id.clone();
+        storage
+            .insert_account(&Account::from(account))
+            .map_err(|e| e.to_string())?;
+        restored_accounts.insert(account_id);
+        result.accounts_restored += 1;
+    }
+    for token in payload.tokens {
+        if !restored_accounts.contains(&token.account_id) {
+            if !existing_accounts.contains(&token.account_id) {
+                result.conflicts.push(conflict(
+                    "token",
+                    &token.account_id,
+                    "token has no matching account in backup",
+                ));
+            }
+            continue;
+        }
+        storage
+            .insert_token(&Token::from(token))
+            .map_err(|e| e.to_string())?;
+        result.tokens_restored += 1;
+    }
+
+    let existing_keys: HashMap<String, String> = storage
+        .list_api_keys()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|k| (k.key_hash, k.id))
+        .collect();
+    let existing_key_ids: HashSet<&String> = existing_keys.values().collect();
+    for key in payload.api_keys {
+        if existing_key_ids.contains(&key.id) {
+            result
+                .conflicts
+                .push(conflict("apiKey", &key.id, "api key id already exists"));
+            continue;
+        }
+        if let Some(local_id) = existing_keys.get(&key.key_hash) {
+            result.conflicts.push(conflict(
+                "apiKey",
+                &key.id,
+                &format!("same key already exists as {local_id}"),
+            ));
+            continue;
+        }
+        storage
+            .insert_api_key(&ApiKey::from(key))
+            .map_err(|e| e.to_string())?;
+        result.api_keys_restored += 1;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+fn conflict(kind: &str, id: &str, reason: &str) -> AccountRestoreConflict {
+    AccountRestoreConflict {
+        kind: kind.to_string(),
+        id: id.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!(
+            "passphrase must be at least {MIN_PASSPHRASE_CHARS} characters"
+        ));
+    }
+    Ok(())
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<[u8; 32], String> {
+    let params = Params::new(memory_kib, iterations, parallelism, Some(32))
+        .map_err(|e| format!("invalid kdf params: {e}"))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("derive key failed: {e}"))?;
+    Ok(key)
+}
+
+fn seal(plaintext: &[u8], passphrase: &str) -> Result<BackupEnvelope, String> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 24];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let key = derive_key(
+        passphrase,
+        &salt,
+        KDF_MEMORY_KIB,
+        KDF_ITERATIONS,
+        KDF_PARALLELISM,
+    )?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "encrypt backup failed".to_string())?;
+    Ok(BackupEnvelope {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        kdf: "argon2id".to_string(),
+        kdf_memory_kib: KDF_MEMORY_KIB,
+        kdf_iterations: KDF_ITERATIONS,
+        kdf_parallelism: KDF_PARALLELISM,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+        created_at: now_ts(),
+    })
+}
+
+fn open(envelope: &BackupEnvelope, passphrase: &str) -> Result<Vec<u8>, String> {
+    if envelope.format != BACKUP_FORMAT || envelope.kdf != "argon2id" {
+        return Err("unsupported backup format".to_string());
+    }
+    let salt = BASE64
+        .decode(&envelope.salt)
+        .map_err(|_| "invalid backup salt".to_string())?;
+    let nonce = BASE64
+        .decode(&envelope.nonce)
+        .map_err(|_| "invalid backup nonce".to_string())?;
+    if nonce.len() != 24 {
+        return Err("invalid backup nonce".to_string());
+    }
+    let ciphertext = BASE64
+        .decode(&envelope.ciphertext)
+        .map_err(|_| "invalid backup ciphertext".to_string())?;
+    let key = derive_key(
+        passphrase,
+        &salt,
+        envelope.kdf_memory_kib.min(MAX_KDF_MEMORY_KIB),
+        envelope.kdf_iterations.min(MAX_KDF_ITERATIONS),
+        envelope.kdf_parallelism.min(MAX_KDF_PARALLELISM),
+    )?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    cipher
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "wrong passphrase or corrupted backup".to_string())
+}
+
+impl From<Account> for BackupAccount {
+    fn from(value: Account) -> Self {
+        Self {
+            id: value.id,
+            label: value.label,
+            issuer: value.issuer,
+            chatgpt_account_id: value.chatgpt_account_id,
+            workspace_id: value.workspace_id,
+            group_name: value.group_name,
+            plan_type: value.plan_type,
+            proxy_url: value.proxy_url,
+            sort: value.sort,
+            status: value.status,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl From<BackupAccount> for Account {
+    fn from(value: BackupAccount) -> Self {
+        Self {
+            id: value.id,
+            label: value.label,
+            issuer: value.issuer,
+            chatgpt_account_id: value.chatgpt_account_id,
+            workspace_id: value.workspace_id,
+            group_name: value.group_name,
+            plan_type: value.plan_type,
+            proxy_url: value.proxy_url,
+            sort: value.sort,
+            status: value.status,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl From<Token> for BackupToken {
+    fn from(value: Token) -> Self {
+        Self {
+            account_id: value.account_id,
+            id_token: value.id_token,
+            access_token: value.access_token,
+            refresh_token: value.refresh_token,
+            api_key_access_token: value.api_key_access_token,
+            last_refresh: value.last_refresh,
+        }
+    }
+}
+
+impl From<BackupToken> for Token {
+    fn from(value: BackupToken) -> Self {
+        Self {
+            account_id: value.account_id,
+            id_token: value.id_token,
+            access_token: value.access_token,
+            refresh_token: value.refresh_token,
+            api_key_access_token: value.api_key_access_token,
+            last_refresh: value.last_refresh,
+        }
+    }
+}
+
+impl From<ApiKey> for BackupApiKey {
+    fn from(value: ApiKey) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            model_slug: value.model_slug,
+            reasoning_effort: value.reasoning_effort,
+            client_type: value.client_type,
+            protocol_type: value.protocol_type,
+            auth_scheme: value.auth_scheme,
+            upstream_base_url: value.upstream_base_url,
+            static_headers_json: value.static_headers_json,
+            key_hash: value.key_hash,
+            status: value.status,
+            created_at: value.created_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
+
+impl From<BackupApiKey> for ApiKey {
+    fn from(value: BackupApiKey) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            model_slug: value.model_slug,
+            reasoning_effort: value.reasoning_effort,
+            client_type: value.client_type,
+            protocol_type: value.protocol_type,
+            auth_scheme: value.auth_scheme,
+            upstream_base_url: value.upstream_base_url,
+            static_headers_json: value.static_headers_json,
+            key_hash: value.key_hash,
+            status: value.status,
+            created_at: value.created_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_from_storage, restore_into_storage};
+    use gpttools_core::storage::{now_ts, Account, ApiKey, Storage, Token};
+
+    fn seeded_storage() -> Storage {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        storage
+            .insert_account(&Account {
+                id: "acc-1".to_string(),
+                label: "one@example.com".to_string(),
+                issuer: "issuer".to_string(),
+                chatgpt_account_id: None,
+                workspace_id: Some("ws-1".to_string()),
+                group_name: Some("team".to_string()),
+                plan_type: Some("pro".to_string()),
+                proxy_url: None,
+                sort: 7,
+                status: "active".to_string(),
+                created_at: now_ts(),
+                updated_at: now_ts(),
+            })
+            .expect("insert account");
+        storage
+            .insert_token(&Token {
+                account_id: "acc-1".to_string(),
+                id_token: "id".to_string(),
+                access_token: "access".to_string(),
+                refresh_token: "refresh".to_string(),
+                api_key_access_token: None,
+                last_refresh: now_ts(),
+            })
+            .expect("insert token");
+        storage
+            .insert_api_key(&ApiKey {
+                id: "key-1".to_string(),
+                name: Some("ci".to_string()),
+                model_slug: None,
+                reasoning_effort: None,
+                client_type: "codex".to_string(),
+                protocol_type: "openai_compat".to_string(),
+                auth_scheme: "authorization_bearer".to_string(),
+                upstream_base_url: None,
+                static_headers_json: None,
+                key_hash: "hash-1".to_string(),
+                status: "active".to_string(),
+                created_at: now_ts(),
+                last_used_at: None,
+            })
+            .expect("insert key");
+        storage
+    }
+
+    #[test]
+    fn export_then_restore_into_empty_storage_roundtrips() {
+        let source = seeded_storage();
+        let exported = export_from_storage(&source, "correct horse").expect("export");
+        assert_eq!(exported.account_count, 1);
+        assert!(!exported.bundle.contains("refresh"));
+
+        let target = Storage::open_in_memory().expect("open");
+        target.init().expect("init");
+        let restored =
+            restore_into_storage(&target, &exported.bundle, "correct horse", "merge")
+                .expect("restore");
+        assert_eq!(restored.accounts_restored, 1);
+        assert_eq!(restored.tokens_restored, 1);
+        assert_eq!(restored.api_keys_restored, 1);
+        assert!(restored.conflicts.is_empty());
+        let accounts = target.list_accounts().expect("accounts");
+        assert_eq!(accounts[0].sort, 7);
+        assert_eq!(accounts[0].group_name.as_deref(), Some("team"));
+        assert_eq!(
+            target.list_tokens().expect("tokens")[0].refresh_token,
+            "refresh"
+        );
+    }
+
+    #[test]
+    fn restore_rejects_wrong_passphrase_and_short_passphrase() {
+        let source = seeded_storage();
+        assert!(export_from_storage(&source, "short").is_err());
+        let exported = export_from_storage(&source, "correct horse").expect("export");
+        let target = seeded_storage();
+        let err = restore_into_storage(&target, &exported.bundle, "wrong horse", "replace")
+            .expect_err("wrong passphrase");
+        assert!(err.contains("passphrase"));
+        // 解密失败时 replace 模式不能清空本地数据
+        assert_eq!(target.list_accounts().expect("accounts").len(), 1);
+    }
+
+    #[test]
+    fn restore_clamps_kdf_params_from_bundle() {
+        let source = seeded_storage();
+        let exported = export_from_storage(&source, "correct horse").expect("export");
+        let mut envelope: serde_json::Value =
+            serde_json::from_str(&exported.bundle).expect("envelope");
+        envelope["kdfParallelism"] = serde_json::json!(u32::MAX);
+        let target = Storage::open_in_memory().expect("open");
+        target.init().expect("init");
+
+        // 被篡改的参数按上限截断后派生出的密钥对不上，直接报错而不是按包里的参数跑满资源
+        let err = restore_into_storage(&target, &envelope.to_string(), "correct horse", "merge")
+            .expect_err("tampered kdf params");
+        assert!(err.contains("passphrase"));
+        assert!(target.list_accounts().expect("accounts").is_empty());
+    }
+
+    #[test]
+    fn merge_reports_conflicts_and_replace_overwrites() {
+        let source = seeded_storage();
+        let exported = export_from_storage(&source, "correct horse").expect("export");
+        let target = seeded_storage();
+        target.update_account_sort("acc-1", 1).expect("update sort");
+
+        let merged = restore_into_storage(&target, &exported.bundle, "correct horse", "merge")
+            .expect("merge");
+        assert_eq!(merged.accounts_restored, 0);
+        assert_eq!(merged.conflicts.len(), 2);
+        assert_eq!(merged.conflicts[0].kind, "account");
+        assert_eq!(target.list_accounts().expect("accounts")[0].sort, 1);
+
+        let replaced =
+            restore_into_storage(&target, &exported.bundle, "correct horse", "replace")
+                .expect("replace");
+        assert_eq!(replaced.accounts_restored, 1);
+        assert!(replaced.conflicts.is_empty());
+        assert_eq!(target.list_accounts().expect("accounts")[0].sort, 7);
+    }
+}
@@ -0,0 +1,450 @@
+use gpttools_core::auth::{DEFAULT_CLIENT_ID, DEFAULT_ISSUER};
+use gpttools_core::rpc::types::{AccountImportItem, AccountImportResult};
+use gpttools_core::storage::{now_ts, Event, Storage, Token};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::account_proxy::account_proxy_url;
+use crate::auth_tokens::{account_from_tokens, obtain_api_key};
+use crate::storage_helpers::open_storage;
+use crate::usage_http::refresh_access_token;
+
+const MAX_IMPORT_FILES: usize = 500;
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct AccountImportOptions {
+    pub(crate) group_name: Option<String>,
+    pub(crate) tags: Option<String>,
+    pub(crate) validate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImportedTokens {
+    pub(crate) id_token: String,
+    pub(crate) access_token: String,
+    pub(crate) refresh_token: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct ImportEntry {
+    pub(crate) source: String,
+    pub(crate) tokens: Result<ImportedTokens, String>,
+}
+
+pub(crate) fn import_accounts(
+    contents: Vec<String>,
+    documents: Vec<Value>,
+    path: Option<&str>,
+    options: AccountImportOptions,
+) -> Result<AccountImportResult, String> {
+    // 导入 auth.json / token 包并逐条返回结果
+    let mut entries = Vec::new();
+    for (idx, raw) in contents.iter().enumerate() {
+        let source = format!("contents[{idx}]");
+        match serde_json::from_str::<Value>(raw) {
+            Ok(value) => entries.extend(entries_from_document(&source, &value)),
+            Err(err) => entries.push(ImportEntry {
+                source,
+                tokens: Err(format!("invalid json: {err}")),
+            }),
+        }
+    }
+    for (idx, value) in documents.iter().enumerate() {
+        entries.extend(entries_from_document(&format!("documents[{idx}]"), value));
+    }
+    if let Some(path) = path.map(str::trim).filter(|v| !v.is_empty()) {
+        entries.extend(entries_from_path(path)?);
+    }
+    if entries.is_empty() {
+        return Err("no auth documents to import".to_string());
+    }
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    Ok(import_entries(&storage, entries, &options))
+}
+
+pub(crate) fn entries_from_document(source: &str, value: &Value) -> Vec<ImportEntry> {
+    // 中文注释：同时兼容 Codex CLI 的 auth.json（tokens 嵌套）、扁平 token 包与批量数组；
+    // 不展开数组会让一次导出的多账号包只能导入第一条。
+    if let Some(items) = value
+        .as_array()
+        .or_else(|| value.get("accounts").and_then(|v| v.as_array()))
+    {
+        return items
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, item)| entries_from_document(&format!("{source}[{idx}]"), item))
+            .collect();
+    }
+    vec![ImportEntry {
+        source: source.to_string(),
+        tokens: parse_imported_tokens(value),
+    }]
+}
+
+pub(crate) fn parse_imported_tokens(value: &Value) -> Result<ImportedTokens, String> {
+    let tokens = value
+        .get("tokens")
+        .filter(|v| v.is_object())
+        .unwrap_or(value);
+    let id_token = token_field(tokens, &["id_token", "idToken"])
+        .ok_or_else(|| "missing id_token".to_string())?;
+    let refresh_token = token_field(tokens, &["refresh_token", "refreshToken"])
+        .ok_or_else(|| "missing refresh_token".to_string())?;
+    let access_token = token_field(tokens, &["access_token", "accessToken"]).unwrap_or_default();
+    Ok(ImportedTokens {
+        id_token,
+        access_token,
+        refresh_token,
+    })
+}
+
+fn token_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| value.get(*key))
+        .filter_map(|v| v.as_str())
+        .map(str::trim)
+        .find(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+fn entries_from_path(path: &str) -> Result<Vec<ImportEntry>, String> {
+    // 读取单个文件或目录下的 *.json
+    let path = expand_home(path);
+    if path.is_file() {
+        return Ok(entries_from_file(&path));
+    }
+    if !path.is_dir() {
+        return Err(format!("import path not found: {}", path.display()));
+    }
+    let mut files = std::fs::read_dir(&path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| ext.eq_ignore_ascii_case("json"))
+                    .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("no json files in {}", path.display()));
+    }
+    if files.len() > MAX_IMPORT_FILES {
+        return Err(format!(
+            "too many json files in {} (max {MAX_IMPORT_FILES})",
+            path.display()
+        ));
+    }
+    Ok(files
+        .iter()
+        .flat_map(|file| entries_from_file(file))
+        .collect())
+}
+
+fn entries_from_file(path: &Path) -> Vec<ImportEntry> {
+    let source = path.display().to_string();
+    let value = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|raw| {
+            serde_json::from_str::<Value>(&raw).map_err(|e| format!("invalid json: {e}"))
+        });
+    match value {
+        Ok(value) => entries_from_document(&source, &value),
+        Err(err) => vec![ImportEntry {
+            source,
+            tokens: Err(err),
+        }],
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_default();
+        if !home.is_empty() {
+            return Path::new(&home).join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+pub(crate) fn import_entries(
+    storage: &Storage,
+    entries: Vec<ImportEntry>,
+    options: &AccountImportOptions,
+) -> AccountImportResult {
+    let issuer = std::env::var("GPTTOOLS_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string());
+    let client_id =
+        std::env::var("GPTTOOLS_CLIENT_ID").unwrap_or_else(|_| DEFAULT_CLIENT_ID.to_string());
+    let existing_ids: HashSet<String> = storage
+        .list_accounts()
+        .map(|accounts| accounts.into_iter().map(|a| a.id).collect())
+        .unwrap_or_default();
+    let mut seen = HashSet::new();
+    let mut result = AccountImportResult {
+        items: Vec::new(),
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        failed: 0,
+    };
+
+    for entry in entries {
+        let item = import_entry(
+            storage,
+            entry,
+            options,
+            &issuer,
+            &client_id,
+            &existing_ids,
+            &mut seen,
+        );
+        match item.status.as_str() {
+            "created" => result.created += 1,
+            "updated" => result.updated += 1,
+            "duplicate" => result.skipped += 1,
+            _ => result.failed += 1,
+        }
+        result.items.push(item);
+    }
+    result
+}
+
+fn import_entry(
+    storage: &Storage,
+    entry: ImportEntry,
+    options: &AccountImportOptions,
+    issuer: &str,
+    client_id: &str,
+    existing_ids: &HashSet<String>,
+    seen: &mut HashSet<String>,
+) -> AccountImportItem {
+    let failed =
+        |account_id: Option<String>, label: Option<String>, err: String| AccountImportItem {
+            source: entry.source.clone(),
+            account_id,
+            label,
+            status: "failed".to_string(),
+            error: Some(err),
+        };
+    let mut tokens = match entry.tokens.clone() {
+        Ok(tokens) => tokens,
+        Err(err) => return failed(None, None, err),
+    };
+    let mut account = match account_from_tokens(
+        &tokens.id_token,
+        &tokens.access_token,
+        issuer,
+        options.tags.as_deref(),
+        options.group_name.clone(),
+    ) {
+        Ok(account) => account,
+        Err(err) => return failed(None, None, err),
+    };
+    // 中文注释：同一批次里重复的 account_key 只导入第一条，否则后一条会用旧 refresh_token 覆盖前一条。
+    if !seen.insert(account.id.clone()) {
+        return AccountImportItem {
+            source: entry.source.clone(),
+            account_id: Some(account.id),
+            label: Some(account.label),
+            status: "duplicate".to_string(),
+            error: None,
+        };
+    }
+
+    let mut api_key_access_token = None;
+    if options.validate {
+        // 中文注释：刷新一次既验证 refresh_token 可用，也拿到新的 access_token；
+        // refresh_token 会轮换，导入后原 CLI 里的副本将失效，需要在界面上提示。
+        // 中文注释：覆盖导入已有账号时沿用它的出站代理校验，避免一次直连就把账号出口暴露给上游。
+        let proxy_url = account_proxy_url(storage, &account.id);
+        match refresh_access_token(
+            issuer,
+            client_id,
+            &tokens.refresh_token,
+            proxy_url.as_deref(),
+        ) {
+            Ok(refreshed) => {
+                tokens.access_token = refreshed.access_token;
+                if let Some(refresh_token) = refreshed.refresh_token {
+                    tokens.refresh_token = refresh_token;
+                }
+                if let Some(id_token) = refreshed.id_token {
+                    tokens.id_token = id_token;
+                }
+                api_key_access_token = obtain_api_key(
+                    issuer,
+                    client_id,
+                    &tokens.id_token,
+                    proxy_url.as_deref(),
+                )
+                .ok();
+            }
+            Err(err) => return failed(Some(account.id), Some(account.label), err),
+        }
+    } else if tokens.access_token.is_empty() {
+        return failed(
+            Some(account.id),
+            Some(account.label),
+            "missing access_token (enable validate to refresh it)".to_string(),
+        );
+    }
+
+    let updated = existing_ids.contains(&account.id);
+    if updated {
+        // 中文注释：已有账号只替换 token 与元数据，保留排序、创建时间、原分组、出站代理与状态，避免导入打乱现有编排；
+        // 状态也不能重置为 active，否则因用量耗尽或 refresh_token 失效而停用的账号会被重新放进号池。
+        if let Ok(Some(current)) = storage.find_account(&account.id) {
+            account.sort = current.sort;
+            account.created_at = current.created_at;
+            account.proxy_url = current.proxy_url;
+            account.status = current.status;
+            if account.group_name.is_none() {
+                account.group_name = current.group_name;
+            }
+        }
+    }
+    if let Err(err) = storage.insert_account(&account) {
+        return failed(Some(account.id), Some(account.label), err.to_string());
+    }
+    let token = Token {
+        account_id: account.id.clone(),
+        id_token: tokens.id_token,
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        api_key_access_token,
+        last_refresh: now_ts(),
+    };
+    let previous_refresh_token = storage
+        .find_token(&account.id)
+        .ok()
+        .flatten()
+        .map(|current| current.refresh_token);
+    if let Err(err) = storage.insert_token(&token) {
+        return failed(Some(account.id), Some(account.label), err.to_string());
+    }
+    // 中文注释：覆盖导入会替换这个身份的 token，它名下的 workspace 条目要跟着换，否则还拿着可能已被轮换作废的旧值。
+    if let Some(previous) = previous_refresh_token.as_deref() {
+        let _ = storage.propagate_refreshed_token(&token, previous);
+    }
+    let status = if updated { "updated" } else { "created" };
+    let _ = storage.insert_event(&Event {
+        account_id: Some(account.id.clone()),
+        event_type: "account_import".to_string(),
+        message: format!("status={status} source={}", entry.source),
+        created_at: now_ts(),
+    });
+    AccountImportItem {
+        source: entry.source,
+        account_id: Some(account.id),
+        label: Some(account.label),
+        status: status.to_string(),
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        entries_from_document, import_entries, parse_imported_tokens, AccountImportOptions,
+    };
+    use gpttools_core::storage::Storage;
+
+    // payload: {"sub":"user-1","email":"test@example.com"}
+    const ID_TOKEN: &str =
+        "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiJ1c2VyLTEiLCJlbWFpbCI6InRlc3RAZXhhbXBsZS5jb20ifQ.sig";
+
+    fn auth_json(access_token: &str) -> serde_json::Value {
+        serde_json::json!({
+            "OPENAI_API_KEY": null,
+            "tokens": {
+                "id_token": ID_TOKEN,
+                "access_token": access_token,
+                "refresh_token": "refresh-1",
+                "account_id": "acc-1"
+            },
+            "last_refresh": "2025-01-01T00:00:00Z"
+        })
+    }
+
+    #[test]
+    fn parse_accepts_codex_auth_json_and_flat_bundle() {
+        let nested = parse_imported_tokens(&auth_json("access-1")).expect("nested");
+        assert_eq!(nested.refresh_token, "refresh-1");
+        assert_eq!(nested.access_token, "access-1");
+
+        let flat = parse_imported_tokens(&serde_json::json!({
+            "idToken": ID_TOKEN,
+            "refreshToken": "refresh-2"
+        }))
+        .expect("flat");
+        assert_eq!(flat.refresh_token, "refresh-2");
+        assert!(flat.access_token.is_empty());
+
+        let err = parse_imported_tokens(&serde_json::json!({ "tokens": { "id_token": ID_TOKEN } }))
+            .expect_err("missing refresh");
+        assert!(err.contains("refresh_token"));
+    }
+
+    #[test]
+    fn import_deduplicates_by_account_key_and_updates_existing() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let options = AccountImportOptions {
+            group_name: Some("team".to_string()),
+            ..Default::default()
+        };
+
+        let bundle = serde_json::json!([auth_json("access-1"), auth_json("access-2")]);
+        let result = import_entries(&storage, entries_from_document("bundle", &bundle), &options);
+        assert_eq!(result.created, 1);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.items[0].account_id.as_deref(), Some("user-1"));
+        assert_eq!(result.items[0].label.as_deref(), Some("test@example.com"));
+        assert_eq!(result.items[1].status, "duplicate");
+        storage
+            .update_account_status("user-1", "inactive")
+            .expect("disable account");
+
+        let again = import_entries(
+            &storage,
+            entries_from_document("again", &auth_json("access-3")),
+            &AccountImportOptions::default(),
+        );
+        assert_eq!(again.updated, 1);
+        let accounts = storage.list_accounts().expect("accounts");
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].group_name.as_deref(), Some("team"));
+        assert_eq!(accounts[0].status, "inactive");
+        let tokens = storage.list_tokens().expect("tokens");
+        assert_eq!(tokens[0].access_token, "access-3");
+    }
+
+    #[test]
+    fn import_reports_failures_per_entry() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let bundle = serde_json::json!({
+            "accounts": [
+                { "tokens": { "id_token": "not-a-jwt", "refresh_token": "r", "access_token": "a" } },
+                { "tokens": { "id_token": ID_TOKEN, "refresh_token": "r" } }
+            ]
+        });
+        let result = import_entries(
+            &storage,
+            entries_from_document("bundle", &bundle),
+            &AccountImportOptions::default(),
+        );
+        assert_eq!(result.failed, 2);
+        assert_eq!(result.items[0].source, "bundle[0]");
+        assert!(result.items[1]
+            .error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("access_token"));
+    }
+}
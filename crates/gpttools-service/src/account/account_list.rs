@@ -1,5 +1,6 @@
 use gpttools_core::rpc::types::AccountSummary;
 
+use crate::account_proxy::redact_proxy_url;
 use crate::storage_helpers::open_storage;
 
 pub(crate) fn read_accounts() -> Vec<AccountSummary> {
@@ -18,7 +19,9 @@ pub(crate) fn read_accounts() -> Vec<AccountSummary> {
             id: acc.id,
             label: acc.label,
             group_name: acc.group_name,
+            plan_type: acc.plan_type,
             sort: acc.sort,
+            proxy_url: acc.proxy_url.as_deref().map(redact_proxy_url),
         })
         .collect()
 }
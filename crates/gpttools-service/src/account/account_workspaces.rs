@@ -0,0 +1,386 @@
+use gpttools_core::rpc::types::{AccountWorkspaceItem, AccountWorkspaceListResult};
+use gpttools_core::storage::{now_ts, Account, Event, Storage, Token};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::storage_helpers::{open_storage, token_group_key, workspace_account_key};
+use crate::usage_http::usage_http_client_for_proxy;
+
+pub(crate) const ALL_WORKSPACES: &str = "*";
+const ACCOUNTS_CHECK_PATH: &str = "/backend-api/accounts/check/v4-2023-04-27";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AccountWorkspace {
+    pub(crate) id: String,
+    pub(crate) name: Option<String>,
+    pub(crate) plan_type: Option<String>,
+    pub(crate) is_personal: bool,
+}
+
+pub(crate) fn workspace_selection(workspace_ids: &[String], all: bool) -> Option<String> {
+    // 把登录参数里的 workspace 选择压成会话字段（* 表示全部）
+    if all {
+        return Some(ALL_WORKSPACES.to_string());
+    }
+    let ids = workspace_ids
+        .iter()
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty() && !id.contains(','))
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        None
+    } else {
+        Some(ids.join(","))
+    }
+}
+
+pub(crate) fn is_workspace_selected(selection: &str, workspace_id: &str) -> bool {
+    selection == ALL_WORKSPACES || selection.split(',').any(|id| id == workspace_id)
+}
+
+fn clean_str(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+pub(crate) fn parse_account_workspaces(value: &Value) -> Vec<AccountWorkspace> {
+    // 解析 accounts/check 返回的 workspace 列表
+    let entries: Vec<(Option<&str>, &Value)> = match value.get("accounts") {
+        Some(Value::Object(map)) => map.iter().map(|(key, v)| (Some(key.as_str()), v)).collect(),
+        Some(Value::Array(items)) => items.iter().map(|v| (None, v)).collect(),
+        _ => Vec::new(),
+    };
+    let mut out: Vec<AccountWorkspace> = Vec::new();
+    for (key, entry) in entries {
+        let account = entry.get("account").unwrap_or(entry);
+        // 中文注释：上游会额外给出 "default" 键指向某个已列出的 workspace，必须按真实 account_id 去重，否则同一配额会被建两次。
+        let Some(id) = clean_str(account.get("account_id"))
+            .or_else(|| clean_str(account.get("id")))
+            .or_else(|| key.filter(|k| *k != "default").map(|k| k.to_string()))
+        else {
+            continue;
+        };
+        if out.iter().any(|item| item.id == id) {
+            continue;
+        }
+        let plan_type = clean_str(account.get("plan_type"))
+            .or_else(|| {
+                entry
+                    .get("entitlement")
+                    .and_then(|e| clean_str(e.get("subscription_plan")))
+            })
+            .map(|v| v.to_ascii_lowercase());
+        let is_personal = account
+            .get("structure")
+            .and_then(|v| v.as_str())
+            .map(|v| v.eq_ignore_ascii_case("personal"))
+            .unwrap_or(false);
+        out.push(AccountWorkspace {
+            id,
+            name: clean_str(account.get("name")),
+            plan_type,
+            is_personal,
+        });
+    }
+    if let Some(ordering) = value.get("account_ordering").and_then(|v| v.as_array()) {
+        let rank = |id: &str| {
+            ordering
+                .iter()
+                .position(|v| v.as_str() == Some(id))
+                .unwrap_or(usize::MAX)
+        };
+        out.sort_by_key(|item| rank(&item.id));
+    }
+    out
+}
+
+pub(crate) fn fetch_account_workspaces(
+    access_token: &str,
+    proxy_url: Option<&str>,
+) -> Result<Vec<AccountWorkspace>, String> {
+    // 用 access_token 枚举当前身份可用的 workspace（按账号代理出站）
+    let base_url = std::env::var("GPTTOOLS_USAGE_BASE_URL")
+        .unwrap_or_else(|_| "https://chatgpt.com".to_string());
+    let url = format!("{}{}", base_url.trim_end_matches('/'), ACCOUNTS_CHECK_PATH);
+    let resp = usage_http_client_for_proxy(proxy_url)?
+        .get(&url)
+        .header("Authorization", format!("Bearer {access_token}"))
+        .send()
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("accounts check status {}", resp.status()));
+    }
+    let value: Value = resp.json().map_err(|e| e.to_string())?;
+    Ok(parse_account_workspaces(&value))
+}
+
+fn workspace_header(account: &Account) -> Option<&str> {
+    account
+        .chatgpt_account_id
+        .as_deref()
+        .or(account.workspace_id.as_deref())
+}
+
+fn workspace_entries(
+    storage: &Storage,
+    group_key: &str,
+) -> Result<HashMap<String, String>, String> {
+    // 读取同一身份下已存在的条目：workspace -> 条目 id
+    let accounts = storage.list_accounts().map_err(|e| e.to_string())?;
+    Ok(accounts
+        .iter()
+        .filter(|account| token_group_key(&account.id) == group_key)
+        .filter_map(|account| {
+            workspace_header(account).map(|ws| (ws.to_string(), account.id.clone()))
+        })
+        .collect())
+}
+
+pub(crate) fn add_workspace_entries(
+    storage: &Storage,
+    primary: &Account,
+    token: &Token,
+    workspaces: &[AccountWorkspace],
+    selection: &str,
+) -> Result<Vec<String>, String> {
+    // 为选中的 workspace 各建一个共享 token 的号池条目
+    let group_key = token_group_key(&primary.id);
+    let existing = workspace_entries(storage, group_key)?;
+    let mut added = Vec::new();
+    for workspace in workspaces {
+        if !is_workspace_selected(selection, &workspace.id) {
+            continue;
+        }
+        let entry_id = match existing.get(&workspace.id) {
+            Some(id) => id.clone(),
+            None => {
+                let id = workspace_account_key(group_key, &workspace.id);
+                let label = match workspace.name.as_deref() {
+                    Some(name) => format!("{} ({name})", primary.label),
+                    None => format!("{} ({})", primary.label, workspace.id),
+                };
+                storage
+                    .insert_account(&Account {
+                        id: id.clone(),
+                        label,
+                        issuer: primary.issuer.clone(),
+                        chatgpt_account_id: Some(workspace.id.clone()),
+                        workspace_id: Some(workspace.id.clone()),
+                        group_name: primary.group_name.clone(),
+                        plan_type: workspace.plan_type.clone(),
+                        proxy_url: primary.proxy_url.clone(),
+                        sort: primary.sort,
+                        status: "active".to_string(),
+                        created_at: now_ts(),
+                        updated_at: now_ts(),
+                    })
+                    .map_err(|e| e.to_string())?;
+                added.push(id.clone());
+                id
+            }
+        };
+        if entry_id == primary.id {
+            continue;
+        }
+        // 中文注释：条目已存在也要覆盖 token；重新登录后旧 refresh_token 已失效，兄弟条目继续用旧值会刷新失败。
+        storage
+            .insert_token(&Token {
+                account_id: entry_id,
+                ..token.clone()
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    if !added.is_empty() {
+        let _ = storage.insert_event(&Event {
+            account_id: Some(primary.id.clone()),
+            event_type: "account_workspace_add".to_string(),
+            message: format!("added={}", added.join(",")),
+            created_at: now_ts(),
+        });
+    }
+    Ok(added)
+}
+
+pub(crate) fn attach_selected_workspaces(
+    storage: &Storage,
+    primary: &Account,
+    token: &Token,
+    selection: &str,
+) {
+    // 登录完成后按会话里的选择补建 workspace 条目；失败只记事件，不影响主条目登录
+    let result = fetch_account_workspaces(&token.access_token, primary.proxy_url.as_deref())
+        .and_then(|workspaces| {
+            add_workspace_entries(storage, primary, token, &workspaces, selection)
+        });
+    if let Err(err) = result {
+        let _ = storage.insert_event(&Event {
+            account_id: Some(primary.id.clone()),
+            event_type: "account_workspace_failed".to_string(),
+            message: err,
+            created_at: now_ts(),
+        });
+    }
+}
+
+fn load_account_and_token(storage: &Storage, account_id: &str) -> Result<(Account, Token), String> {
+    if account_id.is_empty() {
+        return Err("missing accountId".to_string());
+    }
+    let account = storage
+        .find_account(account_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "account not found".to_string())?;
+    let token = storage
+        .find_token(account_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "account token not found".to_string())?;
+    Ok((account, token))
+}
+
+fn to_items(
+    workspaces: Vec<AccountWorkspace>,
+    entries: &HashMap<String, String>,
+) -> AccountWorkspaceListResult {
+    let items = workspaces
+        .into_iter()
+        .map(|workspace| AccountWorkspaceItem {
+            account_id: entries.get(&workspace.id).cloned(),
+            workspace_id: workspace.id,
+            name: workspace.name,
+            plan_type: workspace.plan_type,
+            is_personal: workspace.is_personal,
+        })
+        .collect();
+    AccountWorkspaceListResult { items }
+}
+
+pub(crate) fn list_workspaces(account_id: &str) -> Result<AccountWorkspaceListResult, String> {
+    // 列出账号身份可用的 workspace，并标记已加入号池的条目
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let (account, token) = load_account_and_token(&storage, account_id)?;
+    let workspaces =
+        fetch_account_workspaces(&token.access_token, account.proxy_url.as_deref())?;
+    let entries = workspace_entries(&storage, token_group_key(&account.id))?;
+    Ok(to_items(workspaces, &entries))
+}
+
+pub(crate) fn add_workspaces(
+    account_id: &str,
+    workspace_ids: &[String],
+    all: bool,
+) -> Result<AccountWorkspaceListResult, String> {
+    // 为已有账号追加 workspace 条目
+    let selection = workspace_selection(workspace_ids, all)
+        .ok_or_else(|| "missing workspaceIds".to_string())?;
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let (account, token) = load_account_and_token(&storage, account_id)?;
+    let workspaces =
+        fetch_account_workspaces(&token.access_token, account.proxy_url.as_deref())?;
+    add_workspace_entries(&storage, &account, &token, &workspaces, &selection)?;
+    let entries = workspace_entries(&storage, token_group_key(&account.id))?;
+    Ok(to_items(workspaces, &entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_workspace_entries, is_workspace_selected, parse_account_workspaces,
+        workspace_selection, AccountWorkspace,
+    };
+    use gpttools_core::storage::{now_ts, Account, Storage, Token};
+
+    #[test]
+    fn parse_workspaces_dedupes_default_and_follows_ordering() {
+        let value = serde_json::json!({
+            "accounts": {
+                "default": { "account": { "account_id": "org-team", "name": "Team", "structure": "workspace" } },
+                "acc-personal": {
+                    "account": { "account_id": "acc-personal", "structure": "personal" },
+                    "entitlement": { "subscription_plan": "chatgptplusplan" }
+                },
+                "org-team": { "account": { "account_id": "org-team", "name": "Team", "plan_type": "Team" } }
+            },
+            "account_ordering": ["acc-personal", "org-team"]
+        });
+        let workspaces = parse_account_workspaces(&value);
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(workspaces[0].id, "acc-personal");
+        assert!(workspaces[0].is_personal);
+        assert_eq!(workspaces[0].plan_type.as_deref(), Some("chatgptplusplan"));
+        assert_eq!(workspaces[1].name.as_deref(), Some("Team"));
+        assert!(parse_account_workspaces(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn workspace_selection_supports_all_and_explicit_ids() {
+        assert_eq!(workspace_selection(&[], true).as_deref(), Some("*"));
+        assert_eq!(workspace_selection(&[" ".to_string()], false), None);
+        let selection =
+            workspace_selection(&["org-a".to_string(), "org-b".to_string()], false).expect("sel");
+        assert!(is_workspace_selected(&selection, "org-b"));
+        assert!(!is_workspace_selected(&selection, "org-c"));
+        assert!(is_workspace_selected("*", "org-c"));
+    }
+
+    #[test]
+    fn add_entries_shares_tokens_and_skips_primary_workspace() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let primary = Account {
+            id: "user-1".to_string(),
+            label: "test@example.com".to_string(),
+            issuer: "https://auth.openai.com".to_string(),
+            chatgpt_account_id: Some("acc-personal".to_string()),
+            workspace_id: Some("acc-personal".to_string()),
+            group_name: Some("pool".to_string()),
+            plan_type: Some("plus".to_string()),
+            proxy_url: None,
+            sort: 3,
+            status: "active".to_string(),
+            created_at: now_ts(),
+            updated_at: now_ts(),
+        };
+        storage.insert_account(&primary).expect("insert account");
+        let token = Token {
+            account_id: primary.id.clone(),
+            id_token: "id".to_string(),
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            api_key_access_token: None,
+            last_refresh: now_ts(),
+        };
+        storage.insert_token(&token).expect("insert token");
+        let workspace = |id: &str, name: Option<&str>| AccountWorkspace {
+            id: id.to_string(),
+            name: name.map(|v| v.to_string()),
+            plan_type: Some("team".to_string()),
+            is_personal: false,
+        };
+        let workspaces = vec![
+            workspace("acc-personal", None),
+            workspace("org-team", Some("Team")),
+        ];
+
+        let added =
+            add_workspace_entries(&storage, &primary, &token, &workspaces, "*").expect("add");
+        assert_eq!(added, vec!["user-1::ws::org-team".to_string()]);
+        let again =
+            add_workspace_entries(&storage, &primary, &token, &workspaces, "*").expect("add again");
+        assert!(again.is_empty());
+
+        let accounts = storage.list_accounts().expect("accounts");
+        let entry = accounts
+            .iter()
+            .find(|a| a.id == "user-1::ws::org-team")
+            .expect("entry");
+        assert_eq!(entry.workspace_id.as_deref(), Some("org-team"));
+        assert_eq!(entry.label, "test@example.com (Team)");
+        assert_eq!(entry.group_name.as_deref(), Some("pool"));
+        let tokens = storage.list_tokens().expect("tokens");
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|t| t.refresh_token == "refresh"));
+    }
+}
@@ -0,0 +1,184 @@
+use gpttools_core::rpc::methods;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AdminScope {
+    ReadOnly,
+    KeyManagement,
+    AccountManagement,
+    Full,
+}
+
+/// 单个 RPC 方法所需的权限；只读以外的方法都视为变更操作并写审计事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RpcPermission {
+    Read,
+    ManageKeys,
+    ManageAccounts,
+    Admin,
+}
+
+/// 已通过鉴权的 RPC 调用方。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RpcCaller {
+    pub(crate) token_id: Option<String>,
+    pub(crate) name: String,
+    pub(crate) scope: AdminScope,
+}
+
+impl AdminScope {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "read_only" | "readonly" => Some(Self::ReadOnly),
+            "key_management" => Some(Self::KeyManagement),
+            "account_management" => Some(Self::AccountManagement),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read_only",
+            Self::KeyManagement => "key_management",
+            Self::AccountManagement => "account_management",
+            Self::Full => "full",
+        }
+    }
+
+    pub(crate) fn allows(self, permission: RpcPermission) -> bool {
+        match self {
+            Self::Full => true,
+            Self::KeyManagement => {
+                matches!(permission, RpcPermission::Read | RpcPermission::ManageKeys)
+            }
+            Self::AccountManagement => {
+                matches!(
+                    permission,
+                    RpcPermission::Read | RpcPermission::ManageAccounts
+                )
+            }
+            Self::ReadOnly => permission == RpcPermission::Read,
+        }
+    }
+}
+
+impl RpcPermission {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read_only",
+            Self::ManageKeys => "key_management",
+            Self::ManageAccounts => "account_management",
+            Self::Admin => "full",
+        }
+    }
+}
+
+impl RpcCaller {
+    pub(crate) fn local_full_access(name: &str) -> Self {
+        Self {
+            token_id: None,
+            name: name.to_string(),
+            scope: AdminScope::Full,
+        }
+    }
+}
+
+pub(crate) fn required_permission(method: &str) -> RpcPermission {
+    // 按方法名划分权限；新增方法未登记时默认只有 full 可调用
+    match method {
+        methods::INITIALIZE
+        | methods::ACCOUNT_LIST
+        | methods::ACCOUNT_LOGIN_STATUS
+        | methods::ACCOUNT_WORKSPACE_LIST
+        | methods::ACCOUNT_USAGE_READ
+        | methods::ACCOUNT_USAGE_LIST
+        | methods::ACCOUNT_USAGE_HISTORY
+        | methods::APIKEY_LIST
+        | methods::APIKEY_MODELS
+        | methods::REQUESTLOG_LIST
+        | methods::EVENTS_LIST
+        | methods::STORAGE_INFO => RpcPermission::Read,
+        methods::APIKEY_CREATE
+        | methods::APIKEY_UPDATE_MODEL
+        | methods::APIKEY_DELETE
+        | methods::APIKEY_DISABLE
+        | methods::APIKEY_ENABLE => RpcPermission::ManageKeys,
+        // 中文注释：account/export 会导出 refresh_token 明文，按账号管理权限处理；只读 token 不能拿到账号凭据。
+        methods::ACCOUNT_DELETE
+        | methods::ACCOUNT_UPDATE
+        | methods::ACCOUNT_PROXY_SET
+        | methods::ACCOUNT_IMPORT
+        | methods::ACCOUNT_EXPORT
+        | methods::ACCOUNT_RESTORE
+        | methods::ACCOUNT_LOGIN_START
+        | methods::ACCOUNT_LOGIN_CANCEL
+        | methods::ACCOUNT_LOGIN_COMPLETE
+        | methods::ACCOUNT_WORKSPACE_ADD
+        | methods::ACCOUNT_REAUTH
+        | methods::ACCOUNT_USAGE_REFRESH => RpcPermission::ManageAccounts,
+        _ => RpcPermission::Admin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{required_permission, AdminScope, RpcPermission};
+    use gpttools_core::rpc::methods;
+
+    #[test]
+    fn scopes_cover_their_method_groups_only() {
+        let read = required_permission("account/list");
+        let keys = required_permission("apikey/create");
+        let accounts = required_permission("account/delete");
+        let admin = required_permission("admin/token/create");
+        assert_eq!(read, RpcPermission::Read);
+        assert_eq!(admin, RpcPermission::Admin);
+        assert_eq!(required_permission("some/new/method"), RpcPermission::Admin);
+
+        assert!(AdminScope::ReadOnly.allows(read));
+        assert!(!AdminScope::ReadOnly.allows(keys));
+        assert!(AdminScope::KeyManagement.allows(keys));
+        assert!(!AdminScope::KeyManagement.allows(accounts));
+        assert!(AdminScope::AccountManagement.allows(accounts));
+        assert!(!AdminScope::AccountManagement.allows(keys));
+        assert!(!AdminScope::AccountManagement.allows(admin));
+        assert!(AdminScope::Full.allows(admin));
+    }
+
+    #[test]
+    fn export_requires_account_management() {
+        assert_eq!(
+            required_permission("account/export"),
+            RpcPermission::ManageAccounts
+        );
+        assert!(!AdminScope::ReadOnly.allows(required_permission("account/export")));
+    }
+
+    #[test]
+    fn only_admin_methods_fall_through_to_full_scope() {
+        let admin_only = [
+            methods::REQUESTLOG_CLEAR,
+            methods::ADMIN_TOKEN_LIST,
+            methods::ADMIN_TOKEN_CREATE,
+            methods::ADMIN_TOKEN_REVOKE,
+        ];
+        for method in methods::ALL {
+            let is_admin = required_permission(method) == RpcPermission::Admin;
+            assert_eq!(is_admin, admin_only.contains(method), "{method}");
+        }
+    }
+
+    #[test]
+    fn scope_parse_roundtrips() {
+        for scope in [
+            AdminScope::ReadOnly,
+            AdminScope::KeyManagement,
+            AdminScope::AccountManagement,
+            AdminScope::Full,
+        ] {
+            assert_eq!(AdminScope::parse(scope.as_str()), Some(scope));
+        }
+        assert_eq!(AdminScope::parse("read-only"), Some(AdminScope::ReadOnly));
+        assert_eq!(AdminScope::parse("root"), None);
+    }
+}
@@ -0,0 +1,162 @@
+use gpttools_core::rpc::types::{AdminTokenCreateResult, AdminTokenSummary};
+use gpttools_core::storage::{now_ts, AdminToken, Event};
+use rand::RngCore;
+
+use crate::admin_scope::{AdminScope, RpcCaller};
+use crate::storage_helpers::{generate_platform_key, hash_platform_key, open_storage};
+
+const ADMIN_TOKEN_PREFIX: &str = "gta_";
+const LAST_USED_TOUCH_INTERVAL_SECS: i64 = 60;
+
+fn generate_admin_token_id() -> String {
+    // 生成管理 token 的展示 ID
+    let mut buf = [0u8; 6];
+    rand::rngs::OsRng.fill_bytes(&mut buf);
+    let mut out = String::from("at_");
+    for b in buf {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+pub(crate) fn create_admin_token(
+    name: Option<&str>,
+    scope: Option<&str>,
+) -> Result<AdminTokenCreateResult, String> {
+    // 创建具名管理 token，只落库哈希，明文仅在本次返回
+    let name = name
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| "missing name".to_string())?;
+    let scope = scope
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| "missing scope".to_string())?;
+    let scope = AdminScope::parse(scope).ok_or_else(|| {
+        format!(
+            "invalid scope: {scope} (expected read_only, key_management, account_management or full)"
+        )
+    })?;
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    // 中文注释：加固定前缀便于在日志、剪贴板里一眼认出这是管理凭据，也方便密钥扫描工具识别。
+    let token = format!("{ADMIN_TOKEN_PREFIX}{}", generate_platform_key());
+    let record = AdminToken {
+        id: generate_admin_token_id(),
+        name: name.to_string(),
+        token_hash: hash_platform_key(&token),
+        scope: scope.as_str().to_string(),
+        created_at: now_ts(),
+        last_used_at: None,
+    };
+    storage
+        .insert_admin_token(&record)
+        .map_err(|e| e.to_string())?;
+    Ok(AdminTokenCreateResult {
+        id: record.id,
+        name: record.name,
+        scope: record.scope,
+        token,
+    })
+}
+
+pub(crate) fn read_admin_tokens() -> Vec<AdminTokenSummary> {
+    // 读取管理 token 列表（不含哈希）
+    let Some(storage) = open_storage() else {
+        return Vec::new();
+    };
+    storage
+        .list_admin_tokens()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|token| AdminTokenSummary {
+            id: token.id,
+            name: token.name,
+            scope: token.scope,
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+        })
+        .collect()
+}
+
+pub(crate) fn revoke_admin_token(token_id: &str) -> Result<(), String> {
+    // 吊销管理 token，之后持有者的请求立即返回 401
+    if token_id.is_empty() {
+        return Err("missing id".to_string());
+    }
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    if !storage
+        .delete_admin_token(token_id)
+        .map_err(|e| e.to_string())?
+    {
+        return Err("admin token not found".to_string());
+    }
+    Ok(())
+}
+
+pub(crate) fn resolve_rpc_caller(token: &str) -> Option<RpcCaller> {
+    // 先比对本机主 token，再按哈希查具名管理 token
+    if crate::rpc_auth_token_matches(token) {
+        return Some(RpcCaller::local_full_access("local"));
+    }
+    let token = token.trim();
+    if !token.starts_with(ADMIN_TOKEN_PREFIX) {
+        return None;
+    }
+    let storage = open_storage()?;
+    let record = storage
+        .find_admin_token_by_hash(&hash_platform_key(token))
+        .ok()??;
+    // 中文注释：库里 scope 由 CHECK 约束保证合法；万一解析失败按无权限处理，不能默认放大成 full。
+    let scope = AdminScope::parse(&record.scope)?;
+    // 中文注释：最近使用时间精确到分钟就够了；每次 RPC 都写一遍会让只读面板轮询也不停占写锁。
+    if last_used_is_stale(record.last_used_at, now_ts()) {
+        let _ = storage.update_admin_token_last_used(&record.id);
+    }
+    Some(RpcCaller {
+        token_id: Some(record.id),
+        name: record.name,
+        scope,
+    })
+}
+
+fn last_used_is_stale(last_used_at: Option<i64>, now: i64) -> bool {
+    last_used_at.is_none_or(|last| now - last >= LAST_USED_TOUCH_INTERVAL_SECS)
+}
+
+pub(crate) fn record_admin_audit(
+    caller: &RpcCaller,
+    method: &str,
+    account_id: Option<&str>,
+    outcome: &str,
+) {
+    // 变更类 RPC 的审计事件：谁、以什么权限、调了什么、结果如何
+    let Some(storage) = open_storage() else {
+        return;
+    };
+    let caller_label = match caller.token_id.as_deref() {
+        Some(token_id) => format!("{} ({token_id})", caller.name),
+        None => caller.name.clone(),
+    };
+    let _ = storage.insert_event(&Event {
+        account_id: account_id.map(str::to_string),
+        event_type: "admin_audit".to_string(),
+        message: format!(
+            "method={method} caller={caller_label} scope={} result={outcome}",
+            caller.scope.as_str()
+        ),
+        created_at: now_ts(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::last_used_is_stale;
+
+    #[test]
+    fn last_used_is_touched_at_most_once_a_minute() {
+        assert!(last_used_is_stale(None, 1_000));
+        assert!(!last_used_is_stale(Some(1_000), 1_000));
+        assert!(!last_used_is_stale(Some(1_000), 1_059));
+        assert!(last_used_is_stale(Some(1_000), 1_060));
+    }
+}
@@ -4,6 +4,7 @@ use tiny_http::Response;
 use tiny_http::Server;
 use url::Url;
 
+use crate::auth_session::LOGIN_SESSION_EXPIRED;
 use crate::auth_tokens::complete_login;
 
 
@@ -22,12 +23,19 @@ pub(crate) fn resolve_redirect_uri() -> Option<String> {
 }
 
 pub(crate) fn handle_login_request(request: Request) -> Result<(), String> {
-    // 解析回调地址与参数
-    let url = Url::parse(&format!("http://localhost{}", request.url()))
-        .map_err(|e| format!("invalid url: {e}"))?;
+    let (status_code, message) = login_callback_response(request.url());
+    let _ = request.respond(Response::from_string(message).with_status_code(status_code));
+    Ok(())
+}
+
+pub(crate) fn login_callback_response(request_url: &str) -> (u16, String) {
+    // 解析回调地址与参数；主服务与独立登录端口共用同一套响应
+    let url = match Url::parse(&format!("http://localhost{request_url}")) {
+        Ok(url) => url,
+        Err(err) => return (400, format!("invalid url: {err}")),
+    };
     if url.path() != "/auth/callback" {
-        let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
-        return Ok(());
+        return (404, "Not Found".to_string());
     }
 
     let code = url
@@ -40,25 +48,18 @@ pub(crate) fn handle_login_request(request: Request) -> Result<(), String> {
         .map(|(_, v)| v.into_owned());
 
     let (Some(code), Some(state)) = (code, state) else {
-        let _ = request.respond(Response::from_string("Missing code/state").with_status_code(400));
-        return Ok(());
+        return (400, "Missing code/state".to_string());
     };
 
     // 完成登录流程并响应浏览器
-    let result = handle_login_callback_params(&code, &state);
-    match result {
-        Ok(_) => {
-            let _ = request.respond(Response::from_string(
-                "Login success. You can close this window.",
-            ));
-        }
-        Err(err) => {
-            let _ = request.respond(
-                Response::from_string(format!("Login failed: {err}")).with_status_code(500),
-            );
-        }
+    match handle_login_callback_params(&code, &state) {
+        Ok(_) => (200, "Login success. You can close this window.".to_string()),
+        Err(err) if err == LOGIN_SESSION_EXPIRED => (
+            410,
+            "Login link expired. Please start the login again from GPTTools.".to_string(),
+        ),
+        Err(err) => (500, format!("Login failed: {err}")),
     }
-    Ok(())
 }
 
 pub(crate) fn handle_login_callback_params(code: &str, state: &str) -> Result<(), String> {
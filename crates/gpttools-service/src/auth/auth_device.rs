@@ -0,0 +1,389 @@
+use gpttools_core::auth::{device_redirect_uri, device_token_url, device_usercode_url};
+use gpttools_core::storage::{now_ts, Event};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::auth_session::finish_pending_session;
+use crate::auth_tokens::complete_login_with_verifier;
+use crate::storage_helpers::open_storage;
+use crate::usage_http::usage_http_client;
+
+const DEFAULT_DEVICE_POLL_INTERVAL_SECS: u64 = 5;
+const MAX_DEVICE_POLL_INTERVAL_SECS: u64 = 60;
+const SLOW_DOWN_STEP_SECS: u64 = 5;
+const DEFAULT_DEVICE_CODE_TTL_SECS: i64 = 15 * 60;
+
+static DEVICE_LOGINS: OnceLock<Mutex<HashMap<String, DeviceLoginProgress>>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DeviceCode {
+    pub(crate) device_auth_id: String,
+    pub(crate) user_code: String,
+    pub(crate) interval_secs: u64,
+    pub(crate) expires_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DeviceLoginProgress {
+    pub(crate) user_code: String,
+    pub(crate) verification_url: String,
+    pub(crate) interval_secs: u64,
+    pub(crate) expires_at: i64,
+    pub(crate) last_polled_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DevicePoll {
+    Pending,
+    SlowDown,
+    Authorized {
+        authorization_code: String,
+        code_verifier: String,
+    },
+    Expired,
+    Denied(String),
+}
+
+pub(crate) fn request_device_code(issuer: &str, client_id: &str) -> Result<DeviceCode, String> {
+    // 向授权服务申请设备码（user code）
+    let resp = usage_http_client()
+        .post(device_usercode_url(issuer))
+        .json(&serde_json::json!({ "client_id": client_id }))
+        .send()
+        .map_err(|e| e.to_string())?;
+    let status = resp.status();
+    let body = resp.text().unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!(
+            "device code request failed with status {} body {}",
+            status, body
+        ));
+    }
+    parse_device_code_response(&body, now_ts())
+}
+
+pub(crate) fn parse_device_code_response(body: &str, now: i64) -> Result<DeviceCode, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("invalid device code response: {e}"))?;
+    let device_auth_id = json_str(&value, &["device_auth_id", "device_code"])
+        .ok_or_else(|| "device code response missing device_auth_id".to_string())?;
+    let user_code = json_str(&value, &["user_code", "usercode"])
+        .ok_or_else(|| "device code response missing user_code".to_string())?;
+    // 中文注释：interval 在不同版本里可能是字符串或数字，统一兜底为默认值，避免轮询过快被限流。
+    let interval_secs = json_u64(&value, "interval")
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_DEVICE_POLL_INTERVAL_SECS)
+        .min(MAX_DEVICE_POLL_INTERVAL_SECS);
+    let expires_at = json_u64(&value, "expires_at")
+        .map(|ts| ts as i64)
+        .or_else(|| json_u64(&value, "expires_in").map(|secs| now + secs as i64))
+        .unwrap_or(now + DEFAULT_DEVICE_CODE_TTL_SECS);
+    Ok(DeviceCode {
+        device_auth_id,
+        user_code,
+        interval_secs,
+        expires_at,
+    })
+}
+
+pub(crate) fn classify_poll_response(status: u16, body: &str) -> DevicePoll {
+    let value = serde_json::from_str::<serde_json::Value>(body).ok();
+    if (200..300).contains(&status) {
+        let code = value
+            .as_ref()
+            .and_then(|v| json_str(v, &["authorization_code", "code"]));
+        let verifier = value.as_ref().and_then(|v| json_str(v, &["code_verifier"]));
+        return match (code, verifier) {
+            (Some(authorization_code), Some(code_verifier)) => DevicePoll::Authorized {
+                authorization_code,
+                code_verifier,
+            },
+            _ => DevicePoll::Denied("device token response missing authorization code".to_string()),
+        };
+    }
+    let error_code = value
+        .as_ref()
+        .and_then(|v| {
+            v.get("error").and_then(|err| {
+                err.as_str()
+                    .map(|s| s.to_string())
+                    .or_else(|| json_str(err, &["code"]))
+            })
+        })
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match error_code.as_str() {
+        "authorization_pending" | "deviceauth_authorization_unknown" => DevicePoll::Pending,
+        "slow_down" => DevicePoll::SlowDown,
+        "expired_token" | "deviceauth_expired" => DevicePoll::Expired,
+        "access_denied" => DevicePoll::Denied("device authorization denied".to_string()),
+        // 中文注释：授权前 token 接口以 403/404 表示“尚未确认”，不能当成失败直接结束轮询。
+        _ if status == 403 || status == 404 => DevicePoll::Pending,
+        _ if status == 429 => DevicePoll::SlowDown,
+        _ => DevicePoll::Denied(format!("device token endpoint returned status {status}")),
+    }
+}
+
+pub(crate) fn next_poll_interval(current: u64, poll: &DevicePoll) -> u64 {
+    match poll {
+        DevicePoll::SlowDown => (current + SLOW_DOWN_STEP_SECS).min(MAX_DEVICE_POLL_INTERVAL_SECS),
+        _ => current,
+    }
+}
+
+pub(crate) fn register_device_login(login_id: &str, progress: DeviceLoginProgress) {
+    let registry = DEVICE_LOGINS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut guard) = registry.lock() {
+        guard.insert(login_id.to_string(), progress);
+    }
+}
+
+pub(crate) fn device_login_progress(login_id: &str) -> Option<DeviceLoginProgress> {
+    let registry = DEVICE_LOGINS.get()?;
+    let guard = registry.lock().ok()?;
+    guard.get(login_id).cloned()
+}
+
+fn forget_device_login(login_id: &str) {
+    let Some(registry) = DEVICE_LOGINS.get() else {
+        return;
+    };
+    if let Ok(mut guard) = registry.lock() {
+        guard.remove(login_id);
+    }
+}
+
+fn update_progress(login_id: &str, interval_secs: u64) {
+    let Some(registry) = DEVICE_LOGINS.get() else {
+        return;
+    };
+    if let Ok(mut guard) = registry.lock() {
+        if let Some(progress) = guard.get_mut(login_id) {
+            progress.interval_secs = interval_secs;
+            progress.last_polled_at = Some(now_ts());
+        }
+    }
+}
+
+pub(crate) fn spawn_device_poller(login_id: String, issuer: String, device: DeviceCode) {
+    let _ = thread::spawn(move || {
+        run_device_poller(&login_id, &issuer, device);
+        // 中文注释：轮询无论成功、失败、过期还是被取消都会走到这里；不清理的话每次设备码登录都会在内存里留一条进度。
+        forget_device_login(&login_id);
+    });
+}
+
+fn run_device_poller(login_id: &str, issuer: &str, device: DeviceCode) {
+    let client = usage_http_client();
+    let token_url = device_token_url(issuer);
+    let mut interval_secs = device.interval_secs;
+    loop {
+        thread::sleep(Duration::from_secs(interval_secs));
+        if !session_still_pending(login_id) {
+            return;
+        }
+        if now_ts() >= device.expires_at {
+            finish_failed(login_id, "expired", "device code expired");
+            return;
+        }
+        let poll = match client
+            .post(&token_url)
+            .json(&serde_json::json!({
+                "device_auth_id": device.device_auth_id,
+                "user_code": device.user_code,
+            }))
+            .send()
+        {
+            Ok(resp) => {
+                let status = resp.status().as_u16();
+                let body = resp.text().unwrap_or_default();
+                classify_poll_response(status, &body)
+            }
+            // 中文注释：网络抖动只跳过本轮，不终止会话；真正的终止条件是过期或服务端拒绝。
+            Err(err) => {
+                log::warn!(
+                    "device token poll failed: login_id={} err={}",
+                    login_id,
+                    err
+                );
+                DevicePoll::Pending
+            }
+        };
+        interval_secs = next_poll_interval(interval_secs, &poll);
+        update_progress(login_id, interval_secs);
+        match poll {
+            DevicePoll::Pending | DevicePoll::SlowDown => continue,
+            DevicePoll::Expired => {
+                finish_failed(login_id, "expired", "device code expired");
+                return;
+            }
+            DevicePoll::Denied(err) => {
+                finish_failed(login_id, "failed", &err);
+                return;
+            }
+            DevicePoll::Authorized {
+                authorization_code,
+                code_verifier,
+            } => {
+                let redirect_uri = device_redirect_uri(issuer);
+                if let Err(err) = complete_login_with_verifier(
+                    login_id,
+                    &authorization_code,
+                    Some(&redirect_uri),
+                    Some(&code_verifier),
+                ) {
+                    log::warn!(
+                        "device login completion failed: login_id={} err={}",
+                        login_id,
+                        err
+                    );
+                    // 中文注释：完成流程失败时通常已把会话标成 failed，这里只兜底结束仍为 pending 的会话，失败事件照常记录。
+                    if let Some(storage) = open_storage() {
+                        finish_pending_session(&storage, login_id, "failed", &err);
+                    }
+                    record_device_event(login_id, "login_device_failed", &err);
+                } else {
+                    record_device_event(login_id, "login_device_success", "device login completed");
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn session_still_pending(login_id: &str) -> bool {
+    let Some(storage) = open_storage() else {
+        return false;
+    };
+    matches!(
+        storage.get_login_session(login_id),
+        Ok(Some(session)) if session.status == "pending"
+    )
+}
+
+fn finish_failed(login_id: &str, status: &str, err: &str) {
+    let Some(storage) = open_storage() else {
+        return;
+    };
+    if finish_pending_session(&storage, login_id, status, err) {
+        record_device_event(login_id, "login_device_failed", err);
+    }
+}
+
+fn record_device_event(login_id: &str, event_type: &str, message: &str) {
+    if let Some(storage) = open_storage() {
+        let _ = storage.insert_event(&Event {
+            account_id: None,
+            event_type: event_type.to_string(),
+            message: format!("login_id={} {}", login_id, message),
+            created_at: now_ts(),
+        });
+    }
+}
+
+fn json_str(value: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| value.get(*key))
+        .filter_map(|v| v.as_str())
+        .map(str::trim)
+        .find(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+fn json_u64(value: &serde_json::Value, key: &str) -> Option<u64> {
+    let raw = value.get(key)?;
+    raw.as_u64()
+        .or_else(|| raw.as_str().and_then(|v| v.trim().parse::<u64>().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        classify_poll_response, device_login_progress, forget_device_login, next_poll_interval,
+        parse_device_code_response, register_device_login, DeviceLoginProgress, DevicePoll,
+        DEFAULT_DEVICE_CODE_TTL_SECS,
+    };
+
+    #[test]
+    fn parse_device_code_accepts_string_interval() {
+        let code = parse_device_code_response(
+            r#"{"device_auth_id":"dev-1","user_code":"ABCD-EFGH","interval":"7"}"#,
+            1_000,
+        )
+        .expect("parse");
+        assert_eq!(code.device_auth_id, "dev-1");
+        assert_eq!(code.user_code, "ABCD-EFGH");
+        assert_eq!(code.interval_secs, 7);
+        assert_eq!(code.expires_at, 1_000 + DEFAULT_DEVICE_CODE_TTL_SECS);
+    }
+
+    #[test]
+    fn parse_device_code_uses_expires_in_and_rejects_missing_code() {
+        let code = parse_device_code_response(
+            r#"{"device_code":"dev-2","usercode":"WXYZ","interval":0,"expires_in":600}"#,
+            1_000,
+        )
+        .expect("parse");
+        assert_eq!(code.interval_secs, 5);
+        assert_eq!(code.expires_at, 1_600);
+        assert!(parse_device_code_response(r#"{"device_auth_id":"dev-3"}"#, 0).is_err());
+    }
+
+    #[test]
+    fn classify_poll_covers_pending_slow_down_and_success() {
+        assert_eq!(classify_poll_response(403, ""), DevicePoll::Pending);
+        assert_eq!(
+            classify_poll_response(400, r#"{"error":"authorization_pending"}"#),
+            DevicePoll::Pending
+        );
+        assert_eq!(
+            classify_poll_response(400, r#"{"error":"slow_down"}"#),
+            DevicePoll::SlowDown
+        );
+        assert_eq!(
+            classify_poll_response(400, r#"{"error":"expired_token"}"#),
+            DevicePoll::Expired
+        );
+        assert!(matches!(
+            classify_poll_response(400, r#"{"error":"access_denied"}"#),
+            DevicePoll::Denied(_)
+        ));
+        assert_eq!(
+            classify_poll_response(
+                200,
+                r#"{"authorization_code":"code-1","code_verifier":"verifier-1"}"#
+            ),
+            DevicePoll::Authorized {
+                authorization_code: "code-1".to_string(),
+                code_verifier: "verifier-1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn slow_down_increases_interval_with_cap() {
+        assert_eq!(next_poll_interval(5, &DevicePoll::SlowDown), 10);
+        assert_eq!(next_poll_interval(5, &DevicePoll::Pending), 5);
+        assert_eq!(next_poll_interval(58, &DevicePoll::SlowDown), 60);
+    }
+
+    #[test]
+    fn forget_device_login_drops_progress_entry() {
+        register_device_login(
+            "login-forget",
+            DeviceLoginProgress {
+                user_code: "ABCD-EFGH".to_string(),
+                verification_url: "https://auth.openai.com/codex/device".to_string(),
+                interval_secs: 5,
+                expires_at: 1_000,
+                last_polled_at: None,
+            },
+        );
+        assert!(device_login_progress("login-forget").is_some());
+
+        forget_device_login("login-forget");
+        assert!(device_login_progress("login-forget").is_none());
+    }
+}
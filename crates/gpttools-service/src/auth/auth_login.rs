@@ -3,12 +3,27 @@ use gpttools_core::auth::{
     device_verification_url, generate_pkce, generate_state, DEFAULT_CLIENT_ID, DEFAULT_ISSUER,
     DEFAULT_ORIGINATOR,
 };
-use gpttools_core::rpc::types::{DeviceAuthInfo, LoginStartResult};
+use gpttools_core::rpc::types::{
+    DeviceAuthInfo, LoginDeviceProgress, LoginStartResult, LoginStatusResult,
+};
 use gpttools_core::storage::{now_ts, Event, LoginSession};
 
 use crate::auth_callback::{ensure_login_server, resolve_redirect_uri};
+use crate::auth_device::{
+    device_login_progress, register_device_login, request_device_code, spawn_device_poller,
+    DeviceLoginProgress,
+};
+use crate::auth_session::{is_session_expired, login_session_ttl_secs};
 use crate::storage_helpers::open_storage;
 
+struct LoginSessionMeta {
+    note: Option<String>,
+    tags: Option<String>,
+    group_name: Option<String>,
+    workspace_selection: Option<String>,
+    reauth_account_id: Option<String>,
+}
+
 pub(crate) fn login_start(
     login_type: &str,
     open_browser: bool,
@@ -16,6 +31,56 @@ pub(crate) fn login_start(
     tags: Option<String>,
     group_name: Option<String>,
     workspace_id: Option<String>,
+    workspace_selection: Option<String>,
+) -> Result<LoginStartResult, String> {
+    start_login_session(
+        login_type,
+        open_browser,
+        workspace_id,
+        LoginSessionMeta {
+            note,
+            tags,
+            group_name,
+            workspace_selection,
+            reauth_account_id: None,
+        },
+    )
+}
+
+pub(crate) fn reauth_start(
+    account_id: &str,
+    login_type: &str,
+    open_browser: bool,
+) -> Result<LoginStartResult, String> {
+    // 为已有账号发起重新授权，登录完成后只替换 token
+    if account_id.is_empty() {
+        return Err("missing accountId".to_string());
+    }
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let account = storage
+        .find_account(account_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "account not found".to_string())?;
+    // 中文注释：锁定原 workspace 发起授权，避免用户在授权页误选其他 workspace 后因身份不匹配而白跑一趟。
+    start_login_session(
+        login_type,
+        open_browser,
+        account.workspace_id.clone(),
+        LoginSessionMeta {
+            note: None,
+            tags: None,
+            group_name: account.group_name.clone(),
+            workspace_selection: None,
+            reauth_account_id: Some(account.id),
+        },
+    )
+}
+
+fn start_login_session(
+    login_type: &str,
+    open_browser: bool,
+    workspace_id: Option<String>,
+    meta: LoginSessionMeta,
 ) -> Result<LoginStartResult, String> {
     // 读取登录相关配置
     let issuer = std::env::var("GPTTOOLS_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string());
@@ -30,12 +95,18 @@ pub(crate) fn login_start(
         }
     }
     let redirect_uri = if login_type == "device" {
-        std::env::var("GPTTOOLS_REDIRECT_URI")
-            .unwrap_or_else(|_| "http://localhost:1455/auth/callback".to_string())
+        device_redirect_uri(&issuer)
     } else {
         resolve_redirect_uri().unwrap_or_else(|| "http://localhost:1455/auth/callback".to_string())
     };
 
+    // 设备码登录先向服务端申请 user code，失败时不创建会话
+    let device_code = if login_type == "device" {
+        Some(request_device_code(&issuer, &client_id)?)
+    } else {
+        None
+    };
+
     // 生成 PKCE 与状态
     let pkce = generate_pkce();
     let state = generate_state();
@@ -43,16 +114,12 @@ pub(crate) fn login_start(
     // 写入登录会话
     if let Some(storage) = open_storage() {
         let _ = storage.insert_login_session(&LoginSession {
-            login_id: state.clone(),
-            code_verifier: pkce.code_verifier.clone(),
-            state: state.clone(),
-            status: "pending".to_string(),
-            error: None,
-            note,
-            tags,
-            group_name,
-            created_at: now_ts(),
-            updated_at: now_ts(),
+            note: meta.note,
+            tags: meta.tags,
+            group_name: meta.group_name,
+            reauth_account_id: meta.reauth_account_id,
+            workspace_selection: meta.workspace_selection,
+            ..LoginSession::pending(&state, &pkce.code_verifier, now_ts())
         });
     }
 
@@ -72,30 +139,45 @@ pub(crate) fn login_start(
     };
 
     // 设备登录信息
-    let device = if login_type == "device" {
-        Some(DeviceAuthInfo {
-            user_code_url: device_usercode_url(&issuer),
-            token_url: device_token_url(&issuer),
-            verification_url: device_verification_url(&issuer),
-            redirect_uri: device_redirect_uri(&issuer),
-        })
-    } else {
-        None
-    };
+    let device = device_code.as_ref().map(|code| DeviceAuthInfo {
+        user_code_url: device_usercode_url(&issuer),
+        token_url: device_token_url(&issuer),
+        verification_url: device_verification_url(&issuer),
+        redirect_uri: device_redirect_uri(&issuer),
+        user_code: Some(code.user_code.clone()),
+        interval_secs: Some(code.interval_secs),
+        expires_at: Some(code.expires_at),
+    });
 
     // 写入事件日志
     if let Some(storage) = open_storage() {
         let _ = storage.insert_event(&Event {
             account_id: None,
             event_type: "login_start".to_string(),
+            // 中文注释：code_verifier 只保存在 login_sessions，事件表对 UI/导出可见，不能再写入任何 PKCE 秘密。
             message: format!(
-                "{{\"login_id\":\"{}\",\"code_verifier\":\"{}\"}}",
-                state, pkce.code_verifier
+                "{{\"login_id\":\"{}\",\"login_type\":\"{}\"}}",
+                state, login_type
             ),
             created_at: now_ts(),
         });
     }
 
+    // 设备码登录：后台轮询 token 接口，完成后走与回调相同的入库流程
+    if let Some(code) = device_code {
+        register_device_login(
+            &state,
+            DeviceLoginProgress {
+                user_code: code.user_code.clone(),
+                verification_url: auth_url.clone(),
+                interval_secs: code.interval_secs,
+                expires_at: code.expires_at,
+                last_polled_at: None,
+            },
+        );
+        spawn_device_poller(state.clone(), issuer.clone(), code);
+    }
+
     // 可选自动打开浏览器
     if login_type != "device" && open_browser {
         let _ = webbrowser::open(&auth_url);
@@ -113,22 +195,40 @@ pub(crate) fn login_start(
     })
 }
 
-pub(crate) fn login_status(login_id: &str) -> serde_json::Value {
+pub(crate) fn login_status(login_id: &str) -> LoginStatusResult {
     // 查询登录会话状态
+    let unknown = || LoginStatusResult {
+        status: "unknown".to_string(),
+        error: None,
+        updated_at: None,
+        device: None,
+    };
     if login_id.is_empty() {
-        return serde_json::json!({ "status": "unknown" });
+        return unknown();
     }
     let storage = match open_storage() {
         Some(storage) => storage,
-        None => return serde_json::json!({ "status": "unknown" }),
+        None => return unknown(),
     };
     let session = match storage.get_login_session(login_id) {
         Ok(Some(session)) => session,
-        _ => return serde_json::json!({ "status": "unknown" }),
+        _ => return unknown(),
     };
-    serde_json::json!({
-        "status": session.status,
-        "error": session.error,
-        "updatedAt": session.updated_at
-    })
+    let status = if is_session_expired(&session, now_ts(), login_session_ttl_secs()) {
+        "expired".to_string()
+    } else {
+        session.status
+    };
+    LoginStatusResult {
+        status,
+        error: session.error,
+        updated_at: Some(session.updated_at),
+        device: device_login_progress(login_id).map(|progress| LoginDeviceProgress {
+            user_code: progress.user_code,
+            verification_url: progress.verification_url,
+            interval_secs: progress.interval_secs,
+            expires_at: progress.expires_at,
+            last_polled_at: progress.last_polled_at,
+        }),
+    }
 }
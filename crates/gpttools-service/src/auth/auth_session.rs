@@ -0,0 +1,244 @@
+use gpttools_core::storage::{now_ts, Event, LoginSession, Storage};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+use crate::storage_helpers::open_storage;
+use crate::usage_scheduler::{parse_interval_secs, run_blocking_poll_loop};
+
+pub(crate) const LOGIN_SESSION_EXPIRED: &str = "login session expired";
+const DEFAULT_LOGIN_SESSION_TTL_SECS: u64 = 15 * 60;
+const MIN_LOGIN_SESSION_TTL_SECS: u64 = 60;
+const DEFAULT_LOGIN_SESSION_RETENTION_SECS: u64 = 24 * 60 * 60;
+const MIN_LOGIN_SESSION_RETENTION_SECS: u64 = 60;
+const LOGIN_SESSION_SWEEP_INTERVAL_SECS: u64 = 300;
+
+static LOGIN_SESSION_SWEEPER_STARTED: OnceLock<()> = OnceLock::new();
+
+pub(crate) fn login_session_ttl_secs() -> u64 {
+    let configured = std::env::var("GPTTOOLS_LOGIN_SESSION_TTL_SECS").ok();
+    parse_interval_secs(
+        configured.as_deref(),
+        DEFAULT_LOGIN_SESSION_TTL_SECS,
+        MIN_LOGIN_SESSION_TTL_SECS,
+    )
+}
+
+fn login_session_retention_secs() -> u64 {
+    let configured = std::env::var("GPTTOOLS_LOGIN_SESSION_RETENTION_SECS").ok();
+    parse_interval_secs(
+        configured.as_deref(),
+        DEFAULT_LOGIN_SESSION_RETENTION_SECS,
+        MIN_LOGIN_SESSION_RETENTION_SECS,
+    )
+}
+
+pub(crate) fn is_session_expired(session: &LoginSession, now: i64, ttl_secs: u64) -> bool {
+    session.status == "pending" && session.created_at + ttl_secs as i64 <= now
+}
+
+pub(crate) fn ensure_pending_session(
+    storage: &Storage,
+    session: &LoginSession,
+) -> Result<(), String> {
+    // 校验会话仍可被回调完成：过期的顺手落库为 expired，已结束的直接拒绝
+    if is_session_expired(session, now_ts(), login_session_ttl_secs()) {
+        let _ = storage.transition_login_session_status(
+            &session.login_id,
+            "pending",
+            "expired",
+            Some(LOGIN_SESSION_EXPIRED),
+        );
+        return Err(LOGIN_SESSION_EXPIRED.to_string());
+    }
+    match session.status.as_str() {
+        "pending" => Ok(()),
+        "expired" => Err(LOGIN_SESSION_EXPIRED.to_string()),
+        other => Err(format!("login session already {other}")),
+    }
+}
+
+pub(crate) fn cancel_login(login_id: &str) -> Result<(), String> {
+    // 取消仍在等待中的登录会话
+    if login_id.is_empty() {
+        return Err("missing loginId".to_string());
+    }
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let session = storage
+        .get_login_session(login_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "unknown login session".to_string())?;
+    if session.status != "pending" {
+        return Err(format!("login session already {}", session.status));
+    }
+    let changed = storage
+        .transition_login_session_status(
+            login_id,
+            "pending",
+            "cancelled",
+            Some("cancelled by user"),
+        )
+        .map_err(|e| e.to_string())?;
+    if !changed {
+        return Err("login session is no longer pending".to_string());
+    }
+    let _ = storage.insert_event(&Event {
+        account_id: None,
+        event_type: "login_cancel".to_string(),
+        message: format!("login_id={login_id}"),
+        created_at: now_ts(),
+    });
+    Ok(())
+}
+
+pub(crate) fn finish_pending_session(
+    storage: &Storage,
+    login_id: &str,
+    status: &str,
+    error: &str,
+) -> bool {
+    // 中文注释：只结束仍为 pending 的会话；已被取消/过期/完成的会话不能被后到的失败结果覆盖。
+    storage
+        .transition_login_session_status(login_id, "pending", status, Some(error))
+        .unwrap_or(false)
+}
+
+pub(crate) fn sweep_login_sessions() -> Result<(), String> {
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    sweep_login_sessions_with(
+        &storage,
+        now_ts(),
+        login_session_ttl_secs(),
+        login_session_retention_secs(),
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn sweep_login_sessions_with(
+    storage: &Storage,
+    now: i64,
+    ttl_secs: u64,
+    retention_secs: u64,
+) -> Result<(usize, usize), String> {
+    // 中文注释：先把超时的 pending 标成 expired，再按保留期删除已结束会话；
+    // 直接删除 pending 会让前端轮询 login/status 时只看到 unknown，无法提示“已过期”。
+    let expired = storage
+        .expire_login_sessions(now - ttl_secs as i64)
+        .map_err(|e| e.to_string())?;
+    let deleted = storage
+        .delete_login_sessions_before(now - retention_secs as i64)
+        .map_err(|e| e.to_string())?;
+    Ok((expired, deleted))
+}
+
+pub(crate) fn ensure_login_session_sweeper() {
+    // 启动登录会话清理线程（只启动一次）
+    if std::env::var("GPTTOOLS_DISABLE_POLLING").is_ok() {
+        return;
+    }
+    LOGIN_SESSION_SWEEPER_STARTED.get_or_init(|| {
+        let _ = thread::spawn(|| {
+            run_blocking_poll_loop(
+                "login session sweep",
+                Duration::from_secs(LOGIN_SESSION_SWEEP_INTERVAL_SECS),
+                sweep_login_sessions,
+                |_| true,
+            )
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ensure_pending_session, finish_pending_session, is_session_expired,
+        sweep_login_sessions_with,
+    };
+    use gpttools_core::storage::{now_ts, LoginSession, Storage};
+
+    fn session(login_id: &str, status: &str, created_at: i64) -> LoginSession {
+        LoginSession {
+            status: status.to_string(),
+            ..LoginSession::pending(login_id, "verifier", created_at)
+        }
+    }
+
+    #[test]
+    fn pending_session_expires_after_ttl() {
+        assert!(!is_session_expired(
+            &session("a", "pending", 1_000),
+            1_899,
+            900
+        ));
+        assert!(is_session_expired(
+            &session("a", "pending", 1_000),
+            1_900,
+            900
+        ));
+        assert!(!is_session_expired(
+            &session("a", "success", 1_000),
+            9_999,
+            900
+        ));
+    }
+
+    #[test]
+    fn ensure_pending_rejects_expired_and_finished_sessions() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let stale = session("stale", "pending", now_ts() - 24 * 3600);
+        storage.insert_login_session(&stale).expect("insert");
+
+        let err = ensure_pending_session(&storage, &stale).expect_err("expired");
+        assert!(err.contains("expired"));
+        let loaded = storage
+            .get_login_session("stale")
+            .expect("load")
+            .expect("exists");
+        assert_eq!(loaded.status, "expired");
+
+        let done = session("done", "success", now_ts());
+        assert!(ensure_pending_session(&storage, &done).is_err());
+        assert!(ensure_pending_session(&storage, &session("ok", "pending", now_ts())).is_ok());
+    }
+
+    #[test]
+    fn sweep_expires_pending_then_deletes_after_retention() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let now = now_ts();
+        storage
+            .insert_login_session(&session("old", "pending", now - 2_000))
+            .expect("insert");
+        storage
+            .insert_login_session(&session("finished", "failed", now - 100_000))
+            .expect("insert");
+
+        let (expired, deleted) =
+            sweep_login_sessions_with(&storage, now, 900, 86_400).expect("sweep");
+        assert_eq!((expired, deleted), (1, 1));
+        let (expired, deleted) =
+            sweep_login_sessions_with(&storage, now + 90_000, 900, 86_400).expect("sweep");
+        assert_eq!((expired, deleted), (0, 1));
+    }
+
+    #[test]
+    fn finish_pending_session_keeps_terminal_status() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        storage
+            .insert_login_session(&session("cancelled", "cancelled", now_ts()))
+            .expect("insert");
+        storage
+            .insert_login_session(&session("pending", "pending", now_ts()))
+            .expect("insert");
+
+        assert!(!finish_pending_session(&storage, "cancelled", "expired", "device code expired"));
+        assert!(finish_pending_session(&storage, "pending", "expired", "device code expired"));
+        let cancelled = storage.get_login_session("cancelled").expect("load").expect("exists");
+        assert_eq!(cancelled.status, "cancelled");
+        let expired = storage.get_login_session("pending").expect("load").expect("exists");
+        assert_eq!(expired.status, "expired");
+        assert_eq!(expired.error.as_deref(), Some("device code expired"));
+    }
+}
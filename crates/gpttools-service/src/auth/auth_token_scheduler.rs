@@ -0,0 +1,218 @@
+use gpttools_core::auth::{parse_jwt_expiry, DEFAULT_CLIENT_ID, DEFAULT_ISSUER};
+use gpttools_core::storage::{now_ts, Account, Event, Storage, Token};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::account_status::set_account_status;
+use crate::storage_helpers::{hash_platform_key, open_storage};
+use crate::usage_http::is_refresh_token_revoked;
+use crate::usage_scheduler::{parse_interval_secs, run_blocking_poll_loop};
+use crate::usage_token_refresh::refresh_and_persist_access_token;
+
+const DEFAULT_TOKEN_REFRESH_CHECK_INTERVAL_SECS: u64 = 60;
+const MIN_TOKEN_REFRESH_CHECK_INTERVAL_SECS: u64 = 15;
+const DEFAULT_TOKEN_REFRESH_MARGIN_SECS: u64 = 600;
+const MIN_TOKEN_REFRESH_MARGIN_SECS: u64 = 60;
+const DEFAULT_TOKEN_REFRESH_JITTER_SECS: u64 = 120;
+
+static TOKEN_REFRESH_STARTED: OnceLock<()> = OnceLock::new();
+static REVOKED_REFRESH_TOKENS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+pub(crate) fn ensure_token_refresh_scheduler() {
+    // 启动后台主动刷新 token 线程（只启动一次）
+    if std::env::var("GPTTOOLS_DISABLE_POLLING").is_ok() {
+        return;
+    }
+    TOKEN_REFRESH_STARTED.get_or_init(|| {
+        let _ = thread::spawn(token_refresh_loop);
+    });
+}
+
+fn token_refresh_loop() {
+    let configured = std::env::var("GPTTOOLS_TOKEN_REFRESH_CHECK_INTERVAL_SECS").ok();
+    let interval_secs = parse_interval_secs(
+        configured.as_deref(),
+        DEFAULT_TOKEN_REFRESH_CHECK_INTERVAL_SECS,
+        MIN_TOKEN_REFRESH_CHECK_INTERVAL_SECS,
+    );
+    run_blocking_poll_loop(
+        "token refresh",
+        Duration::from_secs(interval_secs),
+        refresh_expiring_tokens,
+        |_| true,
+    );
+}
+
+fn refresh_margin_secs() -> i64 {
+    let configured = std::env::var("GPTTOOLS_TOKEN_REFRESH_MARGIN_SECS").ok();
+    parse_interval_secs(
+        configured.as_deref(),
+        DEFAULT_TOKEN_REFRESH_MARGIN_SECS,
+        MIN_TOKEN_REFRESH_MARGIN_SECS,
+    ) as i64
+}
+
+fn refresh_jitter_secs() -> u64 {
+    std::env::var("GPTTOOLS_TOKEN_REFRESH_JITTER_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TOKEN_REFRESH_JITTER_SECS)
+}
+
+fn account_jitter_secs(account_id: &str, max_jitter_secs: u64) -> i64 {
+    // 中文注释：按账号 id 哈希得到固定抖动，多账号同时登录时刷新时间会被打散；
+    // 不加抖动会让同一批 token 在同一轮集中打到 oauth/token。
+    if max_jitter_secs == 0 {
+        return 0;
+    }
+    let digest = Sha256::digest(account_id.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) % (max_jitter_secs + 1)) as i64
+}
+
+fn is_refresh_due(now: i64, expires_at: i64, margin_secs: i64, jitter_secs: i64) -> bool {
+    now >= expires_at - margin_secs - jitter_secs
+}
+
+fn revoked_refresh_tokens() -> &'static Mutex<HashMap<String, String>> {
+    REVOKED_REFRESH_TOKENS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn is_known_revoked(token: &Token) -> bool {
+    // 中文注释：已确认吊销的 refresh_token 不再重复请求，直到账号重新登录换了新 token。
+    let Ok(map) = revoked_refresh_tokens().lock() else {
+        return false;
+    };
+    map.get(&token.account_id)
+        .map(|hash| *hash == hash_platform_key(&token.refresh_token))
+        .unwrap_or(false)
+}
+
+fn remember_revoked(token: &Token) {
+    if let Ok(mut map) = revoked_refresh_tokens().lock() {
+        map.insert(token.account_id.clone(), hash_platform_key(&token.refresh_token));
+    }
+}
+
+fn record_token_refresh_event(storage: &Storage, account_id: &str, event_type: &str, message: String) {
+    let _ = storage.insert_event(&Event {
+        account_id: Some(account_id.to_string()),
+        event_type: event_type.to_string(),
+        message,
+        created_at: now_ts(),
+    });
+}
+
+pub(crate) fn refresh_expiring_tokens() -> Result<(), String> {
+    // 扫描即将过期的 access_token 并提前刷新
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let accounts: HashMap<String, Account> = storage
+        .list_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|account| (account.id.clone(), account))
+        .collect();
+    let tokens = storage.list_tokens().map_err(|e| e.to_string())?;
+    let margin_secs = refresh_margin_secs();
+    let max_jitter_secs = refresh_jitter_secs();
+    let now = now_ts();
+
+    for token in tokens {
+        let Some(account) = accounts.get(&token.account_id) else {
+            continue;
+        };
+        if token.refresh_token.trim().is_empty() || is_known_revoked(&token) {
+            continue;
+        }
+        // 中文注释：解析不出 exp 的 token 交给失败后的被动刷新处理，这里不盲目刷新。
+        let Some(expires_at) = parse_jwt_expiry(&token.access_token) else {
+            continue;
+        };
+        let jitter_secs = account_jitter_secs(&account.id, max_jitter_secs);
+        if !is_refresh_due(now, expires_at, margin_secs, jitter_secs) {
+            continue;
+        }
+        refresh_account_token(&storage, account, token);
+    }
+    Ok(())
+}
+
+fn refresh_account_token(storage: &Storage, account: &Account, mut token: Token) {
+    // 中文注释：与网关 token exchange 共用账号锁；refresh_token 会轮换，并发刷新会触发 reused 导致整条 token 失效。
+    let exchange_lock = crate::gateway::account_token_exchange_lock(&account.id);
+    let Ok(_guard) = exchange_lock.lock() else {
+        return;
+    };
+    let latest = storage.find_token(&account.id).ok().flatten();
+    match latest {
+        Some(latest) if latest.access_token == token.access_token => token = latest,
+        // 中文注释：等锁期间其他链路已刷新过，直接跳过。
+        _ => return,
+    }
+
+    let client_id =
+        std::env::var("GPTTOOLS_CLIENT_ID").unwrap_or_else(|_| DEFAULT_CLIENT_ID.to_string());
+    let issuer = if account.issuer.trim().is_empty() {
+        std::env::var("GPTTOOLS_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string())
+    } else {
+        account.issuer.clone()
+    };
+
+    let previous_api_key = token.api_key_access_token.clone();
+    match refresh_and_persist_access_token(storage, &mut token, &issuer, &client_id) {
+        Ok(()) => {
+            let mut api_key_status = "api_key=refreshed".to_string();
+            if token.api_key_access_token == previous_api_key {
+                if let Err(err) = crate::gateway::exchange_and_persist_api_key_access_token(
+                    storage, &mut token, &issuer, &client_id,
+                ) {
+                    api_key_status = format!("api_key=failed: {err}");
+                }
+            }
+            let expires_at = parse_jwt_expiry(&token.access_token)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            record_token_refresh_event(
+                storage,
+                &account.id,
+                "token_refresh_success",
+                format!("exp={expires_at} {api_key_status}"),
+            );
+        }
+        Err(err) if is_refresh_token_revoked(&err) => {
+            remember_revoked(&token);
+            set_account_status(storage, &account.id, "inactive", "refresh_token_revoked");
+            record_token_refresh_event(storage, &account.id, "token_refresh_revoked", err);
+        }
+        Err(err) => {
+            record_token_refresh_event(storage, &account.id, "token_refresh_failed", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_jitter_secs, is_refresh_due};
+
+    #[test]
+    fn refresh_is_due_inside_margin_plus_jitter() {
+        let expires_at = 10_000;
+        assert!(!is_refresh_due(9_000, expires_at, 600, 0));
+        assert!(is_refresh_due(9_400, expires_at, 600, 0));
+        assert!(is_refresh_due(9_300, expires_at, 600, 120));
+        assert!(is_refresh_due(10_500, expires_at, 600, 0));
+    }
+
+    #[test]
+    fn account_jitter_is_stable_and_bounded() {
+        let first = account_jitter_secs("acc-1", 120);
+        let second = account_jitter_secs("acc-1", 120);
+        assert_eq!(first, second);
+        assert!((0..=120).contains(&first));
+        assert_eq!(account_jitter_secs("acc-1", 0), 0);
+    }
+}
@@ -2,11 +2,15 @@ use gpttools_core::auth::{
     extract_chatgpt_account_id, extract_workspace_id, parse_id_token_claims,
     DEFAULT_CLIENT_ID, DEFAULT_ISSUER,
 };
-use gpttools_core::storage::{now_ts, Account, Token};
-use reqwest::blocking::Client;
+use gpttools_core::storage::{now_ts, Account, Event, LoginSession, Storage, Token};
 
+use crate::account_proxy::account_proxy_url;
+use crate::account_status::set_account_status;
+use crate::account_workspaces::attach_selected_workspaces;
 use crate::auth_callback::resolve_redirect_uri;
-use crate::storage_helpers::{account_key, open_storage};
+use crate::auth_session::{ensure_pending_session, finish_pending_session};
+use crate::storage_helpers::{account_key, open_storage, token_group_key};
+use crate::usage_http::usage_http_client_for_proxy;
 
 fn clean_value(value: Option<String>) -> Option<String> {
     match value {
@@ -30,6 +34,15 @@ pub(crate) fn complete_login_with_redirect(
     state: &str,
     code: &str,
     redirect_uri: Option<&str>,
+) -> Result<(), String> {
+    complete_login_with_verifier(state, code, redirect_uri, None)
+}
+
+pub(crate) fn complete_login_with_verifier(
+    state: &str,
+    code: &str,
+    redirect_uri: Option<&str>,
+    code_verifier: Option<&str>,
 ) -> Result<(), String> {
     // 读取登录会话
     let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
@@ -37,6 +50,8 @@ pub(crate) fn complete_login_with_redirect(
         .get_login_session(state)
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "unknown login session".to_string())?;
+    // 中文注释：过期或已结束的 state 一律拒绝；否则泄露的旧回调链接可以在任意时间被重放完成登录。
+    ensure_pending_session(&storage, &session)?;
 
     // 读取 OAuth 配置
     let issuer = std::env::var("GPTTOOLS_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string());
@@ -47,27 +62,186 @@ pub(crate) fn complete_login_with_redirect(
         .or_else(|| resolve_redirect_uri())
         .unwrap_or_else(|| "http://localhost:1455/auth/callback".to_string());
 
-    // 交换授权码获取 token
+    // 中文注释：重新授权时换码与兑换平台 key 都沿用目标账号的出站代理；换码走直连会把账号身份和真实出口 IP 关联起来。
+    let login_proxy = session
+        .reauth_account_id
+        .as_deref()
+        .and_then(|id| account_proxy_url(&storage, id));
+
+    // 交换授权码获取 token（设备码登录的 verifier 由服务端下发）
+    let code_verifier = code_verifier.unwrap_or(session.code_verifier.as_str());
     let tokens = exchange_code_for_tokens(
         &issuer,
         &client_id,
         &redirect_uri,
-        &session.code_verifier,
+        code_verifier,
         code,
+        login_proxy.as_deref(),
     )
-    .map_err(|e| {
-        let _ = storage.update_login_session_status(state, "failed", Some(&e));
-        e
-    })?;
+    .map_err(|e| fail_session(&storage, state, e))?;
 
     // 可选兑换平台 key
-    let api_key_access_token = obtain_api_key(&issuer, &client_id, &tokens.id_token).ok();
-    let claims = parse_id_token_claims(&tokens.id_token).map_err(|e| {
-        let _ = storage.update_login_session_status(state, "failed", Some(&e));
-        e
-    })?;
+    let api_key_access_token =
+        obtain_api_key(&issuer, &client_id, &tokens.id_token, login_proxy.as_deref()).ok();
+    finish_login(&storage, &session, &issuer, tokens, api_key_access_token)
+}
+
+fn fail_session(storage: &Storage, state: &str, err: String) -> String {
+    finish_pending_session(storage, state, "failed", &err);
+    err
+}
+
+fn finish_login(
+    storage: &Storage,
+    session: &LoginSession,
+    issuer: &str,
+    tokens: TokenResponse,
+    api_key_access_token: Option<String>,
+) -> Result<(), String> {
+    let state = session.login_id.as_str();
+    let mut account = account_from_tokens(
+        &tokens.id_token,
+        &tokens.access_token,
+        issuer,
+        session.tags.as_deref(),
+        session.group_name.clone(),
+    )
+    .map_err(|e| fail_session(storage, state, e))?;
+    let reauth_target = match session.reauth_account_id.as_deref() {
+        Some(target_id) => {
+            let existing = storage
+                .find_account(target_id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| {
+                    fail_session(storage, state, "reauth target account not found".to_string())
+                })?;
+            let existing_token = storage.find_token(&existing.id).ok().flatten();
+            if !reauth_identity_matches(
+                &existing,
+                existing_token.as_ref().map(|token| token.id_token.as_str()),
+                &account,
+                &tokens.id_token,
+            ) {
+                let err = format!(
+                    "reauth identity mismatch: signed in as {} but account is {}",
+                    account.label, existing.label
+                );
+                return Err(fail_session(storage, state, err));
+            }
+            Some((existing, existing_token))
+        }
+        None => None,
+    };
+    // 中文注释：换 token 期间会话可能已超时，落库前再校验一次。
+    ensure_pending_session(storage, session)?;
+
+    // 中文注释：先把会话原子地从 pending 改成 success，抢到了才写账号与 token，且和这些写入同一事务提交；
+    // 不这样做，换 token 期间被取消或过期的会话仍会写入账号，并把 cancelled/expired 覆盖成 success。
+    let tx = storage.begin_transaction().map_err(|e| e.to_string())?;
+    let claimed = storage
+        .transition_login_session_status(state, "pending", "success", None)
+        .map_err(|e| e.to_string())?;
+    if !claimed {
+        return Err(match storage.get_login_session(state) {
+            Ok(Some(current)) => format!("login session already {}", current.status),
+            _ => "unknown login session".to_string(),
+        });
+    }
+    let previous_refresh_token = reauth_target
+        .as_ref()
+        .and_then(|(_, existing_token)| existing_token.as_ref())
+        .map(|token| token.refresh_token.clone());
+    let account_key = match reauth_target {
+        Some((existing, _)) => {
+            // 中文注释：重新授权只换 token，不重写账号行；insert_account 是 INSERT OR REPLACE，会把排序/分组/状态一并覆盖。
+            if existing.status != "active" {
+                set_account_status(storage, &existing.id, "active", "reauth_success");
+            }
+            let _ = storage.insert_event(&Event {
+                account_id: Some(existing.id.clone()),
+                event_type: "account_reauth".to_string(),
+                message: format!("login_id={state}"),
+                created_at: now_ts(),
+            });
+            existing.id
+        }
+        None => {
+            // 中文注释：同一账号重新登录会整行覆盖，出站代理需沿用旧值；不保留会让账号悄悄改回直连。
+            account.proxy_url = account_proxy_url(storage, &account.id);
+            storage.insert_account(&account).map_err(|e| e.to_string())?;
+            account.id.clone()
+        }
+    };
+
+    // 写入 token
+    let token = Token {
+        account_id: account_key,
+        id_token: tokens.id_token,
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        api_key_access_token,
+        last_refresh: now_ts(),
+    };
+    storage.insert_token(&token).map_err(|e| e.to_string())?;
+    // 中文注释：重新授权的若是某个 workspace 条目，同一身份的其他条目也还拿着同一份失效的旧 token，一并换新。
+    if let Some(previous) = previous_refresh_token.as_deref() {
+        storage
+            .propagate_refreshed_token(&token, previous)
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    // 中文注释：同一身份可能属于多个 workspace，各自有独立配额；按登录时的选择为每个 workspace 建一个共享 token 的条目。
+    // 这一步要请求上游，放在事务提交之后，避免联网期间一直占着写锁。
+    if session.reauth_account_id.is_none() {
+        if let Some(selection) = session.workspace_selection.as_deref() {
+            attach_selected_workspaces(storage, &account, &token, selection);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn reauth_identity_matches(
+    existing: &Account,
+    existing_id_token: Option<&str>,
+    signed_in: &Account,
+    signed_in_id_token: &str,
+) -> bool {
+    // 账号 id 由 sub（可带 ::tags 与 workspace 后缀）组成，去掉 workspace 后缀后必须是同一个 sub
+    // 中文注释：workspace 条目的 chatgpt_account_id 存的是 workspace id，拿它比身份会把合法的重新授权判成不匹配。
+    let base_key = token_group_key(&existing.id);
+    let same_subject = base_key == signed_in.id
+        || base_key
+            .strip_prefix(signed_in.id.as_str())
+            .is_some_and(|rest| rest.starts_with("::"));
+    // 原 token 还能解析时再核对一次用户 id
+    let same_user = match (
+        existing_id_token.and_then(token_user_id),
+        token_user_id(signed_in_id_token),
+    ) {
+        (Some(left), Some(right)) => left == right,
+        _ => true,
+    };
+    same_subject && same_user
+}
+
+fn token_user_id(id_token: &str) -> Option<String> {
+    let claims = parse_id_token_claims(id_token).ok()?;
+    claims
+        .auth
+        .and_then(|auth| clean_value(auth.chatgpt_user_id).or(clean_value(auth.user_id)))
+        .or(Some(claims.sub))
+}
 
-    // 生成账户记录
+pub(crate) fn account_from_tokens(
+    id_token: &str,
+    access_token: &str,
+    issuer: &str,
+    tags: Option<&str>,
+    group_name: Option<String>,
+) -> Result<Account, String> {
+    // 从 id_token 推导账户元数据（登录回调与导入共用）
+    let claims = parse_id_token_claims(id_token)?;
     let account_id = claims.sub.clone();
     let label = claims.email.clone().unwrap_or_else(|| account_id.clone());
     let chatgpt_account_id = clean_value(
@@ -75,48 +249,39 @@ pub(crate) fn complete_login_with_redirect(
             .auth
             .as_ref()
             .and_then(|auth| auth.chatgpt_account_id.clone())
-            .or_else(|| extract_chatgpt_account_id(&tokens.id_token))
-            .or_else(|| extract_chatgpt_account_id(&tokens.access_token))
+            .or_else(|| extract_chatgpt_account_id(id_token))
+            .or_else(|| extract_chatgpt_account_id(access_token))
             .or_else(|| Some(account_id.clone())),
     );
     let workspace_id = clean_value(
         claims
             .workspace_id
             .clone()
-            .or_else(|| extract_workspace_id(&tokens.id_token))
-            .or_else(|| extract_workspace_id(&tokens.access_token))
+            .or_else(|| extract_workspace_id(id_token))
+            .or_else(|| extract_workspace_id(access_token))
             .or_else(|| chatgpt_account_id.clone()),
     );
-    let account_key = account_key(&account_id, session.tags.as_deref());
-    let account = Account {
-        id: account_key.clone(),
+    let plan_type = clean_value(
+        claims
+            .auth
+            .as_ref()
+            .and_then(|auth| auth.chatgpt_plan_type.clone()),
+    )
+    .map(|v| v.to_ascii_lowercase());
+    Ok(Account {
+        id: account_key(&account_id, tags),
         label,
-        issuer: issuer.clone(),
+        issuer: issuer.to_string(),
         chatgpt_account_id,
         workspace_id,
-        group_name: session.group_name.clone(),
+        group_name,
+        plan_type,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
         updated_at: now_ts(),
-    };
-    storage.insert_account(&account).map_err(|e| e.to_string())?;
-
-    // 写入 token
-    let token = Token {
-        account_id: account_key.clone(),
-        id_token: tokens.id_token,
-        access_token: tokens.access_token,
-        refresh_token: tokens.refresh_token,
-        api_key_access_token,
-        last_refresh: now_ts(),
-    };
-    storage.insert_token(&token).map_err(|e| e.to_string())?;
-
-    storage
-        .update_login_session_status(state, "success", None)
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    })
 }
 
 #[derive(serde::Deserialize)]
@@ -132,9 +297,10 @@ fn exchange_code_for_tokens(
     redirect_uri: &str,
     code_verifier: &str,
     code: &str,
+    proxy_url: Option<&str>,
 ) -> Result<TokenResponse, String> {
-    // 请求 token 接口
-    let client = Client::new();
+    // 请求 token 接口（按账号代理出站）
+    let client = usage_http_client_for_proxy(proxy_url)?;
     let resp = client
         .post(format!("{issuer}/oauth/token"))
         .header("Content-Type", "application/x-www-form-urlencoded")
@@ -153,14 +319,19 @@ fn exchange_code_for_tokens(
     resp.json().map_err(|e| e.to_string())
 }
 
-pub(crate) fn obtain_api_key(issuer: &str, client_id: &str, id_token: &str) -> Result<String, String> {
+pub(crate) fn obtain_api_key(
+    issuer: &str,
+    client_id: &str,
+    id_token: &str,
+    proxy_url: Option<&str>,
+) -> Result<String, String> {
     #[derive(serde::Deserialize)]
     struct ExchangeResp {
         access_token: String,
     }
 
-    // 兑换平台 API Key
-    let client = Client::new();
+    // 兑换平台 API Key（按账号代理出站）
+    let client = usage_http_client_for_proxy(proxy_url)?;
     let resp = client
         .post(format!("{issuer}/oauth/token"))
         .header("Content-Type", "application/x-www-form-urlencoded")
@@ -186,3 +357,146 @@ pub(crate) fn obtain_api_key(issuer: &str, client_id: &str, id_token: &str) -> R
     Ok(body.access_token)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{finish_login, reauth_identity_matches, TokenResponse};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use gpttools_core::storage::{now_ts, Account, LoginSession, Storage, Token};
+
+    // payload: {"sub":"user-1","email":"test@example.com"}
+    const ID_TOKEN: &str =
+        "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiJ1c2VyLTEiLCJlbWFpbCI6InRlc3RAZXhhbXBsZS5jb20ifQ.sig";
+
+    fn tokens() -> TokenResponse {
+        TokenResponse {
+            id_token: ID_TOKEN.to_string(),
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+        }
+    }
+
+    fn account(id: &str, chatgpt_account_id: Option<&str>) -> Account {
+        Account {
+            id: id.to_string(),
+            label: id.to_string(),
+            issuer: "issuer".to_string(),
+            chatgpt_account_id: chatgpt_account_id.map(|v| v.to_string()),
+            workspace_id: None,
+            group_name: None,
+            plan_type: None,
+            proxy_url: None,
+            sort: 0,
+            status: "active".to_string(),
+            created_at: now_ts(),
+            updated_at: now_ts(),
+        }
+    }
+
+    fn id_token(sub: &str, user_id: &str) -> String {
+        let payload = serde_json::json!({
+            "sub": sub,
+            "https://api.openai.com/auth": { "chatgpt_user_id": user_id },
+        });
+        format!(
+            "eyJhbGciOiJIUzI1NiJ9.{}.sig",
+            URL_SAFE_NO_PAD.encode(payload.to_string())
+        )
+    }
+
+    #[test]
+    fn reauth_identity_requires_same_subject_and_user() {
+        let signed_in = account("user-1", Some("acct-1"));
+        let token = id_token("user-1", "uid-1");
+        let matches = |existing: &Account, existing_token: Option<&str>| {
+            reauth_identity_matches(existing, existing_token, &signed_in, &token)
+        };
+        assert!(matches(&account("user-1", Some("acct-1")), Some(&token)));
+        assert!(matches(&account("user-1::team", None), None));
+        // workspace 条目的 chatgpt_account_id 是 workspace id，不参与身份比较
+        assert!(matches(&account("user-1::ws::org-2", Some("org-2")), Some(&token)));
+        assert!(!matches(&account("user-10", Some("acct-1")), None));
+        assert!(!matches(
+            &account("user-1", Some("acct-1")),
+            Some(&id_token("user-1", "uid-2"))
+        ));
+    }
+
+    #[test]
+    fn finish_login_writes_nothing_once_session_left_pending() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let session = LoginSession::pending("login-1", "verifier", now_ts());
+        storage.insert_login_session(&session).expect("insert session");
+        // 换 token 期间用户点了取消
+        storage
+            .transition_login_session_status(
+                "login-1",
+                "pending",
+                "cancelled",
+                Some("cancelled by user"),
+            )
+            .expect("cancel");
+
+        let err =
+            finish_login(&storage, &session, "issuer", tokens(), None).expect_err("cancelled");
+        assert!(err.contains("cancelled"));
+        assert!(storage.list_accounts().expect("accounts").is_empty());
+        assert!(storage.list_tokens().expect("tokens").is_empty());
+        let loaded = storage.get_login_session("login-1").expect("load").expect("exists");
+        assert_eq!(loaded.status, "cancelled");
+    }
+
+    #[test]
+    fn finish_login_claims_pending_session_and_persists_account() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let session = LoginSession::pending("login-2", "verifier", now_ts());
+        storage.insert_login_session(&session).expect("insert session");
+
+        finish_login(&storage, &session, "issuer", tokens(), None).expect("finish");
+        let loaded = storage.get_login_session("login-2").expect("load").expect("exists");
+        assert_eq!(loaded.status, "success");
+        let accounts = storage.list_accounts().expect("accounts");
+        assert_eq!(accounts[0].id, "user-1");
+        assert_eq!(storage.list_tokens().expect("tokens")[0].refresh_token, "refresh");
+
+        // 同一会话的重复回调不会再写一次
+        assert!(finish_login(&storage, &session, "issuer", tokens(), None).is_err());
+    }
+
+    #[test]
+    fn finish_login_reauths_workspace_entry() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let entry = account("user-1::ws::org-2", Some("org-2"));
+        storage.insert_account(&entry).expect("insert entry");
+        storage
+            .insert_token(&Token {
+                account_id: entry.id.clone(),
+                id_token: ID_TOKEN.to_string(),
+                access_token: "old-access".to_string(),
+                refresh_token: "old-refresh".to_string(),
+                api_key_access_token: None,
+                last_refresh: now_ts(),
+            })
+            .expect("insert token");
+        storage
+            .update_account_status(&entry.id, "inactive")
+            .expect("deactivate");
+        let session = LoginSession {
+            reauth_account_id: Some(entry.id.clone()),
+            ..LoginSession::pending("login-3", "verifier", now_ts())
+        };
+        storage.insert_login_session(&session).expect("insert session");
+
+        finish_login(&storage, &session, "issuer", tokens(), None).expect("reauth");
+        let loaded = storage.find_account(&entry.id).expect("find").expect("exists");
+        assert_eq!(loaded.status, "active");
+        assert_eq!(loaded.chatgpt_account_id.as_deref(), Some("org-2"));
+        let token = storage.find_token(&entry.id).expect("find").expect("exists");
+        assert_eq!(token.refresh_token, "refresh");
+        // 重新授权不会另建主条目
+        assert_eq!(storage.list_accounts().expect("accounts").len(), 1);
+    }
+}
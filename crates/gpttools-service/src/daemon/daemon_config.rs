@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_LOG_MAX_SIZE_MB: u64 = 10;
+const DEFAULT_LOG_MAX_FILES: usize = 5;
+
+/// 重载时直接换进进程内状态的变量；其余 `[env]` 只在启动时写入进程环境，改了需要重启。
+pub(crate) const RELOADABLE_ENV: &[&str] = &["GPTTOOLS_ALLOWED_IPS"];
+
+/// 守护进程配置文件（TOML）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DaemonConfig {
+    /// 控制端口地址，缺省沿用 `GPTTOOLS_SERVICE_ADDR` 或默认端口。
+    #[serde(default)]
+    pub(crate) addr: Option<String>,
+    #[serde(default)]
+    pub(crate) pid_file: Option<PathBuf>,
+    /// 收到 SIGTERM/SIGINT 后等待在途请求结束的最长秒数。
+    #[serde(default)]
+    pub(crate) shutdown_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub(crate) log: LogConfig,
+    /// 写入进程环境的 `GPTTOOLS_*` 变量，与环境变量方式配置的含义相同。
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LogConfig {
+    /// 不填时日志写到 stderr。
+    #[serde(default)]
+    pub(crate) file: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) level: Option<String>,
+    #[serde(default)]
+    pub(crate) max_size_mb: Option<u64>,
+    #[serde(default)]
+    pub(crate) max_files: Option<usize>,
+}
+
+impl DaemonConfig {
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|err| format!("read config {}: {err}", path.display()))?;
+        let mut config =
+            Self::parse(&raw).map_err(|err| format!("invalid config {}: {err}", path.display()))?;
+        config.resolve_relative_paths(path.parent().unwrap_or(Path::new(".")));
+        Ok(config)
+    }
+
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(raw).map_err(|err| err.message().to_string())?;
+        if let Some(key) = config.env.keys().find(|key| !key.starts_with("GPTTOOLS_")) {
+            return Err(format!(
+                "[env] only accepts GPTTOOLS_* variables, got {key}"
+            ));
+        }
+        if config.env.contains_key("GPTTOOLS_SERVICE_ADDR") {
+            return Err(
+                "set the listen address with top-level `addr` instead of [env]".to_string(),
+            );
+        }
+        config.log_level()?;
+        Ok(config)
+    }
+
+    fn resolve_relative_paths(&mut self, base: &Path) {
+        // 中文注释：相对路径按配置文件所在目录解析；按工作目录解析的话，systemd 等以 / 为工作目录启动时会写到意外位置。
+        for path in [self.pid_file.as_mut(), self.log.file.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        }
+    }
+
+    pub(crate) fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.shutdown_timeout_secs
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+        )
+    }
+
+    pub(crate) fn log_level(&self) -> Result<log::LevelFilter, String> {
+        match self.log.level.as_deref() {
+            None => Ok(log::LevelFilter::Info),
+            Some(level) => level
+                .parse()
+                .map_err(|_| format!("invalid log level: {level}")),
+        }
+    }
+
+    pub(crate) fn log_max_bytes(&self) -> u64 {
+        self.log
+            .max_size_mb
+            .unwrap_or(DEFAULT_LOG_MAX_SIZE_MB)
+            .max(1)
+            * 1024
+            * 1024
+    }
+
+    pub(crate) fn log_max_files(&self) -> usize {
+        self.log.max_files.unwrap_or(DEFAULT_LOG_MAX_FILES)
+    }
+
+    /// 重载时沿用旧值的部分（监听地址、pidfile 与不能热更新的变量）。
+    pub(crate) fn keep_restart_only_from(mut self, previous: &Self) -> Self {
+        self.addr = previous.addr.clone();
+        self.pid_file = previous.pid_file.clone();
+        let mut env = previous.env.clone();
+        env.retain(|key, _| !RELOADABLE_ENV.contains(&key.as_str()));
+        for key in RELOADABLE_ENV {
+            if let Some(value) = self.env.get(*key) {
+                env.insert(key.to_string(), value.clone());
+            }
+        }
+        self.env = env;
+        self
+    }
+
+    /// 与旧配置相比，需要重启才能生效的改动项。
+    pub(crate) fn restart_required_changes(&self, previous: &Self) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.addr != previous.addr {
+            changed.push("addr".to_string());
+        }
+        if self.pid_file != previous.pid_file {
+            changed.push("pid_file".to_string());
+        }
+        let keys: std::collections::BTreeSet<&String> =
+            self.env.keys().chain(previous.env.keys()).collect();
+        for key in keys {
+            if !RELOADABLE_ENV.contains(&key.as_str()) && self.env.get(key) != previous.env.get(key)
+            {
+                changed.push(format!("env.{key}"));
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DaemonConfig;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn parses_full_config_and_applies_defaults() {
+        let config = DaemonConfig::parse(
+            r#"
+            addr = "127.0.0.1:48760"
+            pid_file = "/run/gpttools.pid"
+            shutdown_timeout_secs = 5
+
+            [log]
+            file = "logs/service.log"
+            level = "debug"
+            max_files = 2
+
+            [env]
+            GPTTOOLS_DB_PATH = "/var/lib/gpttools/gpttools.db"
+            GPTTOOLS_PLAN_ROUTING = "1"
+            "#,
+        )
+        .expect("parse");
+        assert_eq!(config.addr.as_deref(), Some("127.0.0.1:48760"));
+        assert_eq!(config.shutdown_timeout().as_secs(), 5);
+        assert_eq!(config.log_level().unwrap(), log::LevelFilter::Debug);
+        assert_eq!(config.log_max_bytes(), 10 * 1024 * 1024);
+        assert_eq!(config.log_max_files(), 2);
+        assert_eq!(config.env.len(), 2);
+
+        let empty = DaemonConfig::parse("").expect("empty config");
+        assert_eq!(empty.shutdown_timeout().as_secs(), 30);
+        assert_eq!(empty.log_level().unwrap(), log::LevelFilter::Info);
+    }
+
+    #[test]
+    fn rejects_typos_and_foreign_env_keys() {
+        assert!(DaemonConfig::parse("pidfile = \"x\"").is_err());
+        assert!(DaemonConfig::parse("[env]\nPATH = \"/bin\"").is_err());
+        assert!(DaemonConfig::parse("[env]\nGPTTOOLS_SERVICE_ADDR = \"x\"").is_err());
+        assert!(DaemonConfig::parse("[log]\nlevel = \"loud\"").is_err());
+    }
+
+    #[test]
+    fn relative_paths_follow_config_dir_and_restart_keys_are_reported() {
+        let mut config =
+            DaemonConfig::parse("pid_file = \"run/gpttools.pid\"\n[log]\nfile = \"/abs.log\"")
+                .expect("parse");
+        config.resolve_relative_paths(Path::new("/etc/gpttools"));
+        assert_eq!(
+            config.pid_file,
+            Some(PathBuf::from("/etc/gpttools/run/gpttools.pid"))
+        );
+        assert_eq!(config.log.file, Some(PathBuf::from("/abs.log")));
+
+        let mut next = config.clone();
+        next.env
+            .insert("GPTTOOLS_DB_PATH".to_string(), "/tmp/other.db".to_string());
+        next.env
+            .insert("GPTTOOLS_RESERVE_GROUPS".to_string(), "vip".to_string());
+        next.env
+            .insert("GPTTOOLS_ALLOWED_IPS".to_string(), "10.0.0.0/8".to_string());
+        assert_eq!(
+            next.restart_required_changes(&config),
+            vec![
+                "env.GPTTOOLS_DB_PATH".to_string(),
+                "env.GPTTOOLS_RESERVE_GROUPS".to_string()
+            ]
+        );
+        let applied = next.keep_restart_only_from(&config);
+        assert!(!applied.env.contains_key("GPTTOOLS_DB_PATH"));
+        assert!(!applied.env.contains_key("GPTTOOLS_RESERVE_GROUPS"));
+        assert_eq!(
+            applied.env.get("GPTTOOLS_ALLOWED_IPS").map(String::as_str),
+            Some("10.0.0.0/8")
+        );
+    }
+}
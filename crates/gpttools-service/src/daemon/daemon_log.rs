@@ -0,0 +1,254 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gpttools_core::redact::redact_secrets;
+use log::{LevelFilter, Log, Metadata, Record};
+
+static LOGGER: DaemonLogger = DaemonLogger {
+    sink: Mutex::new(None),
+};
+
+/// 日志文件与轮转参数；`file` 为空时写 stderr。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogTarget {
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) max_bytes: u64,
+    pub(crate) max_files: usize,
+}
+
+struct FileSink {
+    target: LogTarget,
+    file: Option<File>,
+    written: u64,
+}
+
+struct DaemonLogger {
+    sink: Mutex<Option<FileSink>>,
+}
+
+fn open_append(path: &Path) -> io::Result<(File, u64)> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let len = file.metadata()?.len();
+    Ok((file, len))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+fn rotate_files(path: &Path, max_files: usize) -> io::Result<()> {
+    // 当前文件改名为 .1，已有的 .N 依次后移，超出保留数的直接删掉
+    if max_files == 0 {
+        return std::fs::remove_file(path);
+    }
+    let _ = std::fs::remove_file(rotated_path(path, max_files));
+    for index in (1..max_files).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+impl FileSink {
+    fn open(target: LogTarget) -> io::Result<Self> {
+        let (file, written) = match target.file.as_deref() {
+            Some(path) => {
+                let (file, len) = open_append(path)?;
+                (Some(file), len)
+            }
+            None => (None, 0),
+        };
+        Ok(Self {
+            target,
+            file,
+            written,
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) {
+        let Some(path) = self.target.file.clone() else {
+            let _ = io::stderr().write_all(line);
+            return;
+        };
+        if self.written > 0 && self.written + line.len() as u64 > self.target.max_bytes {
+            // 中文注释：轮转失败（磁盘满、权限变化）时继续往旧文件追加；不这样做会把失败前后的日志一起丢掉。
+            self.file = None;
+            match rotate_files(&path, self.target.max_files).and_then(|_| open_append(&path)) {
+                Ok((file, len)) => {
+                    self.file = Some(file);
+                    self.written = len;
+                }
+                Err(err) => {
+                    let _ = writeln!(io::stderr(), "log rotate {} failed: {err}", path.display());
+                    if let Ok((file, len)) = open_append(&path) {
+                        self.file = Some(file);
+                        self.written = len;
+                    }
+                }
+            }
+        }
+        let written = self
+            .file
+            .as_mut()
+            .is_some_and(|file| file.write_all(line).is_ok());
+        if written {
+            self.written += line.len() as u64;
+        } else {
+            let _ = io::stderr().write_all(line);
+        }
+    }
+}
+
+fn format_record(record: &Record<'_>) -> Vec<u8> {
+    // 每条日志一行 JSON，方便 journald/日志采集按字段检索
+    let ts_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut line = serde_json::json!({
+        "ts": ts_ms,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        // 中文注释：日志文件会轮转留存在磁盘上，消息里可能带着上游错误体回显的 token，写入前先统一脱敏。
+        "msg": redact_secrets(&record.args().to_string()),
+    })
+    .to_string()
+    .into_bytes();
+    line.push(b'\n');
+    line
+}
+
+impl Log for DaemonLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(record);
+        let mut sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match sink.as_mut() {
+            Some(sink) => sink.write_line(&line),
+            None => {
+                let _ = io::stderr().write_all(&line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let mut sink = self
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(file) = sink.as_mut().and_then(|sink| sink.file.as_mut()) {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// 安装（或按新配置重新打开）守护进程日志；重复调用只切换输出与级别。
+pub(crate) fn configure_logging(target: LogTarget, level: LevelFilter) -> Result<(), String> {
+    let next = FileSink::open(target.clone()).map_err(|err| {
+        format!(
+            "open log file {}: {err}",
+            target
+                .file
+                .as_deref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        )
+    })?;
+    {
+        let mut sink = LOGGER
+            .sink
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // 中文注释：SIGHUP 时总是重新打开文件，外部 logrotate 把文件挪走后也能写到新文件里。
+        *sink = Some(next);
+    }
+    // 已安装过时 set_logger 会返回错误，忽略即可
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_record, rotated_path, FileSink, LogTarget};
+
+    #[test]
+    fn file_sink_rotates_by_size_and_keeps_limited_history() {
+        let dir = std::env::temp_dir().join(format!("gpttools-daemon-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("logs/service.log");
+        let mut sink = FileSink::open(LogTarget {
+            file: Some(path.clone()),
+            max_bytes: 16,
+            max_files: 2,
+        })
+        .expect("open sink");
+        for index in 0..5 {
+            sink.write_line(format!("line-{index}-abcdef\n").as_bytes());
+        }
+        let read = |p: &std::path::Path| std::fs::read_to_string(p).unwrap_or_default();
+        assert_eq!(read(&path), "line-4-abcdef\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "line-3-abcdef\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "line-2-abcdef\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        // 重新打开时沿用已有文件长度，不会立即截断
+        let reopened = FileSink::open(LogTarget {
+            file: Some(path.clone()),
+            max_bytes: 16,
+            max_files: 2,
+        })
+        .expect("reopen");
+        assert_eq!(reopened.written, 14);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_lines_mask_bearer_tokens_and_secret_keys() {
+        let dir =
+            std::env::temp_dir().join(format!("gpttools-daemon-log-redact-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("service.log");
+        let mut sink = FileSink::open(LogTarget {
+            file: Some(path.clone()),
+            max_bytes: 1024 * 1024,
+            max_files: 1,
+        })
+        .expect("open sink");
+        let args = format_args!(
+            "login request error: upstream echoed Authorization: Bearer tok_abcdef123456 for sk-live0123456789abcdef"
+        );
+        let record = log::Record::builder()
+            .args(args)
+            .level(log::Level::Warn)
+            .target("gpttools_service::auth_callback")
+            .build();
+        sink.write_line(&format_record(&record));
+
+        let line = std::fs::read_to_string(&path).expect("read log");
+        assert!(!line.contains("tok_abcdef123456"), "{line}");
+        assert!(!line.contains("sk-live0123456789abcdef"), "{line}");
+        assert!(line.contains("[REDACTED]"), "{line}");
+        assert!(line.contains("login request error"), "{line}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,165 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 独占创建 pidfile 的最多尝试次数，超过说明有别的实例在反复抢同一个文件。
+const ACQUIRE_ATTEMPTS: usize = 5;
+/// pidfile 已存在但还没写入 pid 时最多等待的轮数。
+const PID_WRITE_WAITS: usize = 3;
+
+/// 进程存活期间持有的 pidfile，释放时只删除仍写着本进程 pid 的文件。
+pub(crate) struct PidFile {
+    path: PathBuf,
+    pid: u32,
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn read_written_pid(path: &Path) -> Option<u32> {
+    // 中文注释：读不到 pid 可能是另一个实例刚建好文件还没写入，先等一下再判断；不这样做两个同时启动的实例会互删对方的 pidfile。
+    for _ in 0..PID_WRITE_WAITS {
+        if let Some(pid) = read_pid(path) {
+            return Some(pid);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    read_pid(path)
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // 中文注释：signal 0 只做存在性检查；EPERM 说明进程在但属于别的用户，同样视为占用。
+    let rc = unsafe { libc::kill(pid, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+impl PidFile {
+    pub(crate) fn acquire(path: &Path) -> Result<Self, String> {
+        // 用 create_new 独占创建 pidfile；已存在时确认原进程不在了才删掉残留文件重试
+        let pid = std::process::id();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("create pidfile dir {}: {err}", parent.display()))?;
+        }
+        for _ in 0..ACQUIRE_ATTEMPTS {
+            let err = match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+            {
+                Ok(mut file) => {
+                    file.write_all(format!("{pid}\n").as_bytes())
+                        .map_err(|err| format!("write pidfile {}: {err}", path.display()))?;
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        pid,
+                    });
+                }
+                Err(err) => err,
+            };
+            if err.kind() != std::io::ErrorKind::AlreadyExists {
+                return Err(format!("create pidfile {}: {err}", path.display()));
+            }
+            match read_written_pid(path) {
+                Some(existing) if existing != pid && process_alive(existing) => {
+                    return Err(format!(
+                        "gpttools-service already running (pid {existing}, pidfile {})",
+                        path.display()
+                    ));
+                }
+                _ => {}
+            }
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(format!("remove stale pidfile {}: {err}", path.display())),
+            }
+        }
+        Err(format!(
+            "acquire pidfile {}: still contended after {ACQUIRE_ATTEMPTS} attempts",
+            path.display()
+        ))
+    }
+
+    pub(crate) fn release(&self) {
+        // 中文注释：只删写着自己 pid 的文件；否则新实例已接管时旧进程退出会把新实例的 pidfile 删掉。
+        if read_pid(&self.path) == Some(self.pid) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PidFile;
+
+    #[test]
+    fn pidfile_replaces_stale_entries_and_only_removes_its_own_pid() {
+        let dir = std::env::temp_dir().join(format!("gpttools-daemon-pid-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("run/gpttools.pid");
+
+        let pidfile = PidFile::acquire(&path).expect("acquire");
+        let own = std::process::id().to_string();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().trim(), own);
+        drop(pidfile);
+        assert!(!path.exists());
+
+        // 超出 pid 范围、不可能存活的残留记录会被覆盖
+        std::fs::write(&path, "4000000000\n").expect("stale pid");
+        let pidfile = PidFile::acquire(&path).expect("replace stale");
+        std::fs::write(&path, "4000000001\n").expect("taken over");
+        drop(pidfile);
+        assert!(path.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pidfile_refuses_live_process() {
+        let dir = std::env::temp_dir().join(format!("gpttools-daemon-live-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("dir");
+        let path = dir.join("gpttools.pid");
+        // 父进程一定存活，用它模拟已在运行的实例
+        std::fs::write(&path, format!("{}\n", std::os::unix::process::parent_id()))
+            .expect("write live pid");
+        let err = PidFile::acquire(&path).err().expect("refuse live pid");
+        assert!(err.contains("already running"), "{err}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pidfile_without_pid_is_replaced_after_waiting_for_writer() {
+        let dir =
+            std::env::temp_dir().join(format!("gpttools-daemon-empty-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("dir");
+        let path = dir.join("gpttools.pid");
+        // 空文件模拟另一个实例建了文件却没写完就退出
+        std::fs::write(&path, "").expect("empty pidfile");
+        let pidfile = PidFile::acquire(&path).expect("replace empty pidfile");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        drop(pidfile);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
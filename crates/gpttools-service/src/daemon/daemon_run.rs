@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::daemon_config::{DaemonConfig, RELOADABLE_ENV};
+use crate::daemon_log::{configure_logging, LogTarget};
+use crate::daemon_pidfile::PidFile;
+use crate::http::ip_allowlist::{install_ip_allowlist, IpAllowlist};
+use crate::storage_info;
+use gpttools_core::rpc::types::StorageRollbackResult;
+
+const ENV_SERVICE_ADDR: &str = "GPTTOOLS_SERVICE_ADDR";
+const ENV_DB_PATH: &str = "GPTTOOLS_DB_PATH";
+const ENV_ALLOWED_IPS: &str = "GPTTOOLS_ALLOWED_IPS";
+#[cfg(unix)]
+const FORCE_EXIT_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+struct DaemonState {
+    config_path: PathBuf,
+    config: DaemonConfig,
+    /// 配置接管前进程环境里可热更新变量的原值，配置里删掉某项时据此还原。
+    startup_env: BTreeMap<String, Option<String>>,
+}
+
+fn invalid_input(err: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
+}
+
+fn log_target(config: &DaemonConfig) -> LogTarget {
+    LogTarget {
+        file: config.log.file.clone(),
+        max_bytes: config.log_max_bytes(),
+        max_files: config.log_max_files(),
+    }
+}
+
+fn apply_env(env: &BTreeMap<String, String>) {
+    // 中文注释：服务各处按需读取环境变量，写回进程环境就能让 [env] 与直接设环境变量的效果一致，不必逐个模块改读取方式。
+    // 只能在起任何线程之前调用一次：别的线程读环境变量时 set_var 是未定义行为，所以重载不再改进程环境。
+    for (key, value) in env {
+        std::env::set_var(key, value);
+    }
+}
+
+/// 离线回滚库结构：按配置（或环境变量）找到库文件，确认服务没在跑之后回滚并返回。
+pub(crate) fn rollback_storage(
+    config_path: Option<&Path>,
+    target: &str,
+) -> Result<StorageRollbackResult, String> {
+    let config = match config_path {
+        Some(path) => DaemonConfig::load(path)?,
+        None => DaemonConfig::default(),
+    };
+    let db_path = config
+        .env
+        .get(ENV_DB_PATH)
+        .cloned()
+        .or_else(|| std::env::var(ENV_DB_PATH).ok())
+        .ok_or_else(|| format!("{ENV_DB_PATH} not set"))?;
+    // 中文注释：运行中的服务按当前表结构查询，在线回滚会删掉它正在读的列；占住 pidfile 并确认监听地址空闲后才动库。
+    let _pid_file = config
+        .pid_file
+        .as_deref()
+        .map(PidFile::acquire)
+        .transpose()?;
+    let addr = config
+        .addr
+        .clone()
+        .or_else(|| std::env::var(ENV_SERVICE_ADDR).ok())
+        .unwrap_or_else(|| crate::DEFAULT_ADDR.to_string());
+    if std::net::TcpStream::connect(addr.as_str()).is_ok() {
+        return Err(format!(
+            "gpttools-service is still listening on {addr}; stop it before rolling back"
+        ));
+    }
+    storage_info::rollback_storage(Path::new(&db_path), target)
+}
+
+fn reload_allowlist(state: &DaemonState) {
+    // 白名单每次请求都读当前生效的一份，重载时直接替换
+    let raw = state
+        .config
+        .env
+        .get(ENV_ALLOWED_IPS)
+        .cloned()
+        .or_else(|| state.startup_env.get(ENV_ALLOWED_IPS).cloned().flatten())
+        .unwrap_or_default();
+    match IpAllowlist::parse(&raw) {
+        Ok(list) => install_ip_allowlist(Some(list)),
+        Err(err) => log::warn!("keeping previous ip allowlist: {err}"),
+    }
+}
+
+fn reload(state: &Mutex<DaemonState>) {
+    let mut state = state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let next = match DaemonConfig::load(&state.config_path) {
+        Ok(next) => next,
+        Err(err) => {
+            // 中文注释：重载失败保留当前配置继续运行；不这样做一个手误就会让正在服务的进程退出。
+            log::error!("config reload failed, keeping current config: {err}");
+            return;
+        }
+    };
+    let level = next.log_level().unwrap_or(log::LevelFilter::Info);
+    if let Err(err) = configure_logging(log_target(&next), level) {
+        log::error!("config reload failed, keeping current config: {err}");
+        return;
+    }
+    let pending = next.restart_required_changes(&state.config);
+    if !pending.is_empty() {
+        log::warn!(
+            "config changes need a restart to take effect: {}",
+            pending.join(", ")
+        );
+    }
+    state.config = next.keep_restart_only_from(&state.config);
+    reload_allowlist(&state);
+    log::info!("config reloaded from {}", state.config_path.display());
+}
+
+#[cfg(unix)]
+fn spawn_signal_handler(
+    state: Arc<Mutex<DaemonState>>,
+    addr: String,
+    pidfile: Arc<Option<PidFile>>,
+) -> io::Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGHUP])?;
+    std::thread::Builder::new()
+        .name("gpttools-signals".to_string())
+        .spawn(move || {
+            let mut stopping = false;
+            for signal in signals.forever() {
+                if signal == SIGHUP {
+                    reload(&state);
+                    continue;
+                }
+                if stopping {
+                    log::warn!("second stop signal received, exiting without waiting");
+                    force_exit(&pidfile, 1);
+                }
+                stopping = true;
+                let timeout = state
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .config
+                    .shutdown_timeout();
+                log::info!(
+                    "received {}, draining in-flight requests (up to {}s)",
+                    if signal == SIGINT {
+                        "SIGINT"
+                    } else {
+                        "SIGTERM"
+                    },
+                    timeout.as_secs()
+                );
+                crate::request_shutdown_within(&addr, timeout);
+                let pidfile = pidfile.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(timeout + FORCE_EXIT_GRACE);
+                    // 中文注释：服务到期限会自行截断在途流并退出，这里只兜底收尾卡住的情况（如阻塞在上游握手的线程），避免 systemd 等停服卡住。
+                    log::warn!(
+                        "shutdown deadline of {}s exceeded, exiting with requests still in flight",
+                        timeout.as_secs()
+                    );
+                    force_exit(&pidfile, 1);
+                });
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn force_exit(pidfile: &Option<PidFile>, code: i32) -> ! {
+    crate::gateway::flush_request_logs();
+    if let Some(pidfile) = pidfile {
+        pidfile.release();
+    }
+    log::logger().flush();
+    std::process::exit(code)
+}
+
+pub(crate) fn run_daemon(config_path: &Path) -> io::Result<()> {
+    // 读取配置、接管日志与 pidfile，然后在当前线程跑服务直到停机
+    let config = DaemonConfig::load(config_path).map_err(invalid_input)?;
+    let level = config.log_level().map_err(invalid_input)?;
+    let startup_env = RELOADABLE_ENV
+        .iter()
+        .map(|key| (key.to_string(), std::env::var(key).ok()))
+        .collect();
+    // 中文注释：日志可能起后台线程，[env] 要赶在它之前写入进程环境。
+    apply_env(&config.env);
+    configure_logging(log_target(&config), level).map_err(invalid_input)?;
+    let mut state = DaemonState {
+        config_path: config_path.to_path_buf(),
+        config: DaemonConfig::default(),
+        startup_env,
+    };
+    let addr = config
+        .addr
+        .clone()
+        .or_else(|| std::env::var(ENV_SERVICE_ADDR).ok())
+        .unwrap_or_else(|| crate::DEFAULT_ADDR.to_string());
+    let pidfile = config
+        .pid_file
+        .as_deref()
+        .map(PidFile::acquire)
+        .transpose()
+        .map_err(invalid_input)?;
+    state.config = config;
+    let pidfile = Arc::new(pidfile);
+    #[cfg(unix)]
+    spawn_signal_handler(Arc::new(Mutex::new(state)), addr.clone(), pidfile.clone())?;
+    #[cfg(not(unix))]
+    drop(state);
+
+    log::info!(
+        "gpttools-service daemon started (pid {}, listening on {addr})",
+        std::process::id()
+    );
+    let result = crate::start_server(&addr);
+    match &result {
+        Ok(()) => log::info!("gpttools-service stopped"),
+        Err(err) => log::error!("gpttools-service stopped: {err}"),
+    }
+    if let Some(pidfile) = pidfile.as_ref() {
+        pidfile.release();
+    }
+    log::logger().flush();
+    result
+}
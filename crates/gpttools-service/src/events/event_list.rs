@@ -0,0 +1,40 @@
+use gpttools_core::rpc::types::EventSummary;
+use gpttools_core::storage::{EventQuery, EventRecord};
+
+use crate::storage_helpers::open_storage;
+
+fn to_summary(item: EventRecord) -> EventSummary {
+    EventSummary {
+        id: item.id,
+        account_id: item.account_id,
+        event_type: item.event_type,
+        message: item.message,
+        created_at: item.created_at,
+    }
+}
+
+pub(crate) fn read_events(query: &EventQuery) -> Vec<EventSummary> {
+    // 读取事件列表（按 id 倒序）
+    let storage = match open_storage() {
+        Some(storage) => storage,
+        None => return Vec::new(),
+    };
+    match storage.list_events(query) {
+        Ok(items) => items.into_iter().map(to_summary).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn read_events_after(after_id: i64, query: &EventQuery) -> Result<Vec<EventSummary>, String> {
+    // 读取游标之后的新事件（按 id 正序）
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    storage
+        .list_events_after(after_id, query)
+        .map(|items| items.into_iter().map(to_summary).collect())
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn latest_event_id() -> Result<i64, String> {
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    storage.latest_event_id().map_err(|e| e.to_string())
+}
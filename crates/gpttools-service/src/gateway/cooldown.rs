@@ -9,6 +9,8 @@ const DEFAULT_ACCOUNT_COOLDOWN_429_SECS: i64 = 45;
 const DEFAULT_ACCOUNT_COOLDOWN_5XX_SECS: i64 = 30;
 const DEFAULT_ACCOUNT_COOLDOWN_4XX_SECS: i64 = DEFAULT_ACCOUNT_COOLDOWN_SECS;
 const DEFAULT_ACCOUNT_COOLDOWN_CHALLENGE_SECS: i64 = 6;
+// 中文注释：首个增量前断流多是单条连接抖动，不代表账号本身 5xx；冷却短一些，避免一次断流就把账号挂起半分钟。
+const DEFAULT_ACCOUNT_COOLDOWN_STREAM_PREFETCH_SECS: i64 = 10;
 
 static ACCOUNT_COOLDOWN_UNTIL: OnceLock<Mutex<HashMap<String, i64>>> = OnceLock::new();
 
@@ -20,6 +22,7 @@ pub(super) enum CooldownReason {
     Upstream5xx,
     Upstream4xx,
     Challenge,
+    StreamPrefetch,
 }
 
 fn cooldown_secs_for_reason(reason: CooldownReason) -> i64 {
@@ -30,6 +33,7 @@ fn cooldown_secs_for_reason(reason: CooldownReason) -> i64 {
         CooldownReason::Upstream5xx => DEFAULT_ACCOUNT_COOLDOWN_5XX_SECS,
         CooldownReason::Upstream4xx => DEFAULT_ACCOUNT_COOLDOWN_4XX_SECS,
         CooldownReason::Challenge => DEFAULT_ACCOUNT_COOLDOWN_CHALLENGE_SECS,
+        CooldownReason::StreamPrefetch => DEFAULT_ACCOUNT_COOLDOWN_STREAM_PREFETCH_SECS,
     }
 }
 
@@ -53,6 +57,17 @@ pub(super) fn is_account_in_cooldown(account_id: &str) -> bool {
     map.get(account_id).copied().unwrap_or(0) > now
 }
 
+pub(crate) fn cooldown_account_count() -> usize {
+    // 统计仍在冷却期内的账号数，顺带清掉已过期的记录
+    let lock = ACCOUNT_COOLDOWN_UNTIL.get_or_init(|| Mutex::new(HashMap::new()));
+    let Ok(mut map) = lock.lock() else {
+        return 0;
+    };
+    let now = now_ts();
+    map.retain(|_, until| *until > now);
+    map.len()
+}
+
 pub(super) fn mark_account_cooldown(account_id: &str, reason: CooldownReason) {
     let lock = ACCOUNT_COOLDOWN_UNTIL.get_or_init(|| Mutex::new(HashMap::new()));
     if let Ok(mut map) = lock.lock() {
@@ -11,14 +11,16 @@ pub(crate) fn should_failover_after_refresh(
     match refresh_result {
         Ok(_) => {
             let snap = storage
-                .latest_usage_snapshots_by_account()
+                .latest_usage_snapshot_for_account(account_id)
                 .ok()
-                .and_then(|snaps| snaps.into_iter().find(|s| s.account_id == account_id));
+                .flatten();
             match snap.as_ref().map(evaluate_snapshot) {
                 Some(Availability::Unavailable(reason)) => {
                     set_account_status(storage, account_id, "inactive", reason);
                     true
                 }
+                // 中文注释：请求已经失败，用量未知或超软阈值时换下一个账号，但不把账号标成 inactive。
+                Some(Availability::Unknown(_)) | Some(Availability::SoftLimited(_)) => true,
                 Some(Availability::Available) => false,
                 None => {
                     set_account_status(storage, account_id, "inactive", "usage_missing_snapshot");
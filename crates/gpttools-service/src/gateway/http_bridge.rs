@@ -1,133 +1,219 @@
+use axum::body::{Body, Bytes};
+use axum::http::{Response, StatusCode};
+use futures_util::{stream, Stream, StreamExt};
 use serde_json::{json, Map, Value};
-use std::io::{BufRead, BufReader, Cursor, Read};
-use tiny_http::{Header, Request, Response, StatusCode};
+use std::pin::Pin;
 
-use super::AccountInFlightGuard;
+use super::{AccountInFlightGuard, FinalRequestLog, IncomingRequest};
+use crate::http::proxy_response::{merge_upstream_headers, text_response};
 
-pub(super) fn extract_platform_key(request: &Request) -> Option<String> {
+pub(super) type UpstreamByteStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// 已拿到响应头的上游响应；响应体可能已被预读一段，剩余部分仍按流读取。
+pub(super) struct UpstreamReply {
+    pub(super) status: reqwest::StatusCode,
+    pub(super) headers: reqwest::header::HeaderMap,
+    pub(super) body: UpstreamByteStream,
+}
+
+impl UpstreamReply {
+    pub(super) fn from_response(upstream: reqwest::Response) -> Self {
+        Self {
+            status: upstream.status(),
+            headers: upstream.headers().clone(),
+            body: Box::pin(upstream.bytes_stream()),
+        }
+    }
+
+    pub(super) fn is_event_stream(&self) -> bool {
+        self.headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|value| value.to_ascii_lowercase().starts_with("text/event-stream"))
+            .unwrap_or(false)
+    }
+}
+
+pub(super) fn extract_platform_key(request: &IncomingRequest) -> Option<String> {
     // 从请求头提取平台 Key
-    for header in request.headers() {
-        if header.field.equiv("Authorization") {
-            let value = header.value.as_str();
+    for (name, value) in request.headers() {
+        if name.eq_ignore_ascii_case("Authorization") {
             if let Some(rest) = value.strip_prefix("Bearer ") {
                 return Some(rest.trim().to_string());
             }
         }
-        if header.field.equiv("x-api-key") {
-            return Some(header.value.as_str().trim().to_string());
+        if name.eq_ignore_ascii_case("x-api-key") {
+            return Some(value.trim().to_string());
         }
     }
     None
 }
 
+pub(super) fn respond_text(status_code: u16, message: impl Into<String>) -> Response<Body> {
+    let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    text_response(status, message)
+}
+
 pub(super) fn respond_with_upstream(
-    request: Request,
-    upstream: reqwest::blocking::Response,
-    _inflight_guard: AccountInFlightGuard,
+    upstream: UpstreamReply,
+    inflight_guard: AccountInFlightGuard,
+    final_log: FinalRequestLog,
     response_adapter: super::ResponseAdapter,
-) -> Result<(), String> {
+) -> Response<Body> {
+    let status = upstream.status.as_u16();
     match response_adapter {
         super::ResponseAdapter::Passthrough => {
-            let status = StatusCode(upstream.status().as_u16());
-            let mut headers = Vec::new();
-            for (name, value) in upstream.headers().iter() {
-                let name_str = name.as_str();
-                if name_str.eq_ignore_ascii_case("transfer-encoding")
-                    || name_str.eq_ignore_ascii_case("content-length")
-                    || name_str.eq_ignore_ascii_case("connection")
-                {
-                    continue;
-                }
-                if let Ok(header) = Header::from_bytes(name_str.as_bytes(), value.as_bytes()) {
-                    headers.push(header);
-                }
-            }
-            let len = upstream.content_length().map(|v| v as usize);
-            let response = Response::new(status, headers, upstream, len, None);
-            let _ = request.respond(response);
-            Ok(())
+            let builder =
+                merge_upstream_headers(Response::builder().status(status), &upstream.headers);
+            // 中文注释：并发占用要跟着响应体走到流结束；在这里提前释放会让长连接 SSE 期间账号看起来空闲。
+            let body_stream = passthrough_body(upstream.body, inflight_guard, final_log);
+            builder
+                .body(Body::from_stream(body_stream))
+                .unwrap_or_else(|_| respond_text(502, "build upstream response failed"))
         }
         super::ResponseAdapter::AnthropicJson | super::ResponseAdapter::AnthropicSse => {
-            let status = StatusCode(upstream.status().as_u16());
-            let mut headers = Vec::new();
-            for (name, value) in upstream.headers().iter() {
-                let name_str = name.as_str();
-                if name_str.eq_ignore_ascii_case("transfer-encoding")
-                    || name_str.eq_ignore_ascii_case("content-length")
-                    || name_str.eq_ignore_ascii_case("connection")
-                    || name_str.eq_ignore_ascii_case("content-type")
-                {
+            let mut builder = Response::builder().status(status);
+            for (name, value) in upstream.headers.iter() {
+                if name == reqwest::header::CONTENT_TYPE {
                     continue;
                 }
-                if let Ok(header) = Header::from_bytes(name_str.as_bytes(), value.as_bytes()) {
-                    headers.push(header);
+                if crate::http::header_filter::should_skip_response_header(name) {
+                    continue;
                 }
+                builder = builder.header(name, value);
             }
             let upstream_content_type = upstream
-                .headers()
+                .headers
                 .get(reqwest::header::CONTENT_TYPE)
                 .and_then(|v| v.to_str().ok())
                 .map(|v| v.to_string());
 
             if response_adapter == super::ResponseAdapter::AnthropicSse
-                && upstream_content_type
-                    .as_deref()
-                    .map(|value| value.to_ascii_lowercase().starts_with("text/event-stream"))
-                    .unwrap_or(false)
+                && upstream.is_event_stream()
             {
-                if let Ok(content_type_header) = Header::from_bytes(
-                    b"Content-Type".as_slice(),
-                    b"text/event-stream".as_slice(),
-                ) {
-                    headers.push(content_type_header);
-                }
-                let response = Response::new(
-                    status,
-                    headers,
-                    AnthropicSseReader::new(upstream),
-                    None,
-                    None,
-                );
-                let _ = request.respond(response);
-                return Ok(());
+                let body = anthropic_sse_body(upstream.body, inflight_guard, final_log);
+                return builder
+                    .header("Content-Type", "text/event-stream")
+                    .body(body)
+                    .unwrap_or_else(|_| respond_text(502, "build upstream response failed"));
             }
 
-            let upstream_body = upstream
-                .bytes()
-                .map(|v| v.to_vec())
-                .map_err(|err| format!("read upstream body failed: {err}"))?;
+            let upstream_body = match super::block_on_upstream(collect_body(upstream.body)) {
+                Ok(body) => body,
+                Err(err) => {
+                    let message = format!("read upstream body failed: {err}");
+                    final_log.fail_with_status(502, &message);
+                    return respond_text(502, message);
+                }
+            };
 
             let (body, content_type) = match super::protocol_adapter::adapt_upstream_response(
                 response_adapter,
                 upstream_content_type.as_deref(),
                 &upstream_body,
             ) {
-                Ok(result) => result,
-                Err(err) => (
-                    super::protocol_adapter::build_anthropic_error_body(&format!(
-                        "response conversion failed: {err}"
-                    )),
-                    "application/json",
-                ),
+                Ok(result) => {
+                    final_log.complete();
+                    result
+                }
+                Err(err) => {
+                    let message = format!("response conversion failed: {err}");
+                    final_log.fail(&message);
+                    (
+                        super::protocol_adapter::build_anthropic_error_body(&message),
+                        "application/json",
+                    )
+                }
             };
-            if let Ok(content_type_header) =
-                Header::from_bytes(b"Content-Type".as_slice(), content_type.as_bytes())
-            {
-                headers.push(content_type_header);
-            }
-
-            let len = Some(body.len());
-            let response = Response::new(status, headers, std::io::Cursor::new(body), len, None);
-            let _ = request.respond(response);
-            Ok(())
+            builder
+                .header("Content-Type", content_type)
+                .body(Body::from(body))
+                .unwrap_or_else(|_| respond_text(502, "build upstream response failed"))
         }
     }
 }
 
-struct AnthropicSseReader {
-    upstream: BufReader<reqwest::blocking::Response>,
+async fn collect_body(mut body: UpstreamByteStream) -> reqwest::Result<Vec<u8>> {
+    let mut collected = Vec::new();
+    while let Some(chunk) = body.next().await {
+        collected.extend_from_slice(&chunk?);
+    }
+    Ok(collected)
+}
+
+fn passthrough_body(
+    upstream: UpstreamByteStream,
+    inflight_guard: AccountInFlightGuard,
+    final_log: FinalRequestLog,
+) -> impl Stream<Item = reqwest::Result<Bytes>> {
+    // 原样转发上游分块；读到结尾记完成，上游出错记错误，中途被丢弃由最终日志的 Drop 记中断
+    stream::unfold(
+        (upstream, inflight_guard, Some(final_log)),
+        |(mut upstream, guard, mut final_log)| async move {
+            let next = upstream.next().await;
+            match &next {
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    if let Some(final_log) = final_log.take() {
+                        final_log.fail(&format!("upstream stream failed: {err}"));
+                    }
+                }
+                None => {
+                    if let Some(final_log) = final_log.take() {
+                        final_log.complete();
+                    }
+                }
+            }
+            next.map(|chunk| (chunk, (upstream, guard, final_log)))
+        },
+    )
+}
+
+fn anthropic_sse_body(
+    upstream: UpstreamByteStream,
+    inflight_guard: AccountInFlightGuard,
+    final_log: FinalRequestLog,
+) -> Body {
+    // 逐块把上游 OpenAI SSE 翻译成 Anthropic SSE；客户端断开时流被丢弃，上游连接随之关闭
+    let translated = stream::unfold(
+        (upstream, AnthropicSseTranslator::new(), inflight_guard, Some(final_log)),
+        |(mut upstream, mut translator, guard, mut final_log)| async move {
+            loop {
+                if translator.state.finished {
+                    if let Some(final_log) = final_log.take() {
+                        final_log.complete();
+                    }
+                    return None;
+                }
+                let chunk = match upstream.next().await {
+                    Some(Ok(chunk)) => translator.feed(&chunk),
+                    Some(Err(err)) => {
+                        translator.state.finished = true;
+                        if let Some(final_log) = final_log.take() {
+                            final_log.fail(&format!("upstream stream failed: {err}"));
+                        }
+                        return Some((
+                            Err(std::io::Error::other(err)),
+                            (upstream, translator, guard, final_log),
+                        ));
+                    }
+                    None => translator.finish_stream(),
+                };
+                if !chunk.is_empty() {
+                    return Some((
+                        Ok(Bytes::from(chunk)),
+                        (upstream, translator, guard, final_log),
+                    ));
+                }
+            }
+        },
+    );
+    Body::from_stream(translated)
+}
+
+struct AnthropicSseTranslator {
+    line_buffer: Vec<u8>,
     pending_frame_lines: Vec<String>,
-    out_cursor: Cursor<Vec<u8>>,
     state: AnthropicSseState,
 }
 
@@ -144,34 +230,33 @@ struct AnthropicSseState {
     stop_reason: Option<&'static str>,
 }
 
-impl AnthropicSseReader {
-    fn new(upstream: reqwest::blocking::Response) -> Self {
+impl AnthropicSseTranslator {
+    fn new() -> Self {
         Self {
-            upstream: BufReader::new(upstream),
+            line_buffer: Vec::new(),
             pending_frame_lines: Vec::new(),
-            out_cursor: Cursor::new(Vec::new()),
             state: AnthropicSseState::default(),
         }
     }
 
-    fn next_chunk(&mut self) -> std::io::Result<Vec<u8>> {
-        let mut line = String::new();
-        loop {
-            line.clear();
-            let read = self.upstream.read_line(&mut line)?;
-            if read == 0 {
-                return Ok(self.finish_stream());
-            }
+    fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        // 按行切分上游分块，空行代表一个 SSE 帧结束
+        self.line_buffer.extend_from_slice(chunk);
+        let mut out = Vec::new();
+        while let Some(pos) = self.line_buffer.iter().position(|byte| *byte == b'\n') {
+            let raw: Vec<u8> = self.line_buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&raw).into_owned();
             if line == "\n" || line == "\r\n" {
                 let frame = std::mem::take(&mut self.pending_frame_lines);
-                let mapped = self.process_sse_frame(&frame);
-                if !mapped.is_empty() {
-                    return Ok(mapped);
+                out.extend(self.process_sse_frame(&frame));
+                if self.state.finished {
+                    break;
                 }
                 continue;
             }
-            self.pending_frame_lines.push(line.clone());
+            self.pending_frame_lines.push(line);
         }
+        out
     }
 
     fn process_sse_frame(&mut self, lines: &[String]) -> Vec<u8> {
@@ -437,22 +522,6 @@ impl AnthropicSseReader {
     }
 }
 
-impl Read for AnthropicSseReader {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        loop {
-            let read = self.out_cursor.read(buf)?;
-            if read > 0 {
-                return Ok(read);
-            }
-            if self.state.finished {
-                return Ok(0);
-            }
-            let next = self.next_chunk()?;
-            self.out_cursor = Cursor::new(next);
-        }
-    }
-}
-
 fn append_sse_event(buffer: &mut String, event_name: &str, payload: &Value) {
     let data = serde_json::to_string(payload).unwrap_or_else(|_| "{}".to_string());
     buffer.push_str("event: ");
@@ -0,0 +1,79 @@
+use std::net::SocketAddr;
+
+/// 网关入口收到的完整请求；请求体已在 axum 层读完，后续流程只按引用读取头部。
+pub(crate) struct IncomingRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    remote_addr: Option<SocketAddr>,
+    body: Vec<u8>,
+}
+
+impl IncomingRequest {
+    pub(crate) fn new(
+        method: impl Into<String>,
+        url: impl Into<String>,
+        headers: Vec<(String, String)>,
+        remote_addr: Option<SocketAddr>,
+        body: Vec<u8>,
+    ) -> Self {
+        Self {
+            method: method.into(),
+            url: url.into(),
+            headers,
+            remote_addr,
+            body,
+        }
+    }
+
+    pub(crate) fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// 路径 + query，与原 tiny_http `Request::url` 语义一致。
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub(crate) fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    pub(crate) fn take_body(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncomingRequest;
+
+    #[test]
+    fn header_lookup_is_case_insensitive_and_skips_blank_values() {
+        let request = IncomingRequest::new(
+            "POST",
+            "/v1/responses?x=1",
+            vec![
+                ("Session_ID".to_string(), " sess-1 ".to_string()),
+                ("x-codex-turn-state".to_string(), "  ".to_string()),
+            ],
+            None,
+            b"{}".to_vec(),
+        );
+        assert_eq!(request.header("session_id"), Some("sess-1"));
+        assert_eq!(request.header("x-codex-turn-state"), None);
+        assert_eq!(request.url(), "/v1/responses?x=1");
+    }
+}
@@ -1,5 +1,6 @@
+use axum::body::Body;
+use axum::http::Response;
 use serde_json::{json, Value};
-use tiny_http::Response;
 
 use crate::apikey_profile::PROTOCOL_ANTHROPIC_NATIVE;
 
@@ -48,7 +49,6 @@ fn estimate_input_tokens_from_anthropic_messages(body: &[u8]) -> Result<u64, Str
 }
 
 pub(super) fn maybe_respond_local_count_tokens(
-    request: tiny_http::Request,
     trace_id: &str,
     key_id: &str,
     protocol_type: &str,
@@ -58,12 +58,12 @@ pub(super) fn maybe_respond_local_count_tokens(
     model_for_log: Option<&str>,
     reasoning_for_log: Option<&str>,
     storage: &gpttools_core::storage::Storage,
-) -> Result<Option<tiny_http::Request>, String> {
+) -> Option<Response<Body>> {
     let is_anthropic_count_tokens = protocol_type == PROTOCOL_ANTHROPIC_NATIVE
         && request_method.eq_ignore_ascii_case("POST")
         && (path == "/v1/messages/count_tokens" || path.starts_with("/v1/messages/count_tokens?"));
     if !is_anthropic_count_tokens {
-        return Ok(Some(request));
+        return None;
     }
 
     match estimate_input_tokens_from_anthropic_messages(body) {
@@ -82,17 +82,12 @@ pub(super) fn maybe_respond_local_count_tokens(
                 Some(200),
                 None,
             );
-            let response = Response::from_string(output)
-                .with_status_code(200)
-                .with_header(
-                    tiny_http::Header::from_bytes(
-                        b"content-type".as_slice(),
-                        b"application/json".as_slice(),
-                    )
-                    .map_err(|_| "build content-type header failed".to_string())?,
-                );
-            let _ = request.respond(response);
-            Ok(None)
+            let response = Response::builder()
+                .status(200)
+                .header("content-type", "application/json")
+                .body(Body::from(output))
+                .unwrap_or_else(|_| super::respond_text(500, "build content-type header failed"));
+            Some(response)
         }
         Err(err) => {
             super::trace_log::log_attempt_result(trace_id, "-", None, 400, Some(err.as_str()));
@@ -108,9 +103,7 @@ pub(super) fn maybe_respond_local_count_tokens(
                 Some(400),
                 Some(err.as_str()),
             );
-            let response = Response::from_string(err.clone()).with_status_code(400);
-            let _ = request.respond(response);
-            Ok(None)
+            Some(super::respond_text(400, err))
         }
     }
 }
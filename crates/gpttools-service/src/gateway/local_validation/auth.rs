@@ -1,8 +1,8 @@
 use gpttools_core::storage::{ApiKey, Storage};
 
-use crate::storage_helpers::{hash_platform_key, open_storage};
+use crate::storage_helpers::{hash_platform_key, open_storage, PooledStorage};
 
-pub(super) fn open_storage_or_error() -> Result<Storage, super::LocalValidationError> {
+pub(super) fn open_storage_or_error() -> Result<PooledStorage, super::LocalValidationError> {
     open_storage().ok_or_else(|| super::LocalValidationError::new(500, "storage unavailable"))
 }
 
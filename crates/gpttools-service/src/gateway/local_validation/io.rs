@@ -1,14 +1,7 @@
-use tiny_http::Request;
-
-pub(super) fn read_request_body(request: &mut Request) -> Vec<u8> {
-    // 中文注释：先把请求体读完再进入鉴权判断，避免客户端写流还在进行时被提前断开。
-    let mut body = Vec::new();
-    let _ = request.as_reader().read_to_end(&mut body);
-    body
-}
+use super::super::IncomingRequest;
 
 pub(super) fn extract_platform_key_or_error(
-    request: &Request,
+    request: &IncomingRequest,
     debug: bool,
 ) -> Result<String, super::LocalValidationError> {
     if let Some(platform_key) = super::super::extract_platform_key(request) {
@@ -21,27 +14,22 @@ pub(super) fn extract_platform_key_or_error(
             .map(|a| a.to_string())
             .unwrap_or_else(|| "<none>".to_string());
         let auth_scheme = request
-            .headers()
-            .iter()
-            .find(|h| h.field.equiv("Authorization"))
-            .and_then(|h| h.value.as_str().split_whitespace().next())
+            .header("Authorization")
+            .and_then(|value| value.split_whitespace().next())
             .unwrap_or("<none>");
         let header_names = request
             .headers()
             .iter()
-            .map(|h| h.field.as_str().as_str())
+            .map(|(name, _)| name.as_str())
             .collect::<Vec<_>>()
             .join(",");
         eprintln!(
             "gateway auth missing: url={}, remote={}, has_auth={}, auth_scheme={}, has_x_api_key={}, headers=[{}]",
             request.url(),
             remote,
-            request
-                .headers()
-                .iter()
-                .any(|h| h.field.equiv("Authorization")),
+            request.header("Authorization").is_some(),
             auth_scheme,
-            request.headers().iter().any(|h| h.field.equiv("x-api-key")),
+            request.header("x-api-key").is_some(),
             header_names,
         );
     }
@@ -1,6 +1,8 @@
-use gpttools_core::storage::Storage;
 use reqwest::Method;
-use tiny_http::Request;
+
+use crate::storage_helpers::PooledStorage;
+
+use super::IncomingRequest;
 
 mod auth;
 mod io;
@@ -8,7 +10,7 @@ mod request;
 
 pub(super) struct LocalValidationResult {
     pub(super) trace_id: String,
-    pub(super) storage: Storage,
+    pub(super) storage: PooledStorage,
     pub(super) path: String,
     pub(super) body: Vec<u8>,
     pub(super) is_stream: bool,
@@ -36,11 +38,11 @@ impl LocalValidationError {
 }
 
 pub(super) fn prepare_local_request(
-    request: &mut Request,
+    request: &mut IncomingRequest,
     trace_id: String,
     debug: bool,
 ) -> Result<LocalValidationResult, LocalValidationError> {
-    let body = io::read_request_body(request);
+    let body = request.take_body();
     let platform_key = io::extract_platform_key_or_error(request, debug)?;
 
     let storage = auth::open_storage_or_error()?;
@@ -1,7 +1,9 @@
-use gpttools_core::storage::{ApiKey, Storage};
+use gpttools_core::storage::ApiKey;
 use reqwest::Method;
-use tiny_http::Request;
 
+use crate::storage_helpers::PooledStorage;
+
+use super::super::IncomingRequest;
 use super::{LocalValidationError, LocalValidationResult};
 
 fn resolve_effective_request_overrides(api_key: &ApiKey) -> (Option<String>, Option<String>) {
@@ -25,9 +27,9 @@ fn resolve_effective_request_overrides(api_key: &ApiKey) -> (Option<String>, Opt
 }
 
 pub(super) fn build_local_validation_result(
-    request: &Request,
+    request: &IncomingRequest,
     trace_id: String,
-    storage: Storage,
+    storage: PooledStorage,
     mut body: Vec<u8>,
     api_key: ApiKey,
 ) -> Result<LocalValidationResult, LocalValidationError> {
@@ -49,7 +51,7 @@ pub(super) fn build_local_validation_result(
         effective_reasoning.as_deref(),
     );
 
-    let request_method = request.method().as_str().to_string();
+    let request_method = request.method().to_string();
     let method = Method::from_bytes(request_method.as_bytes())
         .map_err(|_| LocalValidationError::new(405, "unsupported method"))?;
 
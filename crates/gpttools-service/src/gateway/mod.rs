@@ -1,5 +1,6 @@
 use crate::storage_helpers::open_storage;
 
+mod incoming;
 mod local_validation;
 mod upstream;
 mod request_helpers;
@@ -20,6 +21,7 @@ mod trace_log;
 mod route_hint;
 mod local_count_tokens;
 mod route_quality;
+mod plan_routing;
 
 pub(super) use request_helpers::{
     extract_request_model, extract_request_reasoning_effort, extract_request_stream,
@@ -43,29 +45,38 @@ use metrics::{
     record_gateway_cooldown_mark, record_gateway_failover_attempt, AccountInFlightGuard,
 };
 pub(crate) use metrics::gateway_metrics_prometheus;
-use selection::{collect_gateway_candidates, rotate_candidates_for_fairness};
+pub(crate) use selection::collect_gateway_candidates;
+use selection::rotate_candidates_for_fairness;
 use upstream::candidates::prepare_gateway_candidates;
+use plan_routing::{configured_plan_routing_rules, order_candidates_by_plan};
 use failover::should_failover_after_refresh;
 pub(crate) use model_picker::fetch_models_for_picker;
-use http_bridge::{extract_platform_key, respond_with_upstream};
+use http_bridge::{
+    extract_platform_key, respond_text, respond_with_upstream, UpstreamByteStream, UpstreamReply,
+};
+pub(crate) use cooldown::cooldown_account_count;
 use cooldown::{
     clear_account_cooldown, is_account_in_cooldown, mark_account_cooldown,
     mark_account_cooldown_for_status, CooldownReason,
 };
 #[cfg(test)]
 use cooldown::cooldown_reason_for_status;
-#[cfg(test)]
-use token_exchange::account_token_exchange_lock;
+pub(crate) use token_exchange::{
+    account_token_exchange_lock, exchange_and_persist_api_key_access_token,
+};
 use token_exchange::resolve_openai_bearer_token;
 use openai_fallback::try_openai_fallback;
-use request_log::write_request_log;
+use request_log::{write_request_log, FinalRequestLog};
+pub(crate) use request_log::flush_request_logs;
+pub(crate) use incoming::IncomingRequest;
 pub(crate) use request_entry::handle_gateway_request;
 use route_hint::{preferred_route_account, remember_success_route_account};
 use local_count_tokens::maybe_respond_local_count_tokens;
 use route_quality::{record_route_quality, route_quality_penalty};
+pub(crate) use runtime_config::evict_upstream_client_for_proxy;
 use runtime_config::{
-    account_max_inflight_limit, upstream_client, DEFAULT_GATEWAY_DEBUG,
-    DEFAULT_MODELS_CLIENT_VERSION,
+    account_max_inflight_limit, block_on_upstream, upstream_client_for_proxy,
+    DEFAULT_GATEWAY_DEBUG, DEFAULT_MODELS_CLIENT_VERSION,
 };
 use upstream::proxy::proxy_validated_request;
 
@@ -19,7 +19,6 @@ pub(crate) fn fetch_models_for_picker() -> Result<Vec<ModelOption>, String> {
     let upstream_fallback_base = super::resolve_upstream_fallback_base_url(base);
     let path = super::normalize_models_path("/v1/models");
     let method = Method::GET;
-    let client = super::upstream_client();
     let upstream_cookie = std::env::var("GPTTOOLS_UPSTREAM_COOKIE").ok();
     candidates.sort_by_key(|(account, _)| {
         (
@@ -31,6 +30,13 @@ pub(crate) fn fetch_models_for_picker() -> Result<Vec<ModelOption>, String> {
 
     let mut last_error = "models request failed".to_string();
     for (account, mut token) in candidates {
+        let client = match super::upstream_client_for_proxy(account.proxy_url.as_deref()) {
+            Ok(client) => client,
+            Err(err) => {
+                last_error = err;
+                continue;
+            }
+        };
         match send_models_request(
             &client,
             &storage,
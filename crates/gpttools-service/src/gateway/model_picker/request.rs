@@ -1,5 +1,5 @@
 use gpttools_core::storage::{Account, Storage, Token};
-use reqwest::blocking::Client;
+use reqwest::Client;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::Method;
 
@@ -38,20 +38,22 @@ pub(super) fn send_models_request(
         builder = builder.header("ChatGPT-Account-Id", acc);
     }
 
-    let response = builder.send().map_err(|e| e.to_string())?;
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        return Err(format!("models upstream failed: status={} body={}", status, body));
-    }
-    let content_type = response
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-    if super::super::is_html_content_type(content_type) {
-        return Err("models upstream returned text/html (cloudflare challenge)".to_string());
-    }
+    super::super::block_on_upstream(async move {
+        let response = builder.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("models upstream failed: status={} body={}", status, body));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if super::super::is_html_content_type(content_type) {
+            return Err("models upstream returned text/html (cloudflare challenge)".to_string());
+        }
 
-    response.bytes().map(|v| v.to_vec()).map_err(|e| e.to_string())
+        response.bytes().await.map(|v| v.to_vec()).map_err(|e| e.to_string())
+    })
 }
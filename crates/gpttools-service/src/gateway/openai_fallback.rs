@@ -1,14 +1,15 @@
 use gpttools_core::storage::{Account, Storage, Token};
-use reqwest::blocking::Client;
+use reqwest::Client;
 use reqwest::Method;
-use tiny_http::Request;
+
+use super::IncomingRequest;
 
 pub(super) fn try_openai_fallback(
     client: &Client,
     storage: &Storage,
     method: &Method,
     request_path: &str,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_base: &str,
@@ -17,7 +18,7 @@ pub(super) fn try_openai_fallback(
     upstream_cookie: Option<&str>,
     strip_session_affinity: bool,
     debug: bool,
-) -> Result<Option<reqwest::blocking::Response>, String> {
+) -> Result<Option<reqwest::Response>, String> {
     let (url, _url_alt) = super::compute_upstream_url(upstream_base, request_path);
     let bearer = super::resolve_openai_bearer_token(storage, account, token)?;
 
@@ -51,6 +52,6 @@ pub(super) fn try_openai_fallback(
     if !body.is_empty() {
         builder = builder.body(body.to_vec());
     }
-    let resp = builder.send().map_err(|e| e.to_string())?;
+    let resp = super::block_on_upstream(builder.send()).map_err(|e| e.to_string())?;
     Ok(Some(resp))
 }
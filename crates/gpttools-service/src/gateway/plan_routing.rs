@@ -0,0 +1,152 @@
+use gpttools_core::storage::{Account, Token};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PlanRoutingRule {
+    pattern: String,
+    preferred: Vec<String>,
+    avoided: Vec<String>,
+}
+
+pub(crate) fn parse_plan_routing_rules(raw: &str) -> Vec<PlanRoutingRule> {
+    // 规则格式：`模型模式=套餐列表`，多条用 `;` 分隔；套餐前加 `!` 表示尽量避开。
+    // 例：`gpt-5-pro*=pro;*=!pro` —— pro 模型优先走 Pro 账号，其余模型尽量不占用 Pro 账号。
+    raw.split(';')
+        .filter_map(|entry| {
+            let (pattern, plans) = entry.split_once('=')?;
+            let pattern = pattern.trim().to_ascii_lowercase();
+            if pattern.is_empty() {
+                return None;
+            }
+            let mut preferred = Vec::new();
+            let mut avoided = Vec::new();
+            for plan in plans.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                match plan.strip_prefix('!') {
+                    Some(avoid) => avoided.push(avoid.trim().to_ascii_lowercase()),
+                    None => preferred.push(plan.to_ascii_lowercase()),
+                }
+            }
+            Some(PlanRoutingRule {
+                pattern,
+                preferred,
+                avoided,
+            })
+        })
+        .collect()
+}
+
+fn pattern_matches(pattern: &str, model: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model.starts_with(prefix),
+        None => model == pattern,
+    }
+}
+
+fn match_rule<'a>(rules: &'a [PlanRoutingRule], model: Option<&str>) -> Option<&'a PlanRoutingRule> {
+    let model = model
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_ascii_lowercase())
+        .unwrap_or_default();
+    rules.iter().find(|rule| pattern_matches(&rule.pattern, &model))
+}
+
+fn plan_rank(rule: &PlanRoutingRule, plan_type: Option<&str>) -> u8 {
+    let plan = plan_type.map(|v| v.trim().to_ascii_lowercase()).unwrap_or_default();
+    if rule.preferred.contains(&plan) {
+        0
+    } else if rule.avoided.contains(&plan) {
+        2
+    } else {
+        1
+    }
+}
+
+pub(crate) fn order_candidates_by_plan(
+    rules: &[PlanRoutingRule],
+    candidates: &mut [(Account, Token)],
+    model: Option<&str>,
+) {
+    let Some(rule) = match_rule(rules, model) else {
+        return;
+    };
+    // 中文注释：只做稳定排序而不剔除账号；避开的套餐仍留在末尾兜底，避免规则配置不当直接造成 503。
+    candidates.sort_by_key(|(account, _)| plan_rank(rule, account.plan_type.as_deref()));
+}
+
+pub(crate) fn configured_plan_routing_rules() -> Vec<PlanRoutingRule> {
+    std::env::var("GPTTOOLS_PLAN_ROUTING")
+        .map(|raw| parse_plan_routing_rules(&raw))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{order_candidates_by_plan, parse_plan_routing_rules};
+    use gpttools_core::storage::{now_ts, Account, Token};
+
+    fn candidate(id: &str, plan_type: Option<&str>) -> (Account, Token) {
+        (
+            Account {
+                id: id.to_string(),
+                label: id.to_string(),
+                issuer: "issuer".to_string(),
+                chatgpt_account_id: None,
+                workspace_id: None,
+                group_name: None,
+                plan_type: plan_type.map(|v| v.to_string()),
+                proxy_url: None,
+                sort: 0,
+                status: "active".to_string(),
+                created_at: now_ts(),
+                updated_at: now_ts(),
+            },
+            Token {
+                account_id: id.to_string(),
+                id_token: String::new(),
+                access_token: String::new(),
+                refresh_token: String::new(),
+                api_key_access_token: None,
+                last_refresh: now_ts(),
+            },
+        )
+    }
+
+    fn ids(candidates: &[(Account, Token)]) -> Vec<&str> {
+        candidates.iter().map(|(a, _)| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_preferred_and_avoided_plans() {
+        let rules = parse_plan_routing_rules(" GPT-5-Pro* = Pro, team ; *=!pro ;broken");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].pattern, "gpt-5-pro*");
+        assert_eq!(rules[0].preferred, vec!["pro", "team"]);
+        assert_eq!(rules[1].avoided, vec!["pro"]);
+    }
+
+    #[test]
+    fn heavy_model_prefers_pro_and_others_avoid_it() {
+        let rules = parse_plan_routing_rules("gpt-5-pro*=pro;*=!pro");
+        let mut candidates = vec![
+            candidate("plus-1", Some("plus")),
+            candidate("pro-1", Some("pro")),
+            candidate("unknown", None),
+        ];
+        order_candidates_by_plan(&rules, &mut candidates, Some("gpt-5-pro-high"));
+        assert_eq!(ids(&candidates), vec!["pro-1", "plus-1", "unknown"]);
+
+        order_candidates_by_plan(&rules, &mut candidates, Some("gpt-5.1"));
+        assert_eq!(ids(&candidates), vec!["plus-1", "unknown", "pro-1"]);
+    }
+
+    #[test]
+    fn no_matching_rule_keeps_order() {
+        let rules = parse_plan_routing_rules("o3*=pro");
+        let mut candidates = vec![candidate("a", Some("plus")), candidate("b", Some("pro"))];
+        order_candidates_by_plan(&rules, &mut candidates, Some("gpt-5.1"));
+        assert_eq!(ids(&candidates), vec!["a", "b"]);
+    }
+}
@@ -1,24 +1,25 @@
-use tiny_http::{Request, Response};
+use axum::body::Body;
+use axum::http::Response;
 
-pub(crate) fn handle_gateway_request(mut request: Request) -> Result<(), String> {
+use super::IncomingRequest;
+
+pub(crate) fn handle_gateway_request(mut request: IncomingRequest) -> Response<Body> {
     // 处理代理请求（鉴权后转发到上游）
     let debug = super::DEFAULT_GATEWAY_DEBUG;
-    if request.method().as_str() == "OPTIONS" {
-        let response = Response::empty(204);
-        let _ = request.respond(response);
-        return Ok(());
+    if request.method() == "OPTIONS" {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = axum::http::StatusCode::NO_CONTENT;
+        return response;
     }
 
     if request.url() == "/health" {
-        let response = Response::from_string("ok");
-        let _ = request.respond(response);
-        return Ok(());
+        return super::respond_text(200, "ok");
     }
 
     let _request_guard = super::begin_gateway_request();
     let trace_id = super::trace_log::next_trace_id();
     let request_path_for_log = super::normalize_models_path(request.url());
-    let request_method_for_log = request.method().as_str().to_string();
+    let request_method_for_log = request.method().to_string();
     let validated =
         match super::local_validation::prepare_local_request(&mut request, trace_id.clone(), debug)
         {
@@ -55,9 +56,7 @@ pub(crate) fn handle_gateway_request(mut request: Request) -> Result<(), String>
                     Some(err.message.as_str()),
                 );
             }
-            let response = Response::from_string(err.message).with_status_code(err.status_code);
-            let _ = request.respond(response);
-            return Ok(());
+            return super::respond_text(err.status_code, err.message);
         }
     };
 
@@ -68,8 +67,7 @@ pub(crate) fn handle_gateway_request(mut request: Request) -> Result<(), String>
     let request_method_for_count_tokens = validated.request_method.clone();
     let model_for_count_tokens = validated.model_for_log.clone();
     let reasoning_for_count_tokens = validated.reasoning_for_log.clone();
-    let request = match super::maybe_respond_local_count_tokens(
-        request,
+    if let Some(response) = super::maybe_respond_local_count_tokens(
         trace_id_for_count_tokens.as_str(),
         key_id_for_count_tokens.as_str(),
         protocol_type_for_count_tokens.as_str(),
@@ -79,10 +77,9 @@ pub(crate) fn handle_gateway_request(mut request: Request) -> Result<(), String>
         model_for_count_tokens.as_deref(),
         reasoning_for_count_tokens.as_deref(),
         &validated.storage,
-    )? {
-        Some(request) => request,
-        None => return Ok(()),
-    };
+    ) {
+        return response;
+    }
 
     super::proxy_validated_request(request, validated, debug)
 }
@@ -1,4 +1,20 @@
 use gpttools_core::storage::{now_ts, RequestLog, Storage};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const REQUEST_LOG_BATCH_MAX: usize = 128;
+const REQUEST_LOG_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+const REQUEST_LOG_FLUSH_WAIT: Duration = Duration::from_secs(2);
+
+enum WriterCommand {
+    Write { db_path: String, log: RequestLog },
+    Flush(Sender<()>),
+}
+
+static REQUEST_LOG_WRITER: OnceLock<Mutex<Sender<WriterCommand>>> = OnceLock::new();
 
 pub(super) fn write_request_log(
     storage: &Storage,
@@ -12,7 +28,7 @@ pub(super) fn write_request_log(
     error: Option<&str>,
 ) {
     // 记录请求最终结果（而非内部重试明细），保证 UI 一次请求只展示一条记录。
-    let _ = storage.insert_request_log(&RequestLog {
+    let log = RequestLog {
         key_id: key_id.map(|v| v.to_string()),
         request_path: request_path.to_string(),
         method: method.to_string(),
@@ -22,5 +38,231 @@ pub(super) fn write_request_log(
         status_code: status_code.map(|v| i64::from(v)),
         error: error.map(|v| v.to_string()),
         created_at: now_ts(),
+    };
+    // 中文注释：请求日志交给后台写线程批量提交，网关线程不再逐条抢 SQLite 写锁；内存库没有路径可重开，只能同步写。
+    let Some(db_path) = storage.path() else {
+        let _ = storage.insert_request_log(&log);
+        return;
+    };
+    if let Some(log) = enqueue_request_log(db_path.to_string(), log) {
+        let _ = storage.insert_request_log(&log);
+    }
+}
+
+/// 已拿到上游响应头、响应体还没转发完的请求的最终日志。
+///
+/// 响应体读完、上游出错或流被中途丢弃时才落库，记录的是请求实际的结束状态。
+pub(crate) struct FinalRequestLog {
+    db_path: Option<String>,
+    log: RequestLog,
+    trace_id: String,
+    account_id: String,
+    started_at: Instant,
+    finished: bool,
+}
+
+impl FinalRequestLog {
+    pub(super) fn new(
+        storage: &Storage,
+        log: RequestLog,
+        trace_id: &str,
+        account_id: &str,
+        started_at: Instant,
+    ) -> Self {
+        // 中文注释：内存库没有路径可在流结束时重开，只能在响应头阶段同步写；结束状态仍会记到 trace 日志。
+        let db_path = storage.path().map(str::to_string);
+        if db_path.is_none() {
+            let _ = storage.insert_request_log(&log);
+        }
+        Self {
+            db_path,
+            log,
+            trace_id: trace_id.to_string(),
+            account_id: account_id.to_string(),
+            started_at,
+            finished: false,
+        }
+    }
+
+    fn status_code(&self) -> u16 {
+        self.log
+            .status_code
+            .and_then(|status| u16::try_from(status).ok())
+            .unwrap_or(0)
+    }
+
+    /// 响应体完整转发给了客户端。
+    pub(crate) fn complete(mut self) {
+        self.write(None, None);
+    }
+
+    /// 响应体转发中途失败，客户端已收到的状态码不变。
+    pub(crate) fn fail(mut self, error: &str) {
+        self.write(None, Some(error));
+    }
+
+    /// 读取或转换上游响应体失败，改为返回给客户端的错误状态。
+    pub(crate) fn fail_with_status(mut self, status_code: u16, error: &str) {
+        self.write(Some(status_code), Some(error));
+    }
+
+    fn write(&mut self, status_code: Option<u16>, error: Option<&str>) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if let Some(status_code) = status_code {
+            self.log.status_code = Some(i64::from(status_code));
+        }
+        if let Some(error) = error {
+            self.log.error = Some(error.to_string());
+        }
+        self.log.created_at = now_ts();
+        super::trace_log::log_request_final(
+            &self.trace_id,
+            self.status_code(),
+            Some(&self.account_id),
+            self.log.upstream_url.as_deref(),
+            self.log.error.as_deref(),
+            self.started_at.elapsed().as_millis(),
+        );
+        let Some(db_path) = self.db_path.clone() else {
+            return;
+        };
+        let log = self.log.clone();
+        if let Some(log) = enqueue_request_log(db_path.clone(), log) {
+            insert_request_log_off_runtime(db_path, log);
+        }
+    }
+}
+
+fn insert_request_log_off_runtime(db_path: String, log: RequestLog) {
+    // 写线程不可用时的兜底同步写
+    let insert = move || {
+        if let Ok(storage) = Storage::open(&db_path) {
+            let _ = storage.insert_request_log(&log);
+        }
+    };
+    // 中文注释：流被丢弃时 Drop 跑在 tokio 工作线程上，直接开库写入会卡住同一线程上的其它连接。
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn_blocking(insert);
+        }
+        Err(_) => insert(),
+    }
+}
+
+impl Drop for FinalRequestLog {
+    fn drop(&mut self) {
+        // 中文注释：响应体没读到结尾就被丢弃，说明客户端断开或停服期限到了被强制断开；不在这里补记会让这类请求永远显示为成功。
+        if self.finished {
+            return;
+        }
+        let reason = if crate::http::drain::drain_deadline_exceeded() {
+            "stream interrupted by service shutdown"
+        } else {
+            "client disconnected before response completed"
+        };
+        self.write(None, Some(reason));
+    }
+}
+
+pub(crate) fn flush_request_logs() {
+    // 等待已排队的请求日志落库，供查询/清空前调用以保证读到最新结果
+    let Some(writer) = REQUEST_LOG_WRITER.get() else {
+        return;
+    };
+    let (done_tx, done_rx) = mpsc::channel();
+    let sent = writer
+        .lock()
+        .map(|sender| sender.send(WriterCommand::Flush(done_tx)).is_ok())
+        .unwrap_or(false);
+    if sent {
+        let _ = done_rx.recv_timeout(REQUEST_LOG_FLUSH_WAIT);
+    }
+}
+
+fn enqueue_request_log(db_path: String, log: RequestLog) -> Option<RequestLog> {
+    // 投递到后台写线程；写线程不可用时把日志原样交还给调用方同步写入
+    let writer = REQUEST_LOG_WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        let _ = thread::Builder::new()
+            .name("gpttools-request-log-writer".to_string())
+            .spawn(move || run_request_log_writer(rx));
+        Mutex::new(tx)
     });
+    let command = WriterCommand::Write { db_path, log };
+    let result = match writer.lock() {
+        Ok(sender) => sender.send(command),
+        Err(_) => Err(mpsc::SendError(command)),
+    };
+    match result {
+        Err(mpsc::SendError(WriterCommand::Write { log, .. })) => Some(log),
+        _ => None,
+    }
+}
+
+fn run_request_log_writer(rx: Receiver<WriterCommand>) {
+    let mut connections: HashMap<String, Storage> = HashMap::new();
+    while let Ok(first) = rx.recv() {
+        let mut pending: HashMap<String, Vec<RequestLog>> = HashMap::new();
+        let mut waiters = Vec::new();
+        let mut queued = 0;
+        let deadline = Instant::now() + REQUEST_LOG_FLUSH_INTERVAL;
+        let mut next = Some(first);
+        loop {
+            match next.take() {
+                Some(WriterCommand::Write { db_path, log }) => {
+                    pending.entry(db_path).or_default().push(log);
+                    queued += 1;
+                }
+                // 中文注释：收到 flush 立即提交当前批次，不再等攒满；否则查询接口要白等一个刷新周期。
+                Some(WriterCommand::Flush(done)) => {
+                    waiters.push(done);
+                    break;
+                }
+                None => {}
+            }
+            if queued >= REQUEST_LOG_BATCH_MAX {
+                break;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match rx.recv_timeout(deadline - now) {
+                Ok(command) => next = Some(command),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        for (db_path, logs) in pending {
+            write_batch(&mut connections, &db_path, &logs);
+        }
+        for done in waiters {
+            let _ = done.send(());
+        }
+    }
+}
+
+fn write_batch(connections: &mut HashMap<String, Storage>, db_path: &str, logs: &[RequestLog]) {
+    if !connections.contains_key(db_path) {
+        match Storage::open(db_path) {
+            Ok(storage) => {
+                connections.insert(db_path.to_string(), storage);
+            }
+            Err(err) => {
+                log::warn!("request log writer open failed: {} ({})", db_path, err);
+                return;
+            }
+        }
+    }
+    let Some(storage) = connections.get_mut(db_path) else {
+        return;
+    };
+    if let Err(err) = storage.insert_request_logs(logs) {
+        log::warn!("request log batch write failed: {} logs ({})", logs.len(), err);
+        // 中文注释：连接可能已指向被替换的库文件，丢弃后下一批会重新打开。
+        connections.remove(db_path);
+    }
 }
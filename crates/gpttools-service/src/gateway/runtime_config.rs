@@ -1,29 +1,82 @@
-use reqwest::blocking::Client;
-use std::sync::OnceLock;
+use reqwest::{Client, ClientBuilder};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 static UPSTREAM_CLIENT: OnceLock<Client> = OnceLock::new();
+static PROXIED_UPSTREAM_CLIENTS: OnceLock<Mutex<HashMap<String, Client>>> = OnceLock::new();
+static UPSTREAM_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
 
 pub(crate) const DEFAULT_MODELS_CLIENT_VERSION: &str = "0.98.0";
 pub(crate) const DEFAULT_GATEWAY_DEBUG: bool = false;
 const DEFAULT_UPSTREAM_CONNECT_TIMEOUT_SECS: u64 = 15;
 const DEFAULT_ACCOUNT_MAX_INFLIGHT: usize = 0;
 
+fn upstream_client_builder() -> ClientBuilder {
+    // 中文注释：异步客户端默认没有总超时，长时流式响应不会在客户端层被误判超时中断；不要在这里加 timeout。
+    Client::builder()
+        // 中文注释：连接阶段设置超时，避免网络异常时线程长期卡死占满并发槽位。
+        .connect_timeout(upstream_connect_timeout())
+        .pool_max_idle_per_host(32)
+        .pool_idle_timeout(Some(Duration::from_secs(90)))
+        .tcp_keepalive(Some(Duration::from_secs(30)))
+}
+
 pub(crate) fn upstream_client() -> &'static Client {
     UPSTREAM_CLIENT.get_or_init(|| {
-        Client::builder()
-            // 中文注释：显式关闭总超时，避免长时流式响应在客户端层被误判超时中断。
-            .timeout(None::<Duration>)
-            // 中文注释：连接阶段设置超时，避免网络异常时线程长期卡死占满并发槽位。
-            .connect_timeout(upstream_connect_timeout())
-            .pool_max_idle_per_host(32)
-            .pool_idle_timeout(Some(Duration::from_secs(90)))
-            .tcp_keepalive(Some(Duration::from_secs(30)))
+        upstream_client_builder()
             .build()
             .unwrap_or_else(|_| Client::new())
     })
 }
 
+pub(crate) fn upstream_client_for_proxy(proxy_url: Option<&str>) -> Result<Client, String> {
+    // 按代理地址缓存上游客户端，同一代理的账号共享连接池
+    let Some(proxy_url) = proxy_url.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(upstream_client().clone());
+    };
+    let cache = PROXIED_UPSTREAM_CLIENTS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut clients = cache
+        .lock()
+        .map_err(|_| "upstream proxy client lock poisoned".to_string())?;
+    if let Some(client) = clients.get(proxy_url) {
+        return Ok(client.clone());
+    }
+    let proxy =
+        reqwest::Proxy::all(proxy_url).map_err(|err| format!("invalid account proxy: {err}"))?;
+    let client = upstream_client_builder()
+        .proxy(proxy)
+        .build()
+        .map_err(|err| format!("build proxied client failed: {err}"))?;
+    clients.insert(proxy_url.to_string(), client.clone());
+    Ok(client)
+}
+
+pub(crate) fn evict_upstream_client_for_proxy(proxy_url: &str) {
+    // 丢掉某个代理地址缓存的上游客户端及其连接池
+    if let Some(cache) = PROXIED_UPSTREAM_CLIENTS.get() {
+        if let Ok(mut clients) = cache.lock() {
+            clients.remove(proxy_url.trim());
+        }
+    }
+}
+
+pub(crate) fn block_on_upstream<F: Future>(future: F) -> F::Output {
+    // 中文注释：网关选号/重试阶段跑在 spawn_blocking 线程上（SQLite 与刷新逻辑都是同步的），这里借上游专用运行时驱动异步请求；
+    // 响应头一到就把 reqwest::Response 交回 axum 异步推流，长连接不再占住阻塞线程。
+    // 连接池里的连接任务绑定在创建它的运行时上，固定用一个进程级运行时，避免单次服务运行时退出后池里留下失效连接。
+    UPSTREAM_RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .thread_name("gpttools-upstream")
+                .enable_all()
+                .build()
+                .expect("build upstream runtime")
+        })
+        .block_on(future)
+}
+
 fn upstream_connect_timeout() -> Duration {
     Duration::from_secs(DEFAULT_UPSTREAM_CONNECT_TIMEOUT_SECS)
 }
@@ -1,10 +1,22 @@
-use gpttools_core::storage::{Account, Storage, Token, UsageSnapshotRecord};
+use gpttools_core::storage::{pool_generation, Account, Storage, Token, UsageSnapshotRecord};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-use crate::account_availability::is_available;
+use crate::account_availability::{evaluate_snapshot_with, UsageThresholds};
 
 static CANDIDATE_CURSOR: AtomicUsize = AtomicUsize::new(0);
+static CANDIDATE_CACHE: OnceLock<Mutex<Option<CandidateCache>>> = OnceLock::new();
+// 中文注释：写入代数只覆盖本进程的写入；TTL 兜底外部进程改库、重置窗口到点等不经过 Storage 写入的变化。
+const CANDIDATE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+struct CandidateCache {
+    db_path: String,
+    generation: u64,
+    loaded_at: Instant,
+    candidates: Vec<(Account, Token)>,
+}
 
 pub(crate) fn rotate_candidates_for_fairness(candidates: &mut Vec<(Account, Token)>) {
     if candidates.len() <= 1 {
@@ -19,6 +31,35 @@ pub(crate) fn rotate_candidates_for_fairness(candidates: &mut Vec<(Account, Toke
 }
 
 pub(crate) fn collect_gateway_candidates(storage: &Storage) -> Result<Vec<(Account, Token)>, String> {
+    // 优先复用候选缓存；账号、token 或用量快照有写入时重新加载
+    let Some(db_path) = storage.path() else {
+        return load_gateway_candidates(storage);
+    };
+    // 中文注释：代数必须在加载前读取；加载期间发生的写入会让下次请求看到更新的代数而重新加载，不会把旧结果当新结果缓存。
+    let generation = pool_generation();
+    let cache = CANDIDATE_CACHE.get_or_init(|| Mutex::new(None));
+    if let Ok(guard) = cache.lock() {
+        if let Some(cached) = guard.as_ref().filter(|cached| {
+            cached.db_path == db_path
+                && cached.generation == generation
+                && cached.loaded_at.elapsed() < CANDIDATE_CACHE_TTL
+        }) {
+            return Ok(cached.candidates.clone());
+        }
+    }
+    let candidates = load_gateway_candidates(storage)?;
+    if let Ok(mut guard) = cache.lock() {
+        *guard = Some(CandidateCache {
+            db_path: db_path.to_string(),
+            generation,
+            loaded_at: Instant::now(),
+            candidates: candidates.clone(),
+        });
+    }
+    Ok(candidates)
+}
+
+fn load_gateway_candidates(storage: &Storage) -> Result<Vec<(Account, Token)>, String> {
     // 选择可用账号作为网关上游候选
     let accounts = storage.list_accounts().map_err(|e| e.to_string())?;
     let tokens = storage.list_tokens().map_err(|e| e.to_string())?;
@@ -34,7 +75,10 @@ pub(crate) fn collect_gateway_candidates(storage: &Storage) -> Result<Vec<(Accou
         snap_map.insert(snap.account_id.clone(), snap);
     }
 
+    let thresholds = UsageThresholds::from_env();
+    let reserve_groups = configured_reserve_groups();
     let mut out = Vec::new();
+    let mut reserve = Vec::new();
     for account in &accounts {
         if account.status != "active" {
             continue;
@@ -43,11 +87,23 @@ pub(crate) fn collect_gateway_candidates(storage: &Storage) -> Result<Vec<(Accou
             Some(token) => token.clone(),
             None => continue,
         };
-        let usage = snap_map.get(&account.id);
-        if !is_available(usage) {
+        let routable = snap_map
+            .get(&account.id)
+            .map(|usage| evaluate_snapshot_with(usage, &thresholds).is_routable())
+            .unwrap_or(true);
+        if !routable {
             continue;
         }
-        out.push((account.clone(), token));
+        if is_reserve_account(account, &reserve_groups) {
+            reserve.push((account.clone(), token));
+        } else {
+            out.push((account.clone(), token));
+        }
+    }
+    if out.is_empty() && !reserve.is_empty() {
+        // 中文注释：预留账号只在常规账号全部耗尽/超阈值后才启用，保证关键时刻还有未动用的额度。
+        log::info!("gateway reserve engaged: {} reserve candidates", reserve.len());
+        out = reserve;
     }
     if out.is_empty() {
         let mut fallback = Vec::new();
@@ -73,6 +129,33 @@ pub(crate) fn collect_gateway_candidates(storage: &Storage) -> Result<Vec<(Accou
     Ok(out)
 }
 
+fn configured_reserve_groups() -> Vec<String> {
+    std::env::var("GPTTOOLS_RESERVE_GROUPS")
+        .map(|raw| parse_reserve_groups(&raw))
+        .unwrap_or_default()
+}
+
+fn parse_reserve_groups(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_ascii_lowercase())
+        .collect()
+}
+
+fn is_reserve_account(account: &Account, reserve_groups: &[String]) -> bool {
+    let Some(group) = account
+        .group_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    else {
+        return false;
+    };
+    let group = group.to_ascii_lowercase();
+    reserve_groups.contains(&group)
+}
+
 fn fallback_allowed(usage: Option<&UsageSnapshotRecord>) -> bool {
     if let Some(record) = usage {
         if let Some(value) = record.used_percent {
@@ -116,3 +199,103 @@ fn log_no_candidates(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_gateway_candidates, is_reserve_account, parse_reserve_groups};
+    use gpttools_core::storage::{now_ts, Account, Storage, Token, UsageSnapshotRecord};
+
+    fn account(id: &str, group_name: Option<&str>) -> Account {
+        Account {
+            id: id.to_string(),
+            label: id.to_string(),
+            issuer: "issuer".to_string(),
+            chatgpt_account_id: None,
+            workspace_id: None,
+            group_name: group_name.map(|v| v.to_string()),
+            plan_type: None,
+            proxy_url: None,
+            sort: 0,
+            status: "active".to_string(),
+            created_at: now_ts(),
+            updated_at: now_ts(),
+        }
+    }
+
+    fn insert_candidate(storage: &Storage, id: &str, group_name: Option<&str>, used: Option<f64>) {
+        storage.insert_account(&account(id, group_name)).expect("insert account");
+        storage
+            .insert_token(&Token {
+                account_id: id.to_string(),
+                id_token: String::new(),
+                access_token: "access".to_string(),
+                refresh_token: String::new(),
+                api_key_access_token: None,
+                last_refresh: now_ts(),
+            })
+            .expect("insert token");
+        storage
+            .insert_usage_snapshot(&UsageSnapshotRecord {
+                account_id: id.to_string(),
+                used_percent: used,
+                window_minutes: used.map(|_| 300),
+                resets_at: None,
+                secondary_used_percent: used.map(|_| 0.0),
+                secondary_window_minutes: used.map(|_| 10080),
+                secondary_resets_at: None,
+                credits_json: None,
+                captured_at: now_ts(),
+            })
+            .expect("insert usage");
+    }
+
+    #[test]
+    fn reserve_groups_match_case_insensitively() {
+        let groups = parse_reserve_groups(" Reserve , ,backup");
+        assert_eq!(groups, vec!["reserve", "backup"]);
+        assert!(is_reserve_account(&account("a", Some("RESERVE")), &groups));
+        assert!(!is_reserve_account(&account("b", Some("main")), &groups));
+        assert!(!is_reserve_account(&account("c", None), &groups));
+    }
+
+    #[test]
+    fn unknown_usage_stays_routable_and_exhausted_is_skipped() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        insert_candidate(&storage, "unknown", None, None);
+        insert_candidate(&storage, "exhausted", None, Some(100.0));
+        insert_candidate(&storage, "ok", None, Some(10.0));
+
+        let mut ids: Vec<String> = collect_gateway_candidates(&storage)
+            .expect("collect")
+            .into_iter()
+            .map(|(account, _)| account.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["ok", "unknown"]);
+    }
+
+    #[test]
+    fn candidate_cache_reloads_after_pool_writes() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("gpttools-candidate-cache-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let db_path = dir.join("gpttools.db");
+        let _ = std::fs::remove_file(&db_path);
+        let storage = Storage::open(&db_path).expect("open");
+        storage.init().expect("init");
+        insert_candidate(&storage, "first", None, Some(10.0));
+
+        let first = collect_gateway_candidates(&storage).expect("collect");
+        assert_eq!(first.len(), 1);
+        insert_candidate(&storage, "second", None, Some(10.0));
+        let second = collect_gateway_candidates(&storage).expect("collect again");
+        assert_eq!(second.len(), 2);
+        storage
+            .update_account_status("first", "inactive")
+            .expect("deactivate");
+        let third = collect_gateway_candidates(&storage).expect("collect after status change");
+        assert_eq!(third.len(), 1);
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
@@ -4,40 +4,43 @@ use std::sync::{Arc, Mutex, OnceLock};
 use gpttools_core::auth::{DEFAULT_CLIENT_ID, DEFAULT_ISSUER};
 use gpttools_core::storage::{Account, Storage, Token};
 
+use crate::account_proxy::account_proxy_url;
 use crate::auth_tokens;
+use crate::storage_helpers::token_group_key;
 use crate::usage_http::refresh_access_token;
 
 static ACCOUNT_TOKEN_EXCHANGE_LOCKS: OnceLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> =
     OnceLock::new();
 
-pub(super) fn account_token_exchange_lock(account_id: &str) -> Arc<Mutex<()>> {
+pub(crate) fn account_token_exchange_lock(account_id: &str) -> Arc<Mutex<()>> {
     let lock = ACCOUNT_TOKEN_EXCHANGE_LOCKS.get_or_init(|| Mutex::new(HashMap::new()));
     let Ok(mut map) = lock.lock() else {
         return Arc::new(Mutex::new(()));
     };
-    map.entry(account_id.to_string())
+    // 中文注释：多 workspace 条目共享同一套 refresh_token，锁必须按身份而不是条目加；否则兄弟条目并发刷新会触发 reused。
+    map.entry(token_group_key(account_id).to_string())
         .or_insert_with(|| Arc::new(Mutex::new(())))
         .clone()
 }
 
 fn find_cached_api_key_access_token(storage: &Storage, account_id: &str) -> Option<String> {
     storage
-        .list_tokens()
-        .ok()?
-        .into_iter()
-        .find(|t| t.account_id == account_id)
-        .and_then(|t| t.api_key_access_token)
+        .find_token(account_id)
+        .ok()??
+        .api_key_access_token
         .map(|v| v.trim().to_string())
         .filter(|v| !v.is_empty())
 }
 
-fn exchange_and_persist_api_key_access_token(
+pub(crate) fn exchange_and_persist_api_key_access_token(
     storage: &Storage,
     token: &mut Token,
     issuer: &str,
     client_id: &str,
 ) -> Result<String, String> {
-    let exchanged = auth_tokens::obtain_api_key(issuer, client_id, &token.id_token)?;
+    let proxy_url = account_proxy_url(storage, &token.account_id);
+    let exchanged =
+        auth_tokens::obtain_api_key(issuer, client_id, &token.id_token, proxy_url.as_deref())?;
     token.api_key_access_token = Some(exchanged.clone());
     let _ = storage.insert_token(token);
     Ok(exchanged)
@@ -102,8 +105,14 @@ pub(super) fn resolve_openai_bearer_token(
         Ok(token) => return Ok(token),
         Err(exchange_err) => {
             if !token.refresh_token.trim().is_empty() {
-                match refresh_access_token(&issuer, &client_id, &token.refresh_token) {
+                match refresh_access_token(
+                    &issuer,
+                    &client_id,
+                    &token.refresh_token,
+                    account.proxy_url.as_deref(),
+                ) {
                     Ok(refreshed) => {
+                        let previous_refresh_token = token.refresh_token.clone();
                         token.access_token = refreshed.access_token;
                         if let Some(refresh_token) = refreshed.refresh_token {
                             token.refresh_token = refresh_token;
@@ -112,6 +121,7 @@ pub(super) fn resolve_openai_bearer_token(
                             token.id_token = id_token;
                         }
                         let _ = storage.insert_token(token);
+                        let _ = storage.propagate_refreshed_token(token, &previous_refresh_token);
 
                         if !token.id_token.trim().is_empty() {
                             if let Ok(exchanged) = exchange_and_persist_api_key_access_token(
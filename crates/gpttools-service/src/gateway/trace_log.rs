@@ -1,3 +1,4 @@
+use gpttools_core::redact::redact_secrets;
 use gpttools_core::storage::now_ts;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -33,6 +34,8 @@ fn short_fingerprint(value: &str) -> String {
 }
 
 fn append_trace_line(line: &str) {
+    // 中文注释：所有 trace 行落盘前统一脱敏；只在个别调用点处理会漏掉上游错误体里回显的凭据。
+    let line = redact_secrets(line);
     let lock = TRACE_FILE_LOCK.get_or_init(|| Mutex::new(()));
     let Ok(_guard) = lock.lock() else {
         return;
@@ -105,8 +108,10 @@ pub(crate) fn log_request_body_preview(trace_id: &str, body: &[u8]) {
         .chars()
         .filter(|ch| *ch != '\r' && *ch != '\n' && *ch != '\t')
         .collect::<String>();
-    let preview = if compact.len() > 900 {
-        format!("{}...", &compact[..900])
+    // 先脱敏再截断，避免截断点落在凭据中间导致键值无法识别
+    let compact = redact_secrets(&compact);
+    let preview = if compact.chars().count() > 900 {
+        format!("{}...", compact.chars().take(900).collect::<String>())
     } else {
         compact
     };
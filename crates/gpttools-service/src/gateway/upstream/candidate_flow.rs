@@ -1,22 +1,22 @@
 use gpttools_core::storage::{Account, Storage, Token};
-use tiny_http::Request;
 
+use super::super::IncomingRequest;
 use super::openai_base::{handle_openai_base_attempt, OpenAiAttemptResult};
 use super::postprocess::{process_upstream_post_retry_flow, PostRetryFlowDecision};
 use super::primary_flow::{run_primary_upstream_flow, PrimaryFlowDecision};
 
 pub(super) enum CandidateUpstreamDecision {
-    RespondUpstream(reqwest::blocking::Response),
+    RespondUpstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn process_candidate_upstream_flow<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     storage: &Storage,
     method: &reqwest::Method,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     base: &str,
@@ -8,6 +8,7 @@ pub(crate) enum CandidateSkipReason {
 
 pub(crate) fn prepare_gateway_candidates(
     storage: &Storage,
+    model: Option<&str>,
 ) -> Result<Vec<(Account, Token)>, String> {
     let mut candidates = super::super::collect_gateway_candidates(storage)?;
     // 中文注释：先避开冷却中的账号，再按并发负载排序，减少并发时反复命中不稳定账号。
@@ -18,6 +19,9 @@ pub(crate) fn prepare_gateway_candidates(
         )
     });
     super::super::rotate_candidates_for_fairness(&mut candidates);
+    // 中文注释：套餐偏好在轮转之后做稳定排序，同一档内仍保持轮转后的公平顺序。
+    let plan_rules = super::super::configured_plan_routing_rules();
+    super::super::order_candidates_by_plan(&plan_rules, &mut candidates, model);
     Ok(candidates)
 }
 
@@ -1,4 +1,5 @@
-use gpttools_core::storage::Storage;
+use gpttools_core::storage::{now_ts, RequestLog, Storage};
+use std::time::Instant;
 
 pub(super) struct GatewayUpstreamExecutionContext<'a> {
     trace_id: &'a str,
@@ -134,6 +135,35 @@ impl<'a> GatewayUpstreamExecutionContext<'a> {
         );
     }
 
+    /// 上游已返回响应头时备好最终日志，等响应体转发结束再落库。
+    pub(super) fn begin_final_result(
+        &self,
+        final_account_id: &str,
+        upstream_url: Option<&str>,
+        status_code: u16,
+        error: Option<&str>,
+        started_at: Instant,
+    ) -> super::super::FinalRequestLog {
+        let log = RequestLog {
+            key_id: Some(self.key_id.to_string()),
+            request_path: self.path.to_string(),
+            method: self.request_method.to_string(),
+            model: self.model_for_log.map(str::to_string),
+            reasoning_effort: self.reasoning_for_log.map(str::to_string),
+            upstream_url: upstream_url.map(str::to_string),
+            status_code: Some(i64::from(status_code)),
+            error: error.map(str::to_string),
+            created_at: now_ts(),
+        };
+        super::super::FinalRequestLog::new(
+            self.storage,
+            log,
+            self.trace_id,
+            final_account_id,
+            started_at,
+        )
+    }
+
     pub(super) fn remember_success_account(&self, account_id: &str) {
         super::super::remember_success_route_account(
             self.key_id,
@@ -1,19 +1,21 @@
 use gpttools_core::storage::{Account, Storage, Token};
 use reqwest::header::HeaderValue;
 
+use super::super::IncomingRequest;
+
 pub(super) enum FallbackBranchResult {
     NotTriggered,
-    RespondUpstream(reqwest::blocking::Response),
+    RespondUpstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn handle_openai_fallback_branch<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     storage: &Storage,
     method: &reqwest::Method,
-    request: &tiny_http::Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_base: &str,
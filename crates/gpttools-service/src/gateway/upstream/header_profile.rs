@@ -1,6 +1,7 @@
 use rand::RngCore;
 use sha2::{Digest, Sha256};
-use tiny_http::Request;
+
+use super::super::IncomingRequest;
 
 pub(super) const CODEX_CLIENT_VERSION: &str = "0.101.0";
 pub(super) const CODEX_USER_AGENT: &str =
@@ -22,20 +23,15 @@ pub(crate) struct CodexUpstreamHeaderInput<'a> {
     pub(crate) has_body: bool,
 }
 
-pub(crate) fn find_incoming_header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
-    request
-        .headers()
-        .iter()
-        .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
-        .map(|header| header.value.as_str().trim())
-        .filter(|value| !value.is_empty())
+pub(crate) fn find_incoming_header<'a>(request: &'a IncomingRequest, name: &str) -> Option<&'a str> {
+    request.header(name)
 }
 
-pub(crate) fn derive_sticky_session_id(request: &Request) -> Option<String> {
+pub(crate) fn derive_sticky_session_id(request: &IncomingRequest) -> Option<String> {
     derive_sticky_id_from_request(request, "session")
 }
 
-pub(crate) fn derive_sticky_conversation_id(request: &Request) -> Option<String> {
+pub(crate) fn derive_sticky_conversation_id(request: &IncomingRequest) -> Option<String> {
     derive_sticky_id_from_request(request, "conversation")
 }
 
@@ -133,7 +129,7 @@ fn stable_session_id_from_material(value: &str) -> String {
     )
 }
 
-fn derive_sticky_id_from_request(request: &Request, salt: &str) -> Option<String> {
+fn derive_sticky_id_from_request(request: &IncomingRequest, salt: &str) -> Option<String> {
     let key_material = find_incoming_header(request, "x-api-key")
         .or_else(|| {
             find_incoming_header(request, "authorization").and_then(|value| {
@@ -13,6 +13,7 @@ pub(super) mod primary_flow;
 pub(super) mod proxy;
 pub(super) mod retry;
 pub(super) mod stateless_retry;
+pub(super) mod stream_prefetch;
 pub(super) mod transport;
 
 
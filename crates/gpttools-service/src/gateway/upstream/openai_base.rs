@@ -1,17 +1,19 @@
 use gpttools_core::storage::{Account, Storage, Token};
 
+use super::super::IncomingRequest;
+
 pub(super) enum OpenAiAttemptResult {
-    Upstream(reqwest::blocking::Response),
+    Upstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 pub(super) fn handle_openai_base_attempt<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     storage: &Storage,
     method: &reqwest::Method,
     path: &str,
-    request: &tiny_http::Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     base: &str,
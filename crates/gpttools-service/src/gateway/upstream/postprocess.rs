@@ -1,6 +1,6 @@
 use gpttools_core::storage::{Account, Storage};
-use tiny_http::Request;
 
+use super::super::IncomingRequest;
 use super::outcome::{decide_upstream_outcome, UpstreamOutcomeDecision};
 use super::retry::{retry_with_alternate_path, AltPathRetryResult};
 use super::stateless_retry::{
@@ -10,17 +10,17 @@ use super::stateless_retry::{
 pub(super) enum PostRetryFlowDecision {
     Failover,
     Terminal { status_code: u16, message: String },
-    RespondUpstream(reqwest::blocking::Response),
+    RespondUpstream(reqwest::Response),
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn process_upstream_post_retry_flow<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     storage: &Storage,
     method: &reqwest::Method,
     url: &str,
     url_alt: Option<&str>,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_cookie: Option<&str>,
@@ -30,7 +30,7 @@ pub(super) fn process_upstream_post_retry_flow<F>(
     debug: bool,
     disable_challenge_stateless_retry: bool,
     has_more_candidates: bool,
-    mut upstream: reqwest::blocking::Response,
+    mut upstream: reqwest::Response,
     mut log_gateway_result: F,
 ) -> PostRetryFlowDecision
 where
@@ -1,17 +1,14 @@
 use gpttools_core::storage::{Account, Storage, Token};
-use tiny_http::{Request, Response};
+use axum::body::Body;
+use axum::http::Response;
 
 pub(super) enum CandidatePrecheckResult {
-    Ready {
-        request: Request,
-        candidates: Vec<(Account, Token)>,
-    },
-    Responded,
+    Ready { candidates: Vec<(Account, Token)> },
+    Responded(Response<Body>),
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn prepare_candidates_for_proxy(
-    request: Request,
     storage: &Storage,
     trace_id: &str,
     key_id: &str,
@@ -20,7 +17,7 @@ pub(super) fn prepare_candidates_for_proxy(
     model_for_log: Option<&str>,
     reasoning_for_log: Option<&str>,
 ) -> CandidatePrecheckResult {
-    let candidates = match super::super::prepare_gateway_candidates(storage) {
+    let candidates = match super::super::prepare_gateway_candidates(storage, model_for_log) {
         Ok(v) => v,
         Err(err) => {
             let err_text = format!("candidate resolve failed: {err}");
@@ -35,8 +32,6 @@ pub(super) fn prepare_candidates_for_proxy(
                 Some(500),
                 Some(err_text.as_str()),
             );
-            let response = Response::from_string(err_text.clone()).with_status_code(500);
-            let _ = request.respond(response);
             super::super::trace_log::log_request_final(
                 trace_id,
                 500,
@@ -45,7 +40,7 @@ pub(super) fn prepare_candidates_for_proxy(
                 Some(err_text.as_str()),
                 0,
             );
-            return CandidatePrecheckResult::Responded;
+            return CandidatePrecheckResult::Responded(super::super::respond_text(500, err_text));
         }
     };
 
@@ -61,8 +56,6 @@ pub(super) fn prepare_candidates_for_proxy(
             Some(503),
             Some("no available account"),
         );
-        let response = Response::from_string("no available account").with_status_code(503);
-        let _ = request.respond(response);
         super::super::trace_log::log_request_final(
             trace_id,
             503,
@@ -71,10 +64,13 @@ pub(super) fn prepare_candidates_for_proxy(
             Some("no available account"),
             0,
         );
-        return CandidatePrecheckResult::Responded;
+        return CandidatePrecheckResult::Responded(super::super::respond_text(
+            503,
+            "no available account",
+        ));
     }
 
-    CandidatePrecheckResult::Ready { request, candidates }
+    CandidatePrecheckResult::Ready { candidates }
 }
 
 
@@ -1,17 +1,19 @@
 use gpttools_core::storage::Account;
 
+use super::super::IncomingRequest;
+
 pub(super) enum PrimaryAttemptResult {
-    Upstream(reqwest::blocking::Response),
+    Upstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn run_primary_upstream_attempt<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     method: &reqwest::Method,
     url: &str,
-    request: &tiny_http::Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_cookie: Option<&str>,
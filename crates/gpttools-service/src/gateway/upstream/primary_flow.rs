@@ -1,26 +1,26 @@
 use gpttools_core::storage::{Account, Storage, Token};
 use reqwest::header::CONTENT_TYPE;
-use tiny_http::Request;
 
+use super::super::IncomingRequest;
 use super::fallback_branch::{handle_openai_fallback_branch, FallbackBranchResult};
 use super::primary_attempt::{run_primary_upstream_attempt, PrimaryAttemptResult};
 
 pub(super) enum PrimaryFlowDecision {
     Continue {
-        upstream: reqwest::blocking::Response,
+        upstream: reqwest::Response,
         auth_token: String,
     },
-    RespondUpstream(reqwest::blocking::Response),
+    RespondUpstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn run_primary_upstream_flow<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     storage: &Storage,
     method: &reqwest::Method,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     base: &str,
@@ -1,17 +1,15 @@
 use crate::apikey_profile::PROTOCOL_ANTHROPIC_NATIVE;
+use axum::body::Body;
+use axum::http::Response;
 use std::time::Instant;
-use tiny_http::{Request, Response};
 
 use super::super::local_validation::LocalValidationResult;
+use super::super::IncomingRequest;
 use super::candidate_flow::{process_candidate_upstream_flow, CandidateUpstreamDecision};
 use super::execution_context::GatewayUpstreamExecutionContext;
 use super::precheck::{prepare_candidates_for_proxy, CandidatePrecheckResult};
+use super::stream_prefetch::{prefetch_stream_until_first_delta, StreamPrefetch};
 
-fn respond_terminal(request: Request, status_code: u16, message: String) -> Result<(), String> {
-    let response = Response::from_string(message).with_status_code(status_code);
-    let _ = request.respond(response);
-    Ok(())
-}
 
 fn has_prompt_cache_key(body: &[u8]) -> bool {
     if body.is_empty() {
@@ -28,10 +26,10 @@ fn has_prompt_cache_key(body: &[u8]) -> bool {
 }
 
 pub(in super::super) fn proxy_validated_request(
-    request: Request,
+    request: IncomingRequest,
     validated: LocalValidationResult,
     debug: bool,
-) -> Result<(), String> {
+) -> Response<Body> {
     let LocalValidationResult {
         trace_id,
         storage,
@@ -60,8 +58,7 @@ pub(in super::super) fn proxy_validated_request(
     );
     super::super::trace_log::log_request_body_preview(trace_id.as_str(), &body);
 
-    let (request, mut candidates) = match prepare_candidates_for_proxy(
-        request,
+    let mut candidates = match prepare_candidates_for_proxy(
         &storage,
         trace_id.as_str(),
         &key_id,
@@ -70,17 +67,15 @@ pub(in super::super) fn proxy_validated_request(
         model_for_log.as_deref(),
         reasoning_for_log.as_deref(),
     ) {
-        CandidatePrecheckResult::Ready { request, candidates } => (request, candidates),
-        CandidatePrecheckResult::Responded => return Ok(()),
+        CandidatePrecheckResult::Ready { candidates } => candidates,
+        CandidatePrecheckResult::Responded(response) => return response,
     };
-    let mut request = Some(request);
 
     let upstream_base = super::super::resolve_upstream_base_url();
     let base = upstream_base.as_str();
     let upstream_fallback_base = super::super::resolve_upstream_fallback_base_url(base);
     let (url, url_alt) = super::super::request_rewrite::compute_upstream_url(base, &path);
 
-    let client = super::super::upstream_client();
     let upstream_cookie = std::env::var("GPTTOOLS_UPSTREAM_COOKIE").ok();
 
     let candidate_count = candidates.len();
@@ -149,14 +144,10 @@ pub(in super::super) fn proxy_validated_request(
         None
     };
     let request_shape = super::super::summarize_request_shape(&body);
-    let has_sticky_fallback_session = request
-        .as_ref()
-        .and_then(|value| super::header_profile::derive_sticky_session_id(value))
-        .is_some();
-    let has_sticky_fallback_conversation = request
-        .as_ref()
-        .and_then(|value| super::header_profile::derive_sticky_conversation_id(value))
-        .is_some();
+    let has_sticky_fallback_session =
+        super::header_profile::derive_sticky_session_id(&request).is_some();
+    let has_sticky_fallback_conversation =
+        super::header_profile::derive_sticky_conversation_id(&request).is_some();
 
     for (idx, (account, mut token)) in candidates.into_iter().enumerate() {
         // 中文注释：Claude 兼容入口命中 prompt_cache_key 时，优先保持会话粘性；
@@ -171,10 +162,17 @@ pub(in super::super) fn proxy_validated_request(
             context.log_candidate_skip(&account.id, idx, skip_reason);
             continue;
         }
+        // 中文注释：每个账号固定走自己的出站代理；代理配置坏了就换下一个候选，不能回落直连暴露出口。
+        let client = match super::super::upstream_client_for_proxy(account.proxy_url.as_deref()) {
+            Ok(client) => client,
+            Err(err) => {
+                context.log_attempt_result(&account.id, None, 502, Some(err.as_str()));
+                super::super::record_gateway_failover_attempt();
+                continue;
+            }
+        };
 
-        let request_ref = request
-            .as_ref()
-            .ok_or_else(|| "request already consumed".to_string())?;
+        let request_ref = &request;
         let incoming_session_id = super::header_profile::find_incoming_header(request_ref, "session_id");
         let incoming_turn_state =
             super::header_profile::find_incoming_header(request_ref, "x-codex-turn-state");
@@ -198,6 +196,10 @@ pub(in super::super) fn proxy_validated_request(
         let mut inflight_guard = Some(super::super::acquire_account_inflight(&account.id));
         let mut last_attempt_url: Option<String> = None;
         let mut last_attempt_error: Option<String> = None;
+        // 中文注释：要预读的流式 2xx 先不记尝试结果，等首个增量到达或预读失败后只记一次；
+        // 不这样做同一次尝试会先记 200 再记 502，trace 与路由质量统计都会被算两遍。
+        let prefetch_candidate = is_stream && context.has_more_candidates(idx);
+        let mut deferred_attempt_status: Option<u16> = None;
 
         let decision = process_candidate_upstream_flow(
             &client,
@@ -222,6 +224,11 @@ pub(in super::super) fn proxy_validated_request(
             |upstream_url, status_code, error| {
                 last_attempt_url = upstream_url.map(str::to_string);
                 last_attempt_error = error.map(str::to_string);
+                if prefetch_candidate && (200..300).contains(&status_code) {
+                    deferred_attempt_status = Some(status_code);
+                    return;
+                }
+                deferred_attempt_status = None;
                 super::super::record_route_quality(&account.id, status_code);
                 context.log_attempt_result(&account.id, upstream_url, status_code, error);
             },
@@ -244,36 +251,68 @@ pub(in super::super) fn proxy_validated_request(
                     Some(message.as_str()),
                     elapsed_ms,
                 );
-                let request = request
-                    .take()
-                    .expect("request should be available before terminal response");
-                return respond_terminal(request, status_code, message);
+                return super::super::respond_text(status_code, message);
             }
             CandidateUpstreamDecision::RespondUpstream(resp) => {
                 let status_code = resp.status().as_u16();
+                let mut upstream = super::super::UpstreamReply::from_response(resp);
+                // 中文注释：流式 200 在首个内容增量前仍可能 response.failed 或断连；此时客户端还没收到任何内容，
+                // 换下一个候选账号重试对客户端无感。最后一个候选没有退路，直接透传，不额外缓冲。
+                if prefetch_candidate && upstream.status.is_success() && upstream.is_event_stream()
+                {
+                    match prefetch_stream_until_first_delta(upstream) {
+                        StreamPrefetch::Ready(prefetched) => upstream = prefetched,
+                        StreamPrefetch::Failed(reason) => {
+                            context.log_attempt_result(
+                                &account.id,
+                                last_attempt_url.as_deref(),
+                                502,
+                                Some(reason.as_str()),
+                            );
+                            super::super::record_route_quality(&account.id, 502);
+                            super::super::mark_account_cooldown(
+                                &account.id,
+                                super::super::CooldownReason::StreamPrefetch,
+                            );
+                            super::super::record_gateway_failover_attempt();
+                            continue;
+                        }
+                    }
+                }
+                if let Some(deferred_status) = deferred_attempt_status.take() {
+                    super::super::record_route_quality(&account.id, deferred_status);
+                    context.log_attempt_result(
+                        &account.id,
+                        last_attempt_url.as_deref(),
+                        deferred_status,
+                        last_attempt_error.as_deref(),
+                    );
+                }
                 let final_error = if status_code >= 400 {
                     last_attempt_error.as_deref()
                 } else {
                     None
                 };
-                let elapsed_ms = started_at.elapsed().as_millis();
-                context.log_final_result(
-                    Some(&account.id),
+                // 中文注释：流式响应要等推流结束才知道结果；在响应头阶段就记最终日志，中途断流或停服截断的请求会被记成成功。
+                let final_log = context.begin_final_result(
+                    &account.id,
                     last_attempt_url.as_deref(),
                     status_code,
                     final_error,
-                    elapsed_ms,
+                    started_at,
                 );
                 if status_code >= 200 && status_code < 300 {
                     context.remember_success_account(&account.id);
                 }
-                let request = request
-                    .take()
-                    .expect("request should be available before terminal response");
                 let guard = inflight_guard
                     .take()
                     .expect("inflight guard should be available before terminal response");
-                return super::super::respond_with_upstream(request, resp, guard, response_adapter);
+                return super::super::respond_with_upstream(
+                    upstream,
+                    guard,
+                    final_log,
+                    response_adapter,
+                );
             }
         }
     }
@@ -285,10 +324,7 @@ pub(in super::super) fn proxy_validated_request(
         Some("no available account"),
         started_at.elapsed().as_millis(),
     );
-    let request = request
-        .take()
-        .ok_or_else(|| "request already consumed".to_string())?;
-    respond_terminal(request, 503, "no available account".to_string())
+    super::super::respond_text(503, "no available account")
 }
 
 
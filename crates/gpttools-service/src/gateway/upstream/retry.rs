@@ -1,22 +1,22 @@
 use gpttools_core::storage::Account;
 use reqwest::StatusCode;
-use tiny_http::Request;
 
+use super::super::IncomingRequest;
 use super::transport::send_upstream_request;
 
 pub(super) enum AltPathRetryResult {
     NotTriggered,
-    Upstream(reqwest::blocking::Response),
+    Upstream(reqwest::Response),
     Failover,
     Terminal { status_code: u16, message: String },
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn retry_with_alternate_path<F>(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     method: &reqwest::Method,
     alt_url: Option<&str>,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_cookie: Option<&str>,
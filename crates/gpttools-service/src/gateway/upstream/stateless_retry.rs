@@ -1,12 +1,12 @@
 use gpttools_core::storage::Account;
 use reqwest::StatusCode;
-use tiny_http::Request;
 
+use super::super::IncomingRequest;
 use super::transport::send_upstream_request;
 
 pub(super) enum StatelessRetryResult {
     NotTriggered,
-    Upstream(reqwest::blocking::Response),
+    Upstream(reqwest::Response),
 }
 
 fn should_trigger_stateless_retry(
@@ -25,11 +25,11 @@ fn should_trigger_stateless_retry(
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn retry_stateless_then_optional_alt(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     method: &reqwest::Method,
     primary_url: &str,
     alt_url: Option<&str>,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_cookie: Option<&str>,
@@ -0,0 +1,188 @@
+use axum::body::Bytes;
+use futures_util::{stream, StreamExt};
+use serde_json::Value;
+
+use super::super::{UpstreamByteStream, UpstreamReply};
+
+// 中文注释：预读只为等首个内容增量；上游迟迟不出增量时超过上限直接放行，避免把整段长响应攒在内存里。
+const STREAM_PREFETCH_MAX_BYTES: usize = 256 * 1024;
+
+pub(super) enum StreamPrefetch {
+    Ready(UpstreamReply),
+    Failed(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SseFrameKind {
+    ContentDelta,
+    Completed,
+    Failed(String),
+    Other,
+}
+
+pub(super) fn prefetch_stream_until_first_delta(upstream: UpstreamReply) -> StreamPrefetch {
+    // 预读 SSE 直到首个内容增量；在此之前上游报错或断流，调用方可以无感切换到下一个候选账号
+    super::super::block_on_upstream(async move {
+        let UpstreamReply {
+            status,
+            headers,
+            mut body,
+        } = upstream;
+        let mut buffered: Vec<u8> = Vec::new();
+        let mut scanned = 0usize;
+        loop {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    buffered.extend_from_slice(&chunk);
+                    match scan_buffered_frames(&buffered, &mut scanned) {
+                        Some(SseFrameKind::Failed(reason)) => {
+                            return StreamPrefetch::Failed(reason)
+                        }
+                        Some(_) => break,
+                        None if buffered.len() >= STREAM_PREFETCH_MAX_BYTES => break,
+                        None => {}
+                    }
+                }
+                Some(Err(err)) => {
+                    return StreamPrefetch::Failed(format!(
+                        "upstream stream interrupted before first token: {err}"
+                    ));
+                }
+                None => {
+                    // 中文注释：结尾可能缺少空行分隔，按一帧补判一次；仍没有增量或完成事件就视为失败。
+                    let tail = String::from_utf8_lossy(&buffered[scanned..]).into_owned();
+                    return match classify_sse_frame(&tail) {
+                        SseFrameKind::Failed(reason) => StreamPrefetch::Failed(reason),
+                        SseFrameKind::Other => StreamPrefetch::Failed(
+                            "upstream stream ended before first token".to_string(),
+                        ),
+                        _ => StreamPrefetch::Ready(UpstreamReply {
+                            status,
+                            headers,
+                            body: replay_then_continue(buffered, body),
+                        }),
+                    };
+                }
+            }
+        }
+        StreamPrefetch::Ready(UpstreamReply {
+            status,
+            headers,
+            body: replay_then_continue(buffered, body),
+        })
+    })
+}
+
+fn replay_then_continue(buffered: Vec<u8>, rest: UpstreamByteStream) -> UpstreamByteStream {
+    // 已预读的字节原样先发给客户端，再接上游剩余流，客户端看到的字节序列与直连一致
+    if buffered.is_empty() {
+        return rest;
+    }
+    Box::pin(stream::once(async move { Ok(Bytes::from(buffered)) }).chain(rest))
+}
+
+fn scan_buffered_frames(buffered: &[u8], scanned: &mut usize) -> Option<SseFrameKind> {
+    // 逐个解析已完整到达的 SSE 帧，返回首个决定性事件；scanned 记录下一帧起点
+    let mut line_start = *scanned;
+    while let Some(pos) = buffered[line_start..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+    {
+        let line_end = line_start + pos;
+        let line = &buffered[line_start..line_end];
+        line_start = line_end + 1;
+        if !line.is_empty() && line != b"\r" {
+            continue;
+        }
+        let frame = String::from_utf8_lossy(&buffered[*scanned..line_end]).into_owned();
+        *scanned = line_start;
+        match classify_sse_frame(&frame) {
+            SseFrameKind::Other => continue,
+            decisive => return Some(decisive),
+        }
+    }
+    None
+}
+
+fn classify_sse_frame(frame: &str) -> SseFrameKind {
+    let mut data_lines = Vec::new();
+    for line in frame.lines() {
+        let line = line.trim_end_matches('\r');
+        if let Some(data) = line.strip_prefix("data:") {
+            data_lines.push(data.trim_start());
+        }
+    }
+    if data_lines.is_empty() {
+        return SseFrameKind::Other;
+    }
+    let data = data_lines.join("\n");
+    if data.trim() == "[DONE]" {
+        return SseFrameKind::Completed;
+    }
+    let Ok(value) = serde_json::from_str::<Value>(&data) else {
+        return SseFrameKind::Other;
+    };
+    let event_type = value
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match event_type {
+        "response.failed" | "error" => SseFrameKind::Failed(format!(
+            "upstream stream failed before first token: {}",
+            extract_stream_error_message(&value)
+        )),
+        "response.completed" | "response.done" | "response.incomplete" => SseFrameKind::Completed,
+        _ if event_type.ends_with(".delta") => SseFrameKind::ContentDelta,
+        _ => SseFrameKind::Other,
+    }
+}
+
+fn extract_stream_error_message(value: &Value) -> String {
+    value
+        .pointer("/response/error/message")
+        .or_else(|| value.pointer("/error/message"))
+        .or_else(|| value.get("message"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .unwrap_or("unknown error")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_sse_frame, scan_buffered_frames, SseFrameKind};
+
+    #[test]
+    fn classify_detects_failure_delta_and_completion() {
+        let failed = "event: response.failed\ndata: {\"type\":\"response.failed\",\"response\":{\"error\":{\"message\":\"overloaded\"}}}";
+        assert_eq!(
+            classify_sse_frame(failed),
+            SseFrameKind::Failed(
+                "upstream stream failed before first token: overloaded".to_string()
+            )
+        );
+        let delta = "data: {\"type\":\"response.output_text.delta\",\"delta\":\"hi\"}";
+        assert_eq!(classify_sse_frame(delta), SseFrameKind::ContentDelta);
+        assert_eq!(classify_sse_frame("data: [DONE]"), SseFrameKind::Completed);
+        let created = "data: {\"type\":\"response.created\"}";
+        assert_eq!(classify_sse_frame(created), SseFrameKind::Other);
+    }
+
+    #[test]
+    fn scan_waits_for_complete_frames_and_skips_preamble() {
+        let preamble = b"data: {\"type\":\"response.created\"}\n\n";
+        let mut full = preamble.to_vec();
+        full.extend_from_slice(b"data: {\"type\":\"response.output_text.delta\"");
+        let mut scanned = 0;
+        assert_eq!(scan_buffered_frames(&full, &mut scanned), None);
+        assert_eq!(scanned, preamble.len());
+
+        full.extend_from_slice(b",\"delta\":\"a\"}\r\n\r\n");
+        assert_eq!(
+            scan_buffered_frames(&full, &mut scanned),
+            Some(SseFrameKind::ContentDelta)
+        );
+        assert_eq!(scanned, full.len());
+    }
+}
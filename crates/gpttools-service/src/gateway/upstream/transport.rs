@@ -1,5 +1,6 @@
 use gpttools_core::storage::Account;
-use tiny_http::Request;
+
+use super::super::IncomingRequest;
 
 fn extract_prompt_cache_key(body: &[u8]) -> Option<String> {
     if body.is_empty() || body.len() > 64 * 1024 {
@@ -17,17 +18,17 @@ fn extract_prompt_cache_key(body: &[u8]) -> Option<String> {
 }
 
 pub(super) fn send_upstream_request(
-    client: &reqwest::blocking::Client,
+    client: &reqwest::Client,
     method: &reqwest::Method,
     target_url: &str,
-    request: &Request,
+    request: &IncomingRequest,
     body: &[u8],
     is_stream: bool,
     upstream_cookie: Option<&str>,
     auth_token: &str,
     account: &Account,
     strip_session_affinity: bool,
-) -> Result<reqwest::blocking::Response, reqwest::Error> {
+) -> Result<reqwest::Response, reqwest::Error> {
     let mut builder = client.request(method.clone(), target_url);
     let incoming_session_id = super::header_profile::find_incoming_header(request, "session_id");
     let mut derived_session_id = if !strip_session_affinity && incoming_session_id.is_none() {
@@ -74,7 +75,7 @@ pub(super) fn send_upstream_request(
     if !body.is_empty() {
         builder = builder.body(body.to_vec());
     }
-    builder.send()
+    super::super::block_on_upstream(builder.send())
 }
 
 
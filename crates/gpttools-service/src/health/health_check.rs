@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+use crate::storage_helpers::open_storage;
+
+/// 单项检查结果；失败时带原因。
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CheckResult {
+    pub(crate) ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+impl CheckResult {
+    fn pass() -> Self {
+        Self {
+            ok: true,
+            error: None,
+        }
+    }
+
+    fn fail(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadinessChecks {
+    pub(crate) storage: CheckResult,
+    pub(crate) migrations: CheckResult,
+    pub(crate) accounts: CheckResult,
+}
+
+/// `/readyz` 的返回体。
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadinessReport {
+    pub(crate) ready: bool,
+    pub(crate) shutting_down: bool,
+    pub(crate) checks: ReadinessChecks,
+    pub(crate) schema_version: Option<String>,
+    pub(crate) routable_accounts: usize,
+    pub(crate) cooldown_accounts: usize,
+    pub(crate) last_usage_poll_at: Option<i64>,
+    /// 还没转发完的网关请求数；停服排空时据此观察进度。
+    pub(crate) in_flight_requests: usize,
+}
+
+pub(crate) fn readiness_report() -> ReadinessReport {
+    // 依次检查库能否打开、迁移是否到位、是否还有可路由账号
+    let mut schema_version = None;
+    let mut routable_accounts = 0;
+    let (storage, migrations, accounts) = match open_storage() {
+        None => (
+            CheckResult::fail("storage unavailable"),
+            CheckResult::fail("storage unavailable"),
+            CheckResult::fail("storage unavailable"),
+        ),
+        Some(storage) => {
+            let migrations = match storage.schema_info() {
+                Ok(info) => {
+                    schema_version = info.current_version;
+                    if !info.unknown.is_empty() {
+                        CheckResult::fail(format!(
+                            "database has migrations unknown to this build: {}",
+                            info.unknown.join(", ")
+                        ))
+                    } else if !info.pending.is_empty() {
+                        CheckResult::fail(format!(
+                            "pending migrations: {}",
+                            info.pending.join(", ")
+                        ))
+                    } else {
+                        CheckResult::pass()
+                    }
+                }
+                Err(err) => CheckResult::fail(err.to_string()),
+            };
+            // 中文注释：与网关选号用同一份候选集合（含预留组与兜底规则），计数才能反映网关此刻到底能不能转发。
+            let accounts = match crate::gateway::collect_gateway_candidates(&storage) {
+                Ok(candidates) if candidates.is_empty() => {
+                    CheckResult::fail("no routable accounts")
+                }
+                Ok(candidates) => {
+                    routable_accounts = candidates.len();
+                    CheckResult::pass()
+                }
+                Err(err) => CheckResult::fail(err),
+            };
+            (CheckResult::pass(), migrations, accounts)
+        }
+    };
+    let shutting_down = crate::shutdown_requested();
+    ReadinessReport {
+        ready: !shutting_down && storage.ok && migrations.ok && accounts.ok,
+        shutting_down,
+        checks: ReadinessChecks {
+            storage,
+            migrations,
+            accounts,
+        },
+        schema_version,
+        routable_accounts,
+        cooldown_accounts: crate::gateway::cooldown_account_count(),
+        last_usage_poll_at: crate::usage_refresh::last_usage_refresh_at(),
+        in_flight_requests: crate::drain_status().in_flight_requests,
+    }
+}
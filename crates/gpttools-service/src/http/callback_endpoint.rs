@@ -1,7 +1,19 @@
-use tiny_http::Request;
+use axum::body::Body;
+use axum::http::{Response, StatusCode};
 
-pub fn handle_callback(request: Request) {
-    if let Err(err) = crate::auth_callback::handle_login_request(request) {
-        log::warn!("callback request error: {err}");
+use crate::http::proxy_response::text_response;
+
+pub async fn handle_callback(url: String) -> Response<Body> {
+    match tokio::task::spawn_blocking(move || crate::auth_callback::login_callback_response(&url))
+        .await
+    {
+        Ok((status_code, message)) => text_response(
+            StatusCode::from_u16(status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            message,
+        ),
+        Err(err) => {
+            log::warn!("callback request error: {err}");
+            text_response(StatusCode::INTERNAL_SERVER_ERROR, "callback failed")
+        }
     }
 }
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+const ENV_SHUTDOWN_DRAIN_SECS: &str = "GPTTOOLS_SHUTDOWN_DRAIN_SECS";
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const DRAIN_PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+static IN_FLIGHT_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+static DRAIN_DEADLINE: Mutex<Option<Instant>> = Mutex::new(None);
+static DRAIN_DEADLINE_EXCEEDED: AtomicBool = AtomicBool::new(false);
+/// 进入排空阶段时唤醒等期限的任务。
+static DRAIN_STARTED: Notify = Notify::const_new();
+
+/// 停服排空进度。
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainStatus {
+    /// 是否已进入排空阶段（不再接收新的网关请求）。
+    pub draining: bool,
+    /// 还没转发完的网关请求数，含仍在推流的响应。
+    pub in_flight_requests: usize,
+    /// 距强制断开剩余的毫秒数；未在排空时为空。
+    pub remaining_ms: Option<u64>,
+    /// 是否已到期限、剩余请求被强制断开。
+    pub deadline_exceeded: bool,
+}
+
+/// 网关请求的在途占用，随响应体一起释放。
+pub(crate) struct InFlightGuard;
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT_REQUESTS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn track_in_flight() -> InFlightGuard {
+    IN_FLIGHT_REQUESTS.fetch_add(1, Ordering::SeqCst);
+    InFlightGuard
+}
+
+fn lock_deadline() -> std::sync::MutexGuard<'static, Option<Instant>> {
+    DRAIN_DEADLINE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub(crate) fn drain_timeout_from_env() -> Duration {
+    // 读取排空期限（秒），未配置或无法解析时用默认值
+    std::env::var(ENV_SHUTDOWN_DRAIN_SECS)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DRAIN_TIMEOUT)
+}
+
+pub(crate) fn begin_drain(timeout: Duration) -> DrainStatus {
+    // 中文注释：重复停服请求沿用第一次的期限；不这样做多次点击停止会把期限一再往后推。
+    let mut deadline = lock_deadline();
+    if deadline.is_none() {
+        *deadline = Some(Instant::now() + timeout);
+        log::info!(
+            "shutdown requested, draining {} in-flight gateway requests (up to {}s)",
+            IN_FLIGHT_REQUESTS.load(Ordering::SeqCst),
+            timeout.as_secs()
+        );
+        DRAIN_STARTED.notify_waiters();
+    }
+    drop(deadline);
+    drain_status()
+}
+
+pub(crate) fn reset_drain() {
+    *lock_deadline() = None;
+    DRAIN_DEADLINE_EXCEEDED.store(false, Ordering::SeqCst);
+}
+
+pub(crate) fn drain_status() -> DrainStatus {
+    let deadline = *lock_deadline();
+    DrainStatus {
+        draining: deadline.is_some(),
+        in_flight_requests: IN_FLIGHT_REQUESTS.load(Ordering::SeqCst),
+        remaining_ms: deadline.map(|deadline| {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            u64::try_from(remaining.as_millis()).unwrap_or(u64::MAX)
+        }),
+        deadline_exceeded: drain_deadline_exceeded(),
+    }
+}
+
+pub(crate) fn drain_deadline_exceeded() -> bool {
+    DRAIN_DEADLINE_EXCEEDED.load(Ordering::SeqCst)
+}
+
+/// 一直等到排空期限到达；未在排空时不会返回。
+pub(crate) async fn wait_for_drain_deadline() {
+    loop {
+        // 中文注释：先登记等待再检查期限；不这样做 begin_drain 恰好落在两步之间时这次唤醒会丢掉。
+        let notified = DRAIN_STARTED.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        let deadline = *lock_deadline();
+        match deadline {
+            Some(deadline) => {
+                tokio::time::sleep_until(deadline.into()).await;
+                return;
+            }
+            None => notified.await,
+        }
+    }
+}
+
+/// 停服时等在途网关请求结束，超过期限就放弃等待并标记剩余请求为强制断开。
+pub(crate) async fn wait_until_drained() {
+    let mut last_report = Instant::now();
+    loop {
+        let status = drain_status();
+        if !status.draining {
+            return;
+        }
+        if status.in_flight_requests == 0 {
+            log::info!("all in-flight gateway requests drained");
+            return;
+        }
+        if status.remaining_ms == Some(0) {
+            DRAIN_DEADLINE_EXCEEDED.store(true, Ordering::SeqCst);
+            log::warn!(
+                "shutdown drain deadline reached, cutting off {} in-flight gateway requests",
+                status.in_flight_requests
+            );
+            return;
+        }
+        if last_report.elapsed() >= DRAIN_PROGRESS_LOG_INTERVAL {
+            last_report = Instant::now();
+            log::info!(
+                "draining: {} gateway requests still in flight ({}s left)",
+                status.in_flight_requests,
+                status.remaining_ms.unwrap_or(0) / 1000
+            );
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_status_tracks_guards_and_keeps_first_deadline() {
+        reset_drain();
+        let before = drain_status();
+        assert!(!before.draining);
+        assert_eq!(before.remaining_ms, None);
+
+        let guard = track_in_flight();
+        let status = begin_drain(Duration::from_secs(60));
+        assert!(status.draining);
+        assert!(status.in_flight_requests >= 1);
+        assert!(status.remaining_ms.is_some_and(|ms| ms > 50_000));
+
+        // 第二次停服请求不会延长或缩短期限
+        let again = begin_drain(Duration::from_secs(1));
+        assert!(again.remaining_ms.is_some_and(|ms| ms > 50_000));
+        drop(guard);
+
+        reset_drain();
+        assert!(!drain_status().draining);
+        assert!(!drain_status().deadline_exceeded);
+
+        // 中文注释：和上面共用全局排空状态，放在同一个用例里避免并行测试互相 reset。
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let waiter = tokio::spawn(wait_for_drain_deadline());
+            tokio::task::yield_now().await;
+            assert!(!waiter.is_finished());
+            begin_drain(Duration::from_millis(50));
+            tokio::time::timeout(Duration::from_secs(5), waiter)
+                .await
+                .expect("waiter returns after deadline")
+                .expect("waiter task");
+        });
+        assert_eq!(drain_status().remaining_ms, Some(0));
+        reset_drain();
+    }
+}
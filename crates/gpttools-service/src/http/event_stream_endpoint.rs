@@ -0,0 +1,173 @@
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::Query;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures_util::stream::{self, Stream};
+use gpttools_core::rpc::types::EventSummary;
+use gpttools_core::storage::EventQuery;
+
+use crate::event_list;
+
+const EVENT_STREAM_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+const EVENT_STREAM_BATCH_LIMIT: i64 = 200;
+
+struct EventStreamState {
+    cursor: i64,
+    query: EventQuery,
+    pending: VecDeque<EventSummary>,
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn authorize(headers: &HeaderMap) -> Result<(), StatusCode> {
+    // 中文注释：事件流与 /rpc 使用同一套鉴权与来源校验；不这样做会让事件内容绕过 RPC token 泄露给任意本地页面。
+    match header_value(headers, "X-Gpttools-Rpc-Token") {
+        // 中文注释：事件流只读，任意 scope 的具名管理 token 都可订阅。
+        Some(token) if crate::admin_tokens::resolve_rpc_caller(token).is_none() => {
+            return Err(StatusCode::UNAUTHORIZED)
+        }
+        Some(_) => {}
+        None if !crate::http::rpc_endpoint::allow_unauthenticated_rpc() => {
+            return Err(StatusCode::UNAUTHORIZED)
+        }
+        None => {}
+    }
+    if let Some(fetch_site) = header_value(headers, "Sec-Fetch-Site") {
+        if fetch_site.eq_ignore_ascii_case("cross-site") {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    if let Some(origin) = header_value(headers, "Origin") {
+        if !crate::http::rpc_endpoint::is_loopback_origin(origin) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok(())
+}
+
+fn parse_stream_query(params: &HashMap<String, String>) -> EventQuery {
+    let get_str = |key: &str| {
+        params
+            .get(key)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+    EventQuery {
+        account_id: get_str("accountId"),
+        event_type: get_str("type"),
+        since: None,
+        until: None,
+        limit: EVENT_STREAM_BATCH_LIMIT,
+    }
+}
+
+fn resolve_start_cursor(params: &HashMap<String, String>, headers: &HeaderMap) -> Option<i64> {
+    // 中文注释：优先使用 EventSource 自动重连带回的 Last-Event-ID，其次才是显式 afterId；都没有时只推送连接之后的新事件。
+    header_value(headers, "Last-Event-ID")
+        .or_else(|| params.get("afterId").map(String::as_str))
+        .and_then(|v| v.trim().parse::<i64>().ok())
+}
+
+fn to_sse_event(item: &EventSummary) -> Event {
+    let data = serde_json::to_string(item).unwrap_or_else(|_| "{}".to_string());
+    Event::default()
+        .id(item.id.to_string())
+        .event(item.event_type.clone())
+        .data(data)
+}
+
+fn event_stream(state: EventStreamState) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if crate::shutdown_requested() {
+                return None;
+            }
+            if let Some(item) = state.pending.pop_front() {
+                state.cursor = item.id;
+                return Some((Ok(to_sse_event(&item)), state));
+            }
+            let cursor = state.cursor;
+            let query = state.query.clone();
+            let fetched = tokio::task::spawn_blocking(move || {
+                event_list::read_events_after(cursor, &query)
+            })
+            .await;
+            match fetched {
+                Ok(Ok(items)) if !items.is_empty() => state.pending.extend(items),
+                Ok(Err(err)) => {
+                    log::warn!("event stream poll failed: {err}");
+                    tokio::time::sleep(EVENT_STREAM_POLL_INTERVAL).await;
+                }
+                _ => tokio::time::sleep(EVENT_STREAM_POLL_INTERVAL).await,
+            }
+        }
+    })
+}
+
+pub(crate) async fn handle_event_stream(
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Err(status) = authorize(&headers) {
+        return (status, "{}").into_response();
+    }
+    let cursor = match resolve_start_cursor(&params, &headers) {
+        Some(cursor) => cursor,
+        None => match tokio::task::spawn_blocking(event_list::latest_event_id).await {
+            Ok(Ok(cursor)) => cursor,
+            Ok(Err(err)) => return (StatusCode::SERVICE_UNAVAILABLE, err).into_response(),
+            Err(err) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+            }
+        },
+    };
+    let state = EventStreamState {
+        cursor,
+        query: parse_stream_query(&params),
+        pending: VecDeque::new(),
+    };
+    Sse::new(event_stream(state))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_stream_query, resolve_start_cursor};
+    use axum::http::{HeaderMap, HeaderValue};
+    use std::collections::HashMap;
+
+    #[test]
+    fn stream_query_reads_account_and_type_filters() {
+        let mut params = HashMap::new();
+        params.insert("accountId".to_string(), " acc-1 ".to_string());
+        params.insert("type".to_string(), "".to_string());
+        let query = parse_stream_query(&params);
+        assert_eq!(query.account_id.as_deref(), Some("acc-1"));
+        assert_eq!(query.event_type, None);
+    }
+
+    #[test]
+    fn start_cursor_prefers_last_event_id_header() {
+        let mut params = HashMap::new();
+        params.insert("afterId".to_string(), "5".to_string());
+        let mut headers = HeaderMap::new();
+        assert_eq!(resolve_start_cursor(&params, &headers), Some(5));
+
+        headers.insert("Last-Event-ID", HeaderValue::from_static("9"));
+        assert_eq!(resolve_start_cursor(&params, &headers), Some(9));
+
+        assert_eq!(resolve_start_cursor(&HashMap::new(), &HeaderMap::new()), None);
+    }
+}
@@ -1,16 +1,25 @@
-use tiny_http::{Header, Request, Response};
+use axum::body::Body;
+use axum::http::{Response, StatusCode};
 
-pub fn handle_gateway(request: Request) {
-    if let Err(err) = crate::gateway::handle_gateway_request(request) {
-        log::error!("gateway request error: {err}");
+use crate::gateway::IncomingRequest;
+use crate::http::proxy_response::text_response;
+
+pub async fn handle_gateway(request: IncomingRequest) -> Response<Body> {
+    // 中文注释：选号/重试阶段依赖同步 SQLite，放在阻塞线程池；返回的响应体由 tokio 异步推流，不再为每条长流占一个线程。
+    match tokio::task::spawn_blocking(move || crate::gateway::handle_gateway_request(request)).await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            log::error!("gateway request error: {err}");
+            text_response(StatusCode::INTERNAL_SERVER_ERROR, "gateway request failed")
+        }
     }
 }
 
-pub fn handle_metrics(request: Request) {
+pub fn handle_metrics() -> Response<Body> {
     let body = crate::gateway::gateway_metrics_prometheus();
-    let mut response = Response::from_string(body);
-    if let Ok(content_type) = Header::from_bytes(b"Content-Type", b"text/plain; version=0.0.4") {
-        response = response.with_header(content_type);
-    }
-    let _ = request.respond(response);
+    Response::builder()
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| text_response(StatusCode::INTERNAL_SERVER_ERROR, "metrics failed"))
 }
@@ -0,0 +1,38 @@
+use axum::body::Body;
+use axum::http::{Response, StatusCode};
+
+use crate::http::proxy_response::text_response;
+
+fn json_response(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Cache-Control", "no-store")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| text_response(StatusCode::INTERNAL_SERVER_ERROR, "health failed"))
+}
+
+pub fn handle_healthz() -> Response<Body> {
+    // 中文注释：存活探针只说明进程还在处理请求，不查库；查库失败就重启进程解决不了问题，反而会让监管方反复拉起。
+    json_response(StatusCode::OK, r#"{"status":"ok"}"#.to_string())
+}
+
+pub async fn handle_readyz() -> Response<Body> {
+    match tokio::task::spawn_blocking(crate::health_check::readiness_report).await {
+        Ok(report) => {
+            let status = if report.ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            json_response(
+                status,
+                serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string()),
+            )
+        }
+        Err(err) => {
+            log::error!("readiness check error: {err}");
+            text_response(StatusCode::SERVICE_UNAVAILABLE, "readiness check failed")
+        }
+    }
+}
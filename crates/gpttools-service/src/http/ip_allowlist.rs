@@ -0,0 +1,121 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::RwLock;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Request, Response, StatusCode};
+use axum::middleware::Next;
+use ipnet::IpNet;
+
+use crate::http::proxy_response::text_response;
+
+static ACTIVE_IP_ALLOWLIST: RwLock<Option<IpAllowlist>> = RwLock::new(None);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct IpAllowlist {
+    nets: Vec<IpNet>,
+}
+
+impl IpAllowlist {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        // 解析逗号/空白分隔的 IP 或 CIDR 列表
+        let mut nets = Vec::new();
+        for entry in raw
+            .split(|ch: char| ch == ',' || ch.is_whitespace())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            let net = match entry.parse::<IpNet>() {
+                Ok(net) => net,
+                Err(_) => entry
+                    .parse::<IpAddr>()
+                    .map(IpNet::from)
+                    .map_err(|_| format!("invalid allowlist entry: {entry}"))?,
+            };
+            nets.push(net.trunc());
+        }
+        Ok(Self { nets })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nets.is_empty()
+    }
+
+    pub(crate) fn allows(&self, ip: IpAddr) -> bool {
+        // 中文注释：双栈监听下 IPv4 客户端会以 ::ffff:a.b.c.d 出现，先还原成 IPv4 再匹配；否则 IPv4 网段永远匹配不上。
+        let ip = ip.to_canonical();
+        // 中文注释：本机回环始终放行，桌面端 RPC 与本地客户端不受白名单影响。
+        if self.nets.is_empty() || ip.is_loopback() {
+            return true;
+        }
+        self.nets.iter().any(|net| net.contains(&ip))
+    }
+}
+
+pub(crate) fn install_ip_allowlist(allowlist: Option<IpAllowlist>) {
+    let mut active = ACTIVE_IP_ALLOWLIST
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *active = allowlist.filter(|list| !list.is_empty());
+}
+
+fn client_ip_allowed(ip: IpAddr) -> bool {
+    let active = ACTIVE_IP_ALLOWLIST
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    active.as_ref().map(|list| list.allows(ip)).unwrap_or(true)
+}
+
+pub(crate) async fn enforce_ip_allowlist(
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response<Body> {
+    // 前置代理入口统一按来源 IP 拦截，早于鉴权与请求体读取
+    if !client_ip_allowed(remote_addr.ip()) {
+        log::warn!(
+            "rejected request from {} (not in allowlist)",
+            remote_addr.ip()
+        );
+        return text_response(StatusCode::FORBIDDEN, "client ip not allowed");
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IpAllowlist;
+    use std::net::IpAddr;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().expect("ip")
+    }
+
+    #[test]
+    fn allowlist_matches_single_ips_and_cidrs() {
+        let list = IpAllowlist::parse("192.168.1.0/24, 10.0.0.5 fd00::/8").expect("parse");
+        assert!(list.allows(ip("192.168.1.77")));
+        assert!(list.allows(ip("10.0.0.5")));
+        assert!(list.allows(ip("fd12::1")));
+        assert!(!list.allows(ip("10.0.0.6")));
+        assert!(!list.allows(ip("192.168.2.1")));
+    }
+
+    #[test]
+    fn allowlist_always_allows_loopback_and_maps_ipv4_in_ipv6() {
+        let list = IpAllowlist::parse("192.168.1.0/24").expect("parse");
+        assert!(list.allows(ip("127.0.0.1")));
+        assert!(list.allows(ip("::1")));
+        assert!(list.allows(ip("::ffff:192.168.1.9")));
+        assert!(!list.allows(ip("::ffff:172.16.0.1")));
+    }
+
+    #[test]
+    fn allowlist_rejects_invalid_entries_and_empty_allows_all() {
+        assert!(IpAllowlist::parse("192.168.1.0/33").is_err());
+        assert!(IpAllowlist::parse("lan").is_err());
+        let empty = IpAllowlist::parse(" , ").expect("parse");
+        assert!(empty.is_empty());
+        assert!(empty.allows(ip("203.0.113.1")));
+    }
+}
@@ -0,0 +1,194 @@
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::PathBuf;
+
+use crate::http::ip_allowlist::IpAllowlist;
+
+pub(crate) const ENV_GATEWAY_ADDR: &str = "GPTTOOLS_GATEWAY_ADDR";
+pub(crate) const ENV_TLS_CERT: &str = "GPTTOOLS_TLS_CERT";
+pub(crate) const ENV_TLS_KEY: &str = "GPTTOOLS_TLS_KEY";
+pub(crate) const ENV_ALLOWED_IPS: &str = "GPTTOOLS_ALLOWED_IPS";
+pub(crate) const ENV_RPC_ALLOW_REMOTE: &str = "GPTTOOLS_RPC_ALLOW_REMOTE";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TlsPaths {
+    pub(crate) cert: PathBuf,
+    pub(crate) key: PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ListenOptions {
+    pub(crate) gateway_addr: Option<String>,
+    pub(crate) tls_cert: Option<String>,
+    pub(crate) tls_key: Option<String>,
+    pub(crate) allowed_ips: Option<String>,
+    pub(crate) allow_remote_rpc: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ListenConfig {
+    /// RPC、回调、事件流与本机网关共用的控制端口。
+    pub(crate) control_addr: String,
+    /// 仅转发网关流量的对外端口，可选 TLS。
+    pub(crate) gateway_addr: Option<String>,
+    pub(crate) tls: Option<TlsPaths>,
+    pub(crate) allowlist: Option<IpAllowlist>,
+}
+
+fn env_value(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+impl ListenOptions {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            gateway_addr: env_value(ENV_GATEWAY_ADDR),
+            tls_cert: env_value(ENV_TLS_CERT),
+            tls_key: env_value(ENV_TLS_KEY),
+            allowed_ips: env_value(ENV_ALLOWED_IPS),
+            allow_remote_rpc: env_value(ENV_RPC_ALLOW_REMOTE)
+                .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl ListenConfig {
+    pub(crate) fn resolve(control_addr: &str, options: ListenOptions) -> Result<Self, String> {
+        // 校验监听组合：RPC 默认只许回环，TLS 只挂在对外网关端口上
+        let control_addr = control_addr.trim().to_string();
+        // 中文注释：RPC 能改账号、导出 token；默认拒绝绑到非回环地址，局域网共享应走 GPTTOOLS_GATEWAY_ADDR 单独开网关端口。
+        if !options.allow_remote_rpc && !is_loopback_addr(&control_addr) {
+            return Err(format!(
+                "refusing to expose RPC on non-loopback address {control_addr}; \
+                 use {ENV_GATEWAY_ADDR} for remote gateway access or set {ENV_RPC_ALLOW_REMOTE}=1"
+            ));
+        }
+        let gateway_addr = options.gateway_addr;
+        if gateway_addr.as_deref() == Some(control_addr.as_str()) {
+            return Err(format!(
+                "{ENV_GATEWAY_ADDR} must differ from the service address {control_addr}"
+            ));
+        }
+        let tls = match (options.tls_cert, options.tls_key) {
+            (Some(cert), Some(key)) => Some(TlsPaths {
+                cert: PathBuf::from(cert),
+                key: PathBuf::from(key),
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(format!(
+                    "{ENV_TLS_CERT} and {ENV_TLS_KEY} must be set together"
+                ))
+            }
+        };
+        if tls.is_some() && gateway_addr.is_none() {
+            return Err(format!(
+                "TLS is served on the gateway listener; set {ENV_GATEWAY_ADDR} as well"
+            ));
+        }
+        let allowlist = options
+            .allowed_ips
+            .as_deref()
+            .map(IpAllowlist::parse)
+            .transpose()?;
+        Ok(Self {
+            control_addr,
+            gateway_addr,
+            tls,
+            allowlist,
+        })
+    }
+}
+
+pub(crate) fn is_loopback_addr(addr: &str) -> bool {
+    // 判断监听地址是否只落在本机回环上（主机名按解析结果判断）
+    let host = addr.rsplit_once(':').map(|(host, _)| host).unwrap_or(addr);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return ip.is_loopback();
+    }
+    match addr.to_socket_addrs() {
+        Ok(resolved) => {
+            let resolved: Vec<_> = resolved.collect();
+            !resolved.is_empty() && resolved.iter().all(|item| item.ip().is_loopback())
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_loopback_addr, ListenConfig, ListenOptions};
+
+    #[test]
+    fn loopback_detection_covers_names_and_both_ip_families() {
+        assert!(is_loopback_addr("localhost:48760"));
+        assert!(is_loopback_addr("127.0.0.1:48760"));
+        assert!(is_loopback_addr("[::1]:48760"));
+        assert!(!is_loopback_addr("0.0.0.0:48760"));
+        assert!(!is_loopback_addr("[::]:48760"));
+        assert!(!is_loopback_addr("192.168.1.10:48760"));
+    }
+
+    #[test]
+    fn rpc_stays_loopback_unless_explicitly_allowed() {
+        let err = ListenConfig::resolve("0.0.0.0:48760", ListenOptions::default())
+            .expect_err("remote rpc rejected");
+        assert!(err.contains("GPTTOOLS_GATEWAY_ADDR"), "{err}");
+
+        let config = ListenConfig::resolve(
+            "0.0.0.0:48760",
+            ListenOptions {
+                allow_remote_rpc: true,
+                ..ListenOptions::default()
+            },
+        )
+        .expect("explicit opt-in");
+        assert_eq!(config.control_addr, "0.0.0.0:48760");
+        assert!(config.gateway_addr.is_none());
+    }
+
+    #[test]
+    fn tls_requires_cert_key_pair_and_gateway_listener() {
+        let half = ListenConfig::resolve(
+            "localhost:48760",
+            ListenOptions {
+                gateway_addr: Some("0.0.0.0:48761".to_string()),
+                tls_cert: Some("cert.pem".to_string()),
+                ..ListenOptions::default()
+            },
+        );
+        assert!(half.is_err());
+
+        let without_gateway = ListenConfig::resolve(
+            "localhost:48760",
+            ListenOptions {
+                tls_cert: Some("cert.pem".to_string()),
+                tls_key: Some("key.pem".to_string()),
+                ..ListenOptions::default()
+            },
+        );
+        assert!(without_gateway.is_err());
+
+        let config = ListenConfig::resolve(
+            "localhost:48760",
+            ListenOptions {
+                gateway_addr: Some("0.0.0.0:48761".to_string()),
+                tls_cert: Some("cert.pem".to_string()),
+                tls_key: Some("key.pem".to_string()),
+                allowed_ips: Some("192.168.1.0/24".to_string()),
+                allow_remote_rpc: false,
+            },
+        )
+        .expect("remote gateway with tls");
+        assert_eq!(config.gateway_addr.as_deref(), Some("0.0.0.0:48761"));
+        assert!(config.tls.is_some());
+        assert!(config.allowlist.is_some());
+    }
+}
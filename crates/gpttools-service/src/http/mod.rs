@@ -2,13 +2,16 @@ pub mod server;
 pub mod rpc_endpoint;
 pub mod callback_endpoint;
 pub mod gateway_endpoint;
+pub mod health_endpoint;
 
-pub(crate) mod backend_runtime;
+pub(crate) mod drain;
+pub(crate) mod event_stream_endpoint;
+pub(crate) mod ip_allowlist;
+pub(crate) mod listen_config;
+pub(crate) mod tls_listener;
 pub(crate) mod proxy_bridge;
 pub(crate) mod route_dispatch;
 pub(crate) mod request_dispatch;
 
 pub(crate) mod header_filter;
-pub(crate) mod proxy_request;
 pub(crate) mod proxy_response;
-pub(crate) mod proxy_runtime;
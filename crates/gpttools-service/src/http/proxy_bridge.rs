@@ -1,9 +1,13 @@
 use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use axum::Router;
 
-async fn wait_for_shutdown_signal() {
+use crate::http::tls_listener::{serve_tls_on_listener, TlsReloader};
+
+pub(crate) async fn wait_for_shutdown_signal() {
     while !crate::shutdown_requested() {
         tokio::time::sleep(Duration::from_millis(50)).await;
     }
@@ -13,7 +17,7 @@ async fn serve_proxy_on_listener(
     listener: tokio::net::TcpListener,
     app: Router,
 ) -> io::Result<()> {
-    axum::serve(listener, app)
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(wait_for_shutdown_signal())
         .await
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
@@ -41,3 +45,16 @@ pub(crate) async fn run_proxy_server(addr: &str, app: Router) -> io::Result<()>
     let listener = tokio::net::TcpListener::bind(addr).await?;
     serve_proxy_on_listener(listener, app).await
 }
+
+pub(crate) async fn run_gateway_server(
+    addr: &str,
+    app: Router,
+    tls: Option<Arc<TlsReloader>>,
+) -> io::Result<()> {
+    // 对外网关端口：按配置决定是否套 TLS
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    match tls {
+        Some(reloader) => serve_tls_on_listener(listener, app, reloader).await,
+        None => serve_proxy_on_listener(listener, app).await,
+    }
+}
@@ -1,14 +1,126 @@
-use tiny_http::Request;
+use std::net::SocketAddr;
 
+use axum::body::{to_bytes, Body, HttpBody};
+use axum::extract::ConnectInfo;
+use axum::http::header::{CONNECTION, CONTENT_TYPE};
+use axum::http::{HeaderValue, Request, Response, StatusCode};
+use futures_util::StreamExt;
+
+use crate::gateway::IncomingRequest;
+use crate::http::drain::{track_in_flight, InFlightGuard};
+use crate::http::header_filter::should_skip_request_header;
+use crate::http::proxy_response::text_response;
 use crate::http::route_dispatch::{resolve_backend_route, BackendRoute};
 
-pub(crate) fn dispatch_backend_request(request: Request) {
-    let route = resolve_backend_route(request.method().as_str(), request.url());
+const MAX_REQUEST_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+pub(crate) async fn dispatch_request(
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+) -> Response<Body> {
+    dispatch(remote_addr, request, false).await
+}
+
+pub(crate) async fn dispatch_gateway_request(
+    ConnectInfo(remote_addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+) -> Response<Body> {
+    dispatch(remote_addr, request, true).await
+}
+
+async fn dispatch(
+    remote_addr: SocketAddr,
+    request: Request<Body>,
+    gateway_only: bool,
+) -> Response<Body> {
+    let (parts, body) = request.into_parts();
+    let url = parts
+        .uri
+        .path_and_query()
+        .map(|value| value.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    let route = resolve_backend_route(parts.method.as_str(), &url);
+    // 中文注释：对外网关端口只转发网关流量与健康检查；RPC、回调、指标与 /__shutdown 都留在回环控制端口，否则远端可直接改配置或停服。
+    if gateway_only
+        && ((route != BackendRoute::Gateway && !route.is_health_probe())
+            || parts.uri.path() == "/__shutdown")
+    {
+        return text_response(StatusCode::NOT_FOUND, "not found");
+    }
+    if parts.uri.path() == "/__shutdown" {
+        return drain_status_response();
+    }
+    // 中文注释：停服期间健康检查照常应答；否则就绪探针拿不到 503，负载均衡不会把流量切走。
+    if crate::shutdown_requested() && !route.is_health_probe() {
+        return shutting_down_response();
+    }
+
+    let body = match to_bytes(body, MAX_REQUEST_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            return text_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("request body too large: {err}"),
+            );
+        }
+    };
 
     match route {
-        BackendRoute::Rpc => crate::http::rpc_endpoint::handle_rpc(request),
-        BackendRoute::AuthCallback => crate::http::callback_endpoint::handle_callback(request),
-        BackendRoute::Metrics => crate::http::gateway_endpoint::handle_metrics(request),
-        BackendRoute::Gateway => crate::http::gateway_endpoint::handle_gateway(request),
+        BackendRoute::Rpc => crate::http::rpc_endpoint::handle_rpc(&parts.headers, body).await,
+        BackendRoute::AuthCallback => crate::http::callback_endpoint::handle_callback(url).await,
+        BackendRoute::Metrics => crate::http::gateway_endpoint::handle_metrics(),
+        BackendRoute::Health => crate::http::health_endpoint::handle_healthz(),
+        BackendRoute::Ready => crate::http::health_endpoint::handle_readyz().await,
+        BackendRoute::Gateway => {
+            let headers = parts
+                .headers
+                .iter()
+                .filter(|(name, value)| !should_skip_request_header(name, value))
+                .filter_map(|(name, value)| {
+                    let value = value.to_str().ok()?;
+                    Some((name.as_str().to_string(), value.to_string()))
+                })
+                .collect();
+            let request = IncomingRequest::new(
+                parts.method.as_str(),
+                url,
+                headers,
+                Some(remote_addr),
+                body.to_vec(),
+            );
+            let in_flight = track_in_flight();
+            let response = crate::http::gateway_endpoint::handle_gateway(request).await;
+            hold_until_body_done(response, in_flight)
+        }
+    }
+}
+
+fn shutting_down_response() -> Response<Body> {
+    let mut response = text_response(StatusCode::SERVICE_UNAVAILABLE, "service is shutting down");
+    // 中文注释：排空期间让客户端断开长连接改连其它实例；不这样做 keep-alive 连接会继续把新请求送进来吃 503。
+    response
+        .headers_mut()
+        .insert(CONNECTION, HeaderValue::from_static("close"));
+    response
+}
+
+fn drain_status_response() -> Response<Body> {
+    let body = serde_json::to_string(&crate::drain_status()).unwrap_or_else(|_| "{}".to_string());
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| text_response(StatusCode::OK, "shutdown"))
+}
+
+fn hold_until_body_done(response: Response<Body>, in_flight: InFlightGuard) -> Response<Body> {
+    // 中文注释：在途计数跟着响应体走到流结束；只算到处理函数返回的话，排空时正在推流的 SSE 会被当成已完成直接断开。
+    if response.body().size_hint().exact().is_some() {
+        return response;
     }
+    response.map(|body| {
+        Body::from_stream(body.into_data_stream().map(move |chunk| {
+            let _ = &in_flight;
+            chunk
+        }))
+    })
 }
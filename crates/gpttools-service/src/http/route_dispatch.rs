@@ -3,9 +3,18 @@ pub(crate) enum BackendRoute {
     Rpc,
     AuthCallback,
     Metrics,
+    Health,
+    Ready,
     Gateway,
 }
 
+impl BackendRoute {
+    /// 存活/就绪探针：对外网关端口与停服期间都要能应答。
+    pub(crate) fn is_health_probe(self) -> bool {
+        matches!(self, Self::Health | Self::Ready)
+    }
+}
+
 pub(crate) fn resolve_backend_route(method: &str, path: &str) -> BackendRoute {
     if method == "POST" && path == "/rpc" {
         return BackendRoute::Rpc;
@@ -16,6 +25,12 @@ pub(crate) fn resolve_backend_route(method: &str, path: &str) -> BackendRoute {
     if method == "GET" && path == "/metrics" {
         return BackendRoute::Metrics;
     }
+    if method == "GET" && path == "/healthz" {
+        return BackendRoute::Health;
+    }
+    if method == "GET" && path == "/readyz" {
+        return BackendRoute::Ready;
+    }
     BackendRoute::Gateway
 }
 
@@ -44,6 +59,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolves_health_routes() {
+        assert_eq!(resolve_backend_route("GET", "/healthz"), BackendRoute::Health);
+        assert_eq!(resolve_backend_route("GET", "/readyz"), BackendRoute::Ready);
+        assert_eq!(
+            resolve_backend_route("POST", "/readyz"),
+            BackendRoute::Gateway
+        );
+    }
+
     #[test]
     fn falls_back_to_gateway_route() {
         assert_eq!(
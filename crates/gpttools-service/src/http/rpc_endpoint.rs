@@ -1,24 +1,32 @@
-use tiny_http::Request;
-use tiny_http::Response;
+use axum::body::{Body, Bytes};
+use axum::http::{HeaderMap, Response, StatusCode};
 use url::Url;
 
-fn get_header_value<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
-    request
-        .headers()
-        .iter()
-        .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
-        .map(|header| header.value.as_str().trim())
+use crate::admin_scope::{RpcCaller, RpcPermission};
+use crate::http::proxy_response::text_response;
+
+enum RpcDispatchOutcome {
+    Unauthorized,
+    Forbidden(RpcPermission),
+    Handled(gpttools_core::rpc::types::JsonRpcResponse),
+}
+
+fn get_header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
         .filter(|value| !value.is_empty())
 }
 
-fn is_json_content_type(request: &Request) -> bool {
-    get_header_value(request, "Content-Type")
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    get_header_value(headers, "Content-Type")
         .and_then(|value| value.split(';').next())
         .map(|value| value.trim().eq_ignore_ascii_case("application/json"))
         .unwrap_or(false)
 }
 
-fn is_loopback_origin(origin: &str) -> bool {
+pub(crate) fn is_loopback_origin(origin: &str) -> bool {
     let Ok(url) = Url::parse(origin) else {
         return false;
     };
@@ -28,7 +36,7 @@ fn is_loopback_origin(origin: &str) -> bool {
     matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "::1"))
 }
 
-fn allow_unauthenticated_rpc() -> bool {
+pub(crate) fn allow_unauthenticated_rpc() -> bool {
     matches!(
         std::env::var("GPTTOOLS_RPC_ALLOW_UNAUTH")
             .ok()
@@ -38,62 +46,79 @@ fn allow_unauthenticated_rpc() -> bool {
     )
 }
 
-pub fn handle_rpc(mut request: Request) {
-    if request.method().as_str() != "POST" {
-        let _ = request.respond(Response::from_string("{}").with_status_code(405));
-        return;
-    }
-    if !is_json_content_type(&request) {
-        let _ = request.respond(Response::from_string("{}").with_status_code(415));
-        return;
+fn empty_json(status: StatusCode) -> Response<Body> {
+    text_response(status, "{}")
+}
+
+fn json_response(body: String) -> Response<Body> {
+    Response::builder()
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| empty_json(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+pub async fn handle_rpc(headers: &HeaderMap, body: Bytes) -> Response<Body> {
+    if !is_json_content_type(headers) {
+        return empty_json(StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
 
-    match get_header_value(&request, "X-Gpttools-Rpc-Token") {
-        Some(token) => {
-            if !crate::rpc_auth_token_matches(token) {
-                let _ = request.respond(Response::from_string("{}").with_status_code(401));
-                return;
-            }
-        }
-        None => {
-            if !allow_unauthenticated_rpc() {
-                let _ = request.respond(Response::from_string("{}").with_status_code(401));
-                return;
-            }
-        }
+    let token = get_header_value(headers, "X-Gpttools-Rpc-Token").map(str::to_string);
+    if token.is_none() && !allow_unauthenticated_rpc() {
+        return empty_json(StatusCode::UNAUTHORIZED);
     }
 
-    if let Some(fetch_site) = get_header_value(&request, "Sec-Fetch-Site") {
+    if let Some(fetch_site) = get_header_value(headers, "Sec-Fetch-Site") {
         if fetch_site.eq_ignore_ascii_case("cross-site") {
-            let _ = request.respond(Response::from_string("{}").with_status_code(403));
-            return;
+            return empty_json(StatusCode::FORBIDDEN);
         }
     }
-    if let Some(origin) = get_header_value(&request, "Origin") {
+    if let Some(origin) = get_header_value(headers, "Origin") {
         if !is_loopback_origin(origin) {
-            let _ = request.respond(Response::from_string("{}").with_status_code(403));
-            return;
+            return empty_json(StatusCode::FORBIDDEN);
         }
     }
 
-    let mut body = String::new();
-    if request.as_reader().read_to_string(&mut body).is_err() {
-        let _ = request.respond(Response::from_string("{}").with_status_code(400));
-        return;
-    }
+    let Ok(body) = std::str::from_utf8(&body) else {
+        return empty_json(StatusCode::BAD_REQUEST);
+    };
     if body.trim().is_empty() {
-        let _ = request.respond(Response::from_string("{}").with_status_code(400));
-        return;
+        return empty_json(StatusCode::BAD_REQUEST);
     }
 
-    let req: gpttools_core::rpc::types::JsonRpcRequest = match serde_json::from_str(&body) {
+    let req: gpttools_core::rpc::types::JsonRpcRequest = match serde_json::from_str(body) {
         Ok(v) => v,
-        Err(_) => {
-            let _ = request.respond(Response::from_string("{}").with_status_code(400));
-            return;
-        }
+        Err(_) => return empty_json(StatusCode::BAD_REQUEST),
     };
-    let resp = crate::handle_request(req);
-    let json = serde_json::to_string(&resp).unwrap_or_else(|_| "{}".to_string());
-    let _ = request.respond(Response::from_string(json));
+    // 中文注释：RPC 处理会同步读写 SQLite 并可能请求上游，放到阻塞线程池，避免卡住 tokio 工作线程拖慢流式转发。
+    // 具名管理 token 也要查库，一并放进阻塞线程里解析。
+    let outcome = tokio::task::spawn_blocking(move || {
+        let caller = match token.as_deref() {
+            Some(token) => match crate::admin_tokens::resolve_rpc_caller(token) {
+                Some(caller) => caller,
+                None => return RpcDispatchOutcome::Unauthorized,
+            },
+            None => RpcCaller::local_full_access("unauthenticated"),
+        };
+        match crate::handle_request_as(&caller, req) {
+            Ok(resp) => RpcDispatchOutcome::Handled(resp),
+            Err(permission) => RpcDispatchOutcome::Forbidden(permission),
+        }
+    })
+    .await;
+    match outcome {
+        Ok(RpcDispatchOutcome::Handled(resp)) => {
+            json_response(serde_json::to_string(&resp).unwrap_or_else(|_| "{}".to_string()))
+        }
+        Ok(RpcDispatchOutcome::Unauthorized) => empty_json(StatusCode::UNAUTHORIZED),
+        Ok(RpcDispatchOutcome::Forbidden(permission)) => {
+            let body = serde_json::json!({
+                "error": "insufficient_scope",
+                "requiredScope": permission.as_str(),
+            });
+            let mut response = json_response(body.to_string());
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            response
+        }
+        Err(_) => empty_json(StatusCode::INTERNAL_SERVER_ERROR),
+    }
 }
@@ -1,10 +1,110 @@
-use crate::http::backend_runtime::{start_backend_server, wake_backend_shutdown};
-use crate::http::proxy_runtime::run_front_proxy;
-
-pub fn start_http(addr: &str) -> std::io::Result<()> {
-    let backend = start_backend_server()?;
-    let result = run_front_proxy(addr, &backend.addr);
-    wake_backend_shutdown(&backend.addr);
-    let _ = backend.join.join();
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::middleware::from_fn;
+use axum::routing::{any, get};
+use axum::{Extension, Router};
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+
+use crate::http::drain::{wait_for_drain_deadline, wait_until_drained};
+use crate::http::event_stream_endpoint::handle_event_stream;
+use crate::http::ip_allowlist::{enforce_ip_allowlist, install_ip_allowlist};
+use crate::http::listen_config::{ListenConfig, ListenOptions};
+use crate::http::proxy_bridge::{run_gateway_server, run_proxy_server};
+use crate::http::request_dispatch::{dispatch_gateway_request, dispatch_request};
+use crate::http::tls_listener::TlsReloader;
+
+const RUNTIME_SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+fn build_router() -> Router {
+    // 中文注释：事件流是常驻 SSE，单独挂路由；其余 RPC/回调/网关请求统一走分发器，与 route_dispatch 的规则保持一致。
+    Router::new()
+        .route("/events/stream", get(handle_event_stream))
+        .fallback(any(dispatch_request))
+        .layer(from_fn(enforce_ip_allowlist))
+}
+
+fn build_gateway_router() -> Router {
+    Router::new()
+        .fallback(any(dispatch_gateway_request))
+        .layer(from_fn(enforce_ip_allowlist))
+}
+
+pub fn start_http(addr: &str) -> io::Result<()> {
+    let config = ListenConfig::resolve(addr, ListenOptions::from_env())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    // 中文注释：证书在启动时就加载一次，路径或内容有误直接启动失败；不这样做会等到首个远端连接才暴露配置错误。
+    let tls = config
+        .tls
+        .clone()
+        .map(TlsReloader::new)
+        .transpose()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?
+        .map(Arc::new);
+    install_ip_allowlist(config.allowlist.clone());
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(io::Error::other)?;
+    let result = runtime.block_on(async {
+        let serve = async {
+            let control = run_proxy_server(&config.control_addr, build_router());
+            match config.gateway_addr.as_deref() {
+                Some(gateway_addr) => {
+                    log::info!(
+                        "gateway listening on {gateway_addr}{}",
+                        if tls.is_some() { " (tls)" } else { "" }
+                    );
+                    let gateway = run_gateway_server(gateway_addr, build_gateway_router(), tls);
+                    // 中文注释：任一端口绑定失败就整体退出，避免控制端口正常而对外端口静默缺失。
+                    tokio::try_join!(control, gateway).map(|_| ())
+                }
+                None => control.await,
+            }
+        };
+        // 中文注释：优雅停机会一直等连接自然结束；排空期限一到就不再等，避免一条卡住的长流拖住停服。
+        let result = tokio::select! {
+            result = serve => result,
+            _ = wait_for_drain_deadline() => Ok(()),
+        };
+        // 中文注释：TLS 端口收到停机信号即退出 accept 循环、不等已建立的连接，这里统一补等在途请求。
+        wait_until_drained().await;
+        result
+    });
+    // 中文注释：先关掉运行时，被截断的响应体随之释放并补记最终请求日志，再等日志队列落库；顺序反过来会丢掉这些日志。
+    runtime.shutdown_timeout(RUNTIME_SHUTDOWN_GRACE);
+    crate::gateway::flush_request_logs();
     result
 }
+
+pub(crate) fn start_one_shot_http(
+    listener: std::net::TcpListener,
+) -> io::Result<thread::JoinHandle<()>> {
+    listener.set_nonblocking(true)?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .map_err(io::Error::other)?;
+    Ok(thread::spawn(move || {
+        runtime.block_on(async move {
+            let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                return;
+            };
+            let Ok((stream, remote_addr)) = listener.accept().await else {
+                return;
+            };
+            let app = build_router().layer(Extension(ConnectInfo(remote_addr)));
+            // 中文注释：one-shot 调用方常在写完请求后先关闭写端再读响应；不开 half_close 时 hyper 读到 EOF 会直接断开连接。
+            let _ = hyper::server::conn::http1::Builder::new()
+                .half_close(true)
+                .serve_connection(TokioIo::new(stream), TowerToHyperService::new(app))
+                .await;
+        });
+        crate::gateway::flush_request_logs();
+    }))
+}
@@ -0,0 +1,224 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use axum::extract::ConnectInfo;
+use axum::{Extension, Router};
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+use crate::http::listen_config::TlsPaths;
+
+const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type FileStamp = Option<(SystemTime, SystemTime)>;
+
+struct LoadedTls {
+    acceptor: TlsAcceptor,
+    stamp: FileStamp,
+}
+
+/// 持有当前生效的证书；证书/私钥文件变化后重新加载，已建立的连接不受影响。
+pub(crate) struct TlsReloader {
+    paths: TlsPaths,
+    current: RwLock<LoadedTls>,
+}
+
+fn file_stamp(paths: &TlsPaths) -> FileStamp {
+    let modified = |path: &std::path::Path| std::fs::metadata(path).ok()?.modified().ok();
+    Some((modified(&paths.cert)?, modified(&paths.key)?))
+}
+
+pub(crate) fn load_server_config(paths: &TlsPaths) -> Result<Arc<ServerConfig>, String> {
+    // 读取 PEM 证书链与私钥，构建 rustls 服务端配置
+    let certs = CertificateDer::pem_file_iter(&paths.cert)
+        .and_then(|items| items.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("read tls cert {}: {err}", paths.cert.display()))?;
+    if certs.is_empty() {
+        return Err(format!("no certificate found in {}", paths.cert.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(&paths.key)
+        .map_err(|err| format!("read tls key {}: {err}", paths.key.display()))?;
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|err| format!("tls protocol setup failed: {err}"))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|err| format!("invalid tls cert/key pair: {err}"))?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+impl TlsReloader {
+    pub(crate) fn new(paths: TlsPaths) -> Result<Self, String> {
+        let stamp = file_stamp(&paths);
+        let config = load_server_config(&paths)?;
+        Ok(Self {
+            paths,
+            current: RwLock::new(LoadedTls {
+                acceptor: TlsAcceptor::from(config),
+                stamp,
+            }),
+        })
+    }
+
+    pub(crate) fn acceptor(&self) -> TlsAcceptor {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .acceptor
+            .clone()
+    }
+
+    pub(crate) fn reload_if_changed(&self) -> bool {
+        // 按文件修改时间判断证书是否轮换
+        let stamp = file_stamp(&self.paths);
+        let mut current = self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if stamp == current.stamp {
+            return false;
+        }
+        current.stamp = stamp;
+        match load_server_config(&self.paths) {
+            Ok(config) => {
+                current.acceptor = TlsAcceptor::from(config);
+                log::info!(
+                    "tls certificate reloaded from {}",
+                    self.paths.cert.display()
+                );
+                true
+            }
+            Err(err) => {
+                // 中文注释：证书轮换常是先写证书再写私钥，中间态会加载失败；保留旧证书继续服务，等下一次文件变化再试。
+                log::warn!("tls reload failed, keeping previous certificate: {err}");
+                false
+            }
+        }
+    }
+}
+
+async fn serve_tls_connection(
+    acceptor: TlsAcceptor,
+    stream: tokio::net::TcpStream,
+    remote_addr: SocketAddr,
+    app: Router,
+) {
+    // 中文注释：握手放进单连接任务并限时；放在 accept 循环里做会让一个慢客户端卡住所有新连接。
+    let tls_stream =
+        match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(err)) => {
+                log::debug!("tls handshake with {remote_addr} failed: {err}");
+                return;
+            }
+            Err(_) => {
+                log::debug!("tls handshake with {remote_addr} timed out");
+                return;
+            }
+        };
+    let app = app.layer(Extension(ConnectInfo(remote_addr)));
+    let _ = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(tls_stream), TowerToHyperService::new(app))
+        .await;
+}
+
+pub(crate) async fn serve_tls_on_listener(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    reloader: Arc<TlsReloader>,
+) -> io::Result<()> {
+    let mut reload_tick = tokio::time::interval(TLS_RELOAD_INTERVAL);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, remote_addr) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        log::warn!("gateway tls accept failed: {err}");
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                tokio::spawn(serve_tls_connection(
+                    reloader.acceptor(),
+                    stream,
+                    remote_addr,
+                    app.clone(),
+                ));
+            }
+            _ = reload_tick.tick() => {
+                reloader.reload_if_changed();
+            }
+            _ = crate::http::proxy_bridge::wait_for_shutdown_signal() => {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_server_config, TlsReloader};
+    use crate::http::listen_config::TlsPaths;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/tls")
+            .join(name)
+    }
+
+    #[test]
+    fn load_server_config_rejects_missing_or_mismatched_files() {
+        assert!(load_server_config(&TlsPaths {
+            cert: fixture("cert.pem"),
+            key: fixture("key.pem"),
+        })
+        .is_ok());
+        assert!(load_server_config(&TlsPaths {
+            cert: fixture("cert.pem"),
+            key: fixture("key_rotated.pem"),
+        })
+        .is_err());
+        assert!(load_server_config(&TlsPaths {
+            cert: fixture("missing.pem"),
+            key: fixture("key.pem"),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn reloader_picks_up_rotated_files_and_keeps_old_cert_on_bad_write() {
+        let dir = std::env::temp_dir().join(format!("gpttools-tls-reload-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let paths = TlsPaths {
+            cert: dir.join("cert.pem"),
+            key: dir.join("key.pem"),
+        };
+        std::fs::copy(fixture("cert.pem"), &paths.cert).expect("copy cert");
+        std::fs::copy(fixture("key.pem"), &paths.key).expect("copy key");
+        let reloader = TlsReloader::new(paths.clone()).expect("initial load");
+        assert!(!reloader.reload_if_changed());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::copy(fixture("cert_rotated.pem"), &paths.cert).expect("rotate cert");
+        std::fs::copy(fixture("key_rotated.pem"), &paths.key).expect("rotate key");
+        assert!(reloader.reload_if_changed());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(&paths.key, "not a key").expect("corrupt key");
+        assert!(!reloader.reload_if_changed());
+        let _ = reloader.acceptor();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
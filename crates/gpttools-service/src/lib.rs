@@ -10,6 +10,8 @@ use std::time::Duration;
 mod http;
 #[path = "storage/storage_helpers.rs"]
 mod storage_helpers;
+#[path = "storage/storage_info.rs"]
+mod storage_info;
 #[path = "account/account_availability.rs"]
 mod account_availability;
 #[path = "account/account_status.rs"]
@@ -20,6 +22,18 @@ mod account_list;
 mod account_delete;
 #[path = "account/account_update.rs"]
 mod account_update;
+#[path = "account/account_import.rs"]
+mod account_import;
+#[path = "account/account_backup.rs"]
+mod account_backup;
+#[path = "account/account_workspaces.rs"]
+mod account_workspaces;
+#[path = "account/account_proxy.rs"]
+mod account_proxy;
+#[path = "admin/admin_scope.rs"]
+mod admin_scope;
+#[path = "admin/admin_tokens.rs"]
+mod admin_tokens;
 #[path = "apikey/apikey_list.rs"]
 mod apikey_list;
 #[path = "apikey/apikey_create.rs"]
@@ -42,10 +56,18 @@ mod auth_login;
 mod auth_callback;
 #[path = "auth/auth_tokens.rs"]
 mod auth_tokens;
+#[path = "auth/auth_token_scheduler.rs"]
+mod auth_token_scheduler;
+#[path = "auth/auth_device.rs"]
+mod auth_device;
+#[path = "auth/auth_session.rs"]
+mod auth_session;
 #[path = "usage/usage_read.rs"]
 mod usage_read;
 #[path = "usage/usage_list.rs"]
 mod usage_list;
+#[path = "usage/usage_history.rs"]
+mod usage_history;
 #[path = "usage/usage_scheduler.rs"]
 mod usage_scheduler;
 #[path = "usage/usage_http.rs"]
@@ -65,9 +87,23 @@ mod gateway;
 mod requestlog_list;
 #[path = "requestlog/requestlog_clear.rs"]
 mod requestlog_clear;
+#[path = "events/event_list.rs"]
+mod event_list;
+#[path = "health/health_check.rs"]
+mod health_check;
+#[path = "daemon/daemon_config.rs"]
+mod daemon_config;
+#[path = "daemon/daemon_log.rs"]
+mod daemon_log;
+#[path = "daemon/daemon_pidfile.rs"]
+mod daemon_pidfile;
+#[path = "daemon/daemon_run.rs"]
+mod daemon_run;
 mod reasoning_effort;
 mod rpc_dispatch;
 
+pub use http::drain::DrainStatus;
+
 pub const DEFAULT_ADDR: &str = "localhost:48760";
 
 static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
@@ -89,18 +125,9 @@ pub fn start_one_shot_server() -> std::io::Result<ServerHandle> {
     if let Err(err) = storage_helpers::initialize_storage() {
         log::warn!("storage startup init skipped: {}", err);
     }
-    let server = tiny_http::Server::http("127.0.0.1:0")
-        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-    let addr = server
-        .server_addr()
-        .to_ip()
-        .map(|a| a.to_string())
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "server addr missing"))?;
-    let join = thread::spawn(move || {
-        if let Some(request) = server.incoming_requests().next() {
-            crate::http::request_dispatch::dispatch_backend_request(request);
-        }
-    });
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?.to_string();
+    let join = http::server::start_one_shot_http(listener)?;
     Ok(ServerHandle { addr, join })
 }
 
@@ -109,17 +136,39 @@ pub fn start_server(addr: &str) -> std::io::Result<()> {
     if let Err(err) = storage_helpers::initialize_storage() {
         log::warn!("storage startup init skipped: {}", err);
     }
+    storage_helpers::ensure_schema_supported().map_err(io::Error::other)?;
     usage_refresh::ensure_usage_polling();
     usage_refresh::ensure_gateway_keepalive();
+    auth_token_scheduler::ensure_token_refresh_scheduler();
+    auth_session::ensure_login_session_sweeper();
     http::server::start_http(addr)
 }
 
+/// 以守护进程方式运行：读取 TOML 配置，接管日志、pidfile 与停机/重载信号，阻塞到服务停止。
+pub fn run_daemon(config_path: &std::path::Path) -> std::io::Result<()> {
+    daemon_run::run_daemon(config_path)
+}
+
+/// 离线把库结构回滚到 `target` 版本（先备份），服务仍在运行时拒绝；不启动任何监听。
+pub fn rollback_storage(
+    config_path: Option<&std::path::Path>,
+    target: &str,
+) -> Result<gpttools_core::rpc::types::StorageRollbackResult, String> {
+    daemon_run::rollback_storage(config_path, target)
+}
+
 pub fn shutdown_requested() -> bool {
     SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
 }
 
 pub fn clear_shutdown_flag() {
     SHUTDOWN_REQUESTED.store(false, Ordering::SeqCst);
+    http::drain::reset_drain();
+}
+
+/// 当前停服排空进度，供停服方轮询。
+pub fn drain_status() -> DrainStatus {
+    http::drain::drain_status()
 }
 
 fn build_rpc_auth_token() -> String {
@@ -160,7 +209,14 @@ pub fn rpc_auth_token_matches(candidate: &str) -> bool {
     constant_time_eq(expected.as_bytes(), candidate.trim().as_bytes())
 }
 
-pub fn request_shutdown(addr: &str) {
+/// 请求停服：不再接收新的网关请求，在途请求最多等 `GPTTOOLS_SHUTDOWN_DRAIN_SECS` 秒（默认 30）。
+pub fn request_shutdown(addr: &str) -> DrainStatus {
+    request_shutdown_within(addr, http::drain::drain_timeout_from_env())
+}
+
+/// 请求停服并指定排空期限；返回发起时的排空进度。
+pub fn request_shutdown_within(addr: &str, timeout: Duration) -> DrainStatus {
+    let status = http::drain::begin_drain(timeout);
     SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
     // Best-effort wakeups for both IPv4 and IPv6 loopback so whichever listener is active exits.
     let _ = send_shutdown_request(addr);
@@ -168,6 +224,7 @@ pub fn request_shutdown(addr: &str) {
         let _ = send_shutdown_request(&format!("127.0.0.1:{port}"));
         let _ = send_shutdown_request(&format!("[::1]:{port}"));
     }
+    status
 }
 
 fn send_shutdown_request(addr: &str) -> std::io::Result<()> {
@@ -188,8 +245,11 @@ fn send_shutdown_request(addr: &str) -> std::io::Result<()> {
     Ok(())
 }
 
-pub(crate) fn handle_request(req: JsonRpcRequest) -> JsonRpcResponse {
-    rpc_dispatch::handle_request(req)
+pub(crate) fn handle_request_as(
+    caller: &admin_scope::RpcCaller,
+    req: JsonRpcRequest,
+) -> Result<JsonRpcResponse, admin_scope::RpcPermission> {
+    rpc_dispatch::handle_request_as(caller, req)
 }
 
 #[cfg(test)]
@@ -203,7 +263,7 @@ mod tests {
             method: "account/login/complete".to_string(),
             params: None,
         };
-        let resp = handle_request(req);
+        let resp = rpc_dispatch::handle_request(req);
         let err = resp
             .result
             .get("error")
@@ -216,7 +276,7 @@ mod tests {
             method: "account/login/complete".to_string(),
             params: Some(serde_json::json!({ "code": "x" })),
         };
-        let resp = handle_request(req);
+        let resp = rpc_dispatch::handle_request(req);
         let err = resp
             .result
             .get("error")
@@ -229,7 +289,7 @@ mod tests {
             method: "account/login/complete".to_string(),
             params: Some(serde_json::json!({ "state": "y" })),
         };
-        let resp = handle_request(req);
+        let resp = rpc_dispatch::handle_request(req);
         let err = resp
             .result
             .get("error")
@@ -1,6 +1,87 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: gpttools-service [--config <path>] [--rollback-to <version>]";
+
+struct Args {
+    config: Option<PathBuf>,
+    rollback_to: Option<String>,
+}
+
+fn args_from_env() -> Result<Args, String> {
+    // 只认 `--config` 与 `--rollback-to`（均支持 `=` 写法），其余配置仍走环境变量
+    let mut args = std::env::args().skip(1);
+    let mut config = None;
+    let mut rollback_to = None;
+    while let Some(arg) = args.next() {
+        if arg == "--config" || arg == "-c" {
+            let path = args
+                .next()
+                .ok_or_else(|| format!("{arg} needs a path\n{USAGE}"))?;
+            config = Some(PathBuf::from(path));
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            config = Some(PathBuf::from(path));
+        } else if arg == "--rollback-to" {
+            let version = args
+                .next()
+                .ok_or_else(|| format!("{arg} needs a migration version\n{USAGE}"))?;
+            rollback_to = Some(version);
+        } else if let Some(version) = arg.strip_prefix("--rollback-to=") {
+            rollback_to = Some(version.to_string());
+        } else if arg == "--help" || arg == "-h" {
+            println!("{USAGE}");
+            std::process::exit(0);
+        } else {
+            return Err(format!("unknown argument: {arg}\n{USAGE}"));
+        }
+    }
+    Ok(Args {
+        config: config.or_else(|| std::env::var_os("GPTTOOLS_CONFIG").map(PathBuf::from)),
+        rollback_to,
+    })
+}
+
 fn main() {
+    let Args {
+        config,
+        rollback_to,
+    } = match args_from_env() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(target) = rollback_to {
+        // 中文注释：回滚只在服务停着时做，完成后直接退出；不这样做运行中的服务会按新表结构查询被删掉的列，下次 initialize 还会把迁移重新执行回去。
+        match gpttools_service::rollback_storage(config.as_deref(), &target) {
+            Ok(result) => {
+                let reverted = if result.reverted.is_empty() {
+                    "-".to_string()
+                } else {
+                    result.reverted.join(", ")
+                };
+                println!("reverted: {reverted}");
+                println!(
+                    "schema: {}",
+                    result.current_version.as_deref().unwrap_or("-")
+                );
+            }
+            Err(err) => {
+                eprintln!("rollback failed: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(config) = config {
+        if let Err(err) = gpttools_service::run_daemon(&config) {
+            eprintln!("service stopped: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
     let addr = std::env::var("GPTTOOLS_SERVICE_ADDR")
         .unwrap_or_else(|_| gpttools_service::DEFAULT_ADDR.to_string());
     println!("gpttools-service listening on {addr}");
@@ -1,6 +1,8 @@
 use crate::storage_helpers::open_storage;
 
 pub(crate) fn clear_request_logs() -> Result<(), String> {
+    // 中文注释：先落库排队中的日志再清空，否则清空后又冒出几条旧请求记录。
+    crate::gateway::flush_request_logs();
     let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
     storage.clear_request_logs().map_err(|e| e.to_string())
 }
@@ -3,6 +3,7 @@ use gpttools_core::rpc::types::RequestLogSummary;
 use crate::storage_helpers::open_storage;
 
 pub(crate) fn read_request_logs(query: Option<String>, limit: Option<i64>) -> Vec<RequestLogSummary> {
+    crate::gateway::flush_request_logs();
     let storage = match open_storage() {
         Some(storage) => storage,
         None => return Vec::new(),
@@ -1,16 +1,21 @@
+use gpttools_core::rpc::methods;
 use gpttools_core::rpc::types::{AccountListResult, JsonRpcRequest, JsonRpcResponse};
 use serde_json::Value;
 
-use crate::{account_delete, account_list, account_update, auth_login, auth_tokens};
+use crate::account_import::AccountImportOptions;
+use crate::{
+    account_backup, account_delete, account_import, account_list, account_proxy,
+    account_update, account_workspaces, auth_login, auth_session, auth_tokens,
+};
 
 pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
     let result = match req.method.as_str() {
-        "account/list" => {
+        methods::ACCOUNT_LIST => {
             let items = account_list::read_accounts();
             let result = AccountListResult { items };
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "account/delete" => {
+        methods::ACCOUNT_DELETE => {
             let account_id = req
                 .params
                 .as_ref()
@@ -22,7 +27,7 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "ok": false, "error": err }),
             }
         }
-        "account/update" => {
+        methods::ACCOUNT_UPDATE => {
             let account_id = req
                 .params
                 .as_ref()
@@ -40,7 +45,101 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "ok": false, "error": err }),
             }
         }
-        "account/login/start" => {
+        methods::ACCOUNT_PROXY_SET => {
+            let params = req.params.as_ref();
+            let account_id = params
+                .and_then(|v| v.get("accountId"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            // 中文注释：proxyUrl 传 null 或空串表示改回直连。
+            let proxy_url = params
+                .and_then(|v| v.get("proxyUrl"))
+                .and_then(|v| v.as_str());
+            match account_proxy::set_account_proxy(account_id, proxy_url) {
+                Ok(proxy_url) => serde_json::json!({ "ok": true, "proxyUrl": proxy_url }),
+                Err(err) => serde_json::json!({ "ok": false, "error": err }),
+            }
+        }
+        methods::ACCOUNT_IMPORT => {
+            let params = req.params.as_ref();
+            let mut contents = Vec::new();
+            let mut documents = Vec::new();
+            // 中文注释：contents 既可以是原始 JSON 文本，也可以直接是对象，前端粘贴与文件读取两种来源都要兼容。
+            let raw_items = params
+                .and_then(|v| v.get("contents"))
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            for item in raw_items {
+                match item {
+                    Value::String(text) => contents.push(text),
+                    other => documents.push(other),
+                }
+            }
+            if let Some(text) = params
+                .and_then(|v| v.get("content"))
+                .and_then(|v| v.as_str())
+            {
+                contents.push(text.to_string());
+            }
+            let path = params.and_then(|v| v.get("path")).and_then(|v| v.as_str());
+            let options = AccountImportOptions {
+                group_name: params
+                    .and_then(|v| v.get("groupName"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                tags: params
+                    .and_then(|v| v.get("tags"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                validate: params
+                    .and_then(|v| v.get("validate"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true),
+            };
+            match account_import::import_accounts(contents, documents, path, options) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_EXPORT => {
+            let passphrase = req
+                .params
+                .as_ref()
+                .and_then(|v| v.get("passphrase"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            match account_backup::export_accounts(passphrase) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_RESTORE => {
+            let params = req.params.as_ref();
+            // 中文注释：bundle 允许直接传导出结果对象，避免前端再做一次 JSON 字符串化。
+            let bundle = match params.and_then(|v| v.get("bundle")) {
+                Some(Value::String(text)) => text.clone(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            let passphrase = params
+                .and_then(|v| v.get("passphrase"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let mode = params
+                .and_then(|v| v.get("mode"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("merge");
+            if bundle.trim().is_empty() {
+                serde_json::json!({ "error": "missing bundle" })
+            } else {
+                match account_backup::restore_accounts(&bundle, passphrase, mode) {
+                    Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                    Err(err) => serde_json::json!({ "error": err }),
+                }
+            }
+        }
+        methods::ACCOUNT_LOGIN_START => {
             let login_type = req
                 .params
                 .as_ref()
@@ -78,6 +177,10 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 .and_then(|v| v.as_str())
                 .map(|v| v.to_string())
                 .and_then(|v| if v.trim().is_empty() { None } else { Some(v) });
+            let workspace_selection = account_workspaces::workspace_selection(
+                &string_array_param(req, "workspaceIds"),
+                bool_param(req, "allWorkspaces"),
+            );
             match auth_login::login_start(
                 login_type,
                 open_browser,
@@ -85,12 +188,60 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 tags,
                 group_name,
                 workspace_id,
+                workspace_selection,
+            ) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_WORKSPACE_LIST => {
+            let account_id = req
+                .params
+                .as_ref()
+                .and_then(|v| v.get("accountId"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            match account_workspaces::list_workspaces(account_id) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_WORKSPACE_ADD => {
+            let account_id = req
+                .params
+                .as_ref()
+                .and_then(|v| v.get("accountId"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            match account_workspaces::add_workspaces(
+                account_id,
+                &string_array_param(req, "workspaceIds"),
+                bool_param(req, "allWorkspaces"),
             ) {
                 Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
                 Err(err) => serde_json::json!({ "error": err }),
             }
         }
-        "account/login/status" => {
+        methods::ACCOUNT_REAUTH => {
+            let params = req.params.as_ref();
+            let account_id = params
+                .and_then(|v| v.get("accountId"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let login_type = params
+                .and_then(|v| v.get("type"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("chatgpt");
+            let open_browser = params
+                .and_then(|v| v.get("openBrowser"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+            match auth_login::reauth_start(account_id, login_type, open_browser) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_LOGIN_STATUS => {
             let login_id = req
                 .params
                 .as_ref()
@@ -100,7 +251,19 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
             let result = auth_login::login_status(login_id);
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "account/login/complete" => {
+        methods::ACCOUNT_LOGIN_CANCEL => {
+            let login_id = req
+                .params
+                .as_ref()
+                .and_then(|v| v.get("loginId"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            match auth_session::cancel_login(login_id) {
+                Ok(_) => serde_json::json!({ "ok": true }),
+                Err(err) => serde_json::json!({ "ok": false, "error": err }),
+            }
+        }
+        methods::ACCOUNT_LOGIN_COMPLETE => {
             let state = req
                 .params
                 .as_ref()
@@ -132,3 +295,25 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
 
     Some(JsonRpcResponse { id: req.id, result })
 }
+
+fn string_array_param(req: &JsonRpcRequest, key: &str) -> Vec<String> {
+    req.params
+        .as_ref()
+        .and_then(|v| v.get(key))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(|v| v.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn bool_param(req: &JsonRpcRequest, key: &str) -> bool {
+    req.params
+        .as_ref()
+        .and_then(|v| v.get(key))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
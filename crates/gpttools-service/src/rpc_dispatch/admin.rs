@@ -0,0 +1,40 @@
+use gpttools_core::rpc::methods;
+use gpttools_core::rpc::types::{AdminTokenListResult, JsonRpcRequest, JsonRpcResponse};
+use serde_json::Value;
+
+use crate::admin_tokens;
+
+pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let result = match req.method.as_str() {
+        methods::ADMIN_TOKEN_LIST => {
+            let result = AdminTokenListResult {
+                items: admin_tokens::read_admin_tokens(),
+            };
+            serde_json::to_value(result).unwrap_or(Value::Null)
+        }
+        methods::ADMIN_TOKEN_CREATE => {
+            let params = req.params.as_ref();
+            let name = params.and_then(|v| v.get("name")).and_then(|v| v.as_str());
+            let scope = params.and_then(|v| v.get("scope")).and_then(|v| v.as_str());
+            match admin_tokens::create_admin_token(name, scope) {
+                Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ADMIN_TOKEN_REVOKE => {
+            let token_id = req
+                .params
+                .as_ref()
+                .and_then(|v| v.get("id"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            match admin_tokens::revoke_admin_token(token_id) {
+                Ok(_) => serde_json::json!({ "ok": true }),
+                Err(err) => serde_json::json!({ "ok": false, "error": err }),
+            }
+        }
+        _ => return None,
+    };
+
+    Some(JsonRpcResponse { id: req.id, result })
+}
@@ -1,3 +1,4 @@
+use gpttools_core::rpc::methods;
 use gpttools_core::rpc::types::{ApiKeyListResult, JsonRpcRequest, JsonRpcResponse};
 use serde_json::Value;
 
@@ -8,13 +9,13 @@ use crate::{
 
 pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
     let result = match req.method.as_str() {
-        "apikey/list" => {
+        methods::APIKEY_LIST => {
             let result = ApiKeyListResult {
                 items: apikey_list::read_api_keys(),
             };
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "apikey/create" => {
+        methods::APIKEY_CREATE => {
             let name = req
                 .params
                 .as_ref()
@@ -49,11 +50,11 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "error": err }),
             }
         }
-        "apikey/models" => match apikey_models::read_model_options() {
+        methods::APIKEY_MODELS => match apikey_models::read_model_options() {
             Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
             Err(err) => serde_json::json!({ "error": err }),
         },
-        "apikey/updateModel" => {
+        methods::APIKEY_UPDATE_MODEL => {
             let key_id = req
                 .params
                 .as_ref()
@@ -88,7 +89,7 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "ok": false, "error": err }),
             }
         }
-        "apikey/delete" => {
+        methods::APIKEY_DELETE => {
             let key_id = req
                 .params
                 .as_ref()
@@ -100,7 +101,7 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "ok": false, "error": err }),
             }
         }
-        "apikey/disable" => {
+        methods::APIKEY_DISABLE => {
             let key_id = req
                 .params
                 .as_ref()
@@ -112,7 +113,7 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
                 Err(err) => serde_json::json!({ "ok": false, "error": err }),
             }
         }
-        "apikey/enable" => {
+        methods::APIKEY_ENABLE => {
             let key_id = req
                 .params
                 .as_ref()
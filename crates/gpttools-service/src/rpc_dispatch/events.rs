@@ -0,0 +1,40 @@
+use gpttools_core::rpc::methods;
+use gpttools_core::rpc::types::{EventListResult, JsonRpcRequest, JsonRpcResponse};
+use gpttools_core::storage::EventQuery;
+use serde_json::Value;
+
+use crate::event_list;
+
+fn read_event_query(params: Option<&Value>) -> EventQuery {
+    let get_str = |key: &str| {
+        params
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+    let get_i64 = |key: &str| params.and_then(|v| v.get(key)).and_then(|v| v.as_i64());
+    EventQuery {
+        account_id: get_str("accountId"),
+        event_type: get_str("type"),
+        since: get_i64("since"),
+        until: get_i64("until"),
+        limit: get_i64("limit").unwrap_or(200),
+    }
+}
+
+pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let result = match req.method.as_str() {
+        methods::EVENTS_LIST => {
+            let query = read_event_query(req.params.as_ref());
+            let result = EventListResult {
+                items: event_list::read_events(&query),
+            };
+            serde_json::to_value(result).unwrap_or(Value::Null)
+        }
+        _ => return None,
+    };
+
+    Some(JsonRpcResponse { id: req.id, result })
+}
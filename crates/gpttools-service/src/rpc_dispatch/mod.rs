@@ -1,15 +1,32 @@
+use gpttools_core::rpc::methods;
 use gpttools_core::rpc::types::{InitializeResult, JsonRpcRequest, JsonRpcResponse};
 use gpttools_core::storage::{now_ts, Event};
 
+use crate::admin_scope::{required_permission, RpcCaller, RpcPermission};
+use crate::admin_tokens::record_admin_audit;
 use crate::storage_helpers;
 
 mod account;
+mod admin;
 mod apikey;
+mod events;
 mod requestlog;
+mod storage;
 mod usage;
 
+fn initialize_response(id: u64) -> JsonRpcResponse {
+    let result = InitializeResult {
+        server_name: "gpttools-service".to_string(),
+        version: gpttools_core::core_version().to_string(),
+    };
+    JsonRpcResponse {
+        id,
+        result: serde_json::to_value(result).unwrap_or(serde_json::Value::Null),
+    }
+}
+
 pub(crate) fn handle_request(req: JsonRpcRequest) -> JsonRpcResponse {
-    if req.method == "initialize" {
+    if req.method == methods::INITIALIZE {
         let _ = storage_helpers::initialize_storage();
         if let Some(storage) = storage_helpers::open_storage() {
             let _ = storage.insert_event(&Event {
@@ -19,14 +36,7 @@ pub(crate) fn handle_request(req: JsonRpcRequest) -> JsonRpcResponse {
                 created_at: now_ts(),
             });
         }
-        let result = InitializeResult {
-            server_name: "gpttools-service".to_string(),
-            version: gpttools_core::core_version().to_string(),
-        };
-        return JsonRpcResponse {
-            id: req.id,
-            result: serde_json::to_value(result).unwrap_or(serde_json::Value::Null),
-        };
+        return initialize_response(req.id);
     }
 
     if let Some(resp) = account::try_handle(&req) {
@@ -41,9 +51,56 @@ pub(crate) fn handle_request(req: JsonRpcRequest) -> JsonRpcResponse {
     if let Some(resp) = requestlog::try_handle(&req) {
         return resp;
     }
+    if let Some(resp) = events::try_handle(&req) {
+        return resp;
+    }
+    if let Some(resp) = storage::try_handle(&req) {
+        return resp;
+    }
+    if let Some(resp) = admin::try_handle(&req) {
+        return resp;
+    }
 
     JsonRpcResponse {
         id: req.id,
         result: serde_json::json!({"error": "unknown_method"}),
     }
 }
+
+fn audit_outcome(resp: &JsonRpcResponse) -> &'static str {
+    let failed = resp.result.get("error").is_some()
+        || resp.result.get("ok").and_then(|v| v.as_bool()) == Some(false);
+    if failed {
+        "error"
+    } else {
+        "ok"
+    }
+}
+
+pub(crate) fn handle_request_as(
+    caller: &RpcCaller,
+    req: JsonRpcRequest,
+) -> Result<JsonRpcResponse, RpcPermission> {
+    // 按调用方权限放行方法；变更类方法无论成败都写审计事件
+    let permission = required_permission(&req.method);
+    let method = req.method.clone();
+    let account_id = req
+        .params
+        .as_ref()
+        .and_then(|v| v.get("accountId"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    if !caller.scope.allows(permission) {
+        record_admin_audit(caller, &method, account_id.as_deref(), "denied");
+        return Err(permission);
+    }
+    // 中文注释：initialize 是握手，只读 token 也要能调；但它会跑迁移并写事件，这些副作用只留给 full 权限，其余调用方只拿版本信息。
+    if req.method == methods::INITIALIZE && !caller.scope.allows(RpcPermission::Admin) {
+        return Ok(initialize_response(req.id));
+    }
+    let resp = handle_request(req);
+    if permission != RpcPermission::Read {
+        record_admin_audit(caller, &method, account_id.as_deref(), audit_outcome(&resp));
+    }
+    Ok(resp)
+}
@@ -1,3 +1,4 @@
+use gpttools_core::rpc::methods;
 use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse, RequestLogListResult};
 use serde_json::Value;
 
@@ -5,7 +6,7 @@ use crate::{requestlog_clear, requestlog_list};
 
 pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
     let result = match req.method.as_str() {
-        "requestlog/list" => {
+        methods::REQUESTLOG_LIST => {
             let query = req
                 .params
                 .as_ref()
@@ -22,7 +23,7 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
             };
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "requestlog/clear" => match requestlog_clear::clear_request_logs() {
+        methods::REQUESTLOG_CLEAR => match requestlog_clear::clear_request_logs() {
             Ok(_) => serde_json::json!({ "ok": true }),
             Err(err) => serde_json::json!({ "ok": false, "error": err }),
         },
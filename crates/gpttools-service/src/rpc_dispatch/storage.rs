@@ -0,0 +1,17 @@
+use gpttools_core::rpc::methods;
+use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse};
+use serde_json::Value;
+
+use crate::storage_info;
+
+pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let result = match req.method.as_str() {
+        methods::STORAGE_INFO => match storage_info::read_storage_info() {
+            Ok(result) => serde_json::to_value(result).unwrap_or(Value::Null),
+            Err(err) => serde_json::json!({ "error": err }),
+        },
+        _ => return None,
+    };
+
+    Some(JsonRpcResponse { id: req.id, result })
+}
@@ -1,11 +1,14 @@
-use gpttools_core::rpc::types::{JsonRpcRequest, JsonRpcResponse, UsageListResult, UsageReadResult};
+use gpttools_core::rpc::methods;
+use gpttools_core::rpc::types::{
+    JsonRpcRequest, JsonRpcResponse, UsageHistoryResult, UsageListResult, UsageReadResult,
+};
 use serde_json::Value;
 
-use crate::{usage_list, usage_read, usage_refresh};
+use crate::{usage_history, usage_list, usage_read, usage_refresh};
 
 pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
     let result = match req.method.as_str() {
-        "account/usage/read" => {
+        methods::ACCOUNT_USAGE_READ => {
             let account_id = req
                 .params
                 .as_ref()
@@ -16,13 +19,26 @@ pub(super) fn try_handle(req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
             };
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "account/usage/list" => {
+        methods::ACCOUNT_USAGE_LIST => {
             let result = UsageListResult {
                 items: usage_list::read_usage_snapshots(),
             };
             serde_json::to_value(result).unwrap_or(Value::Null)
         }
-        "account/usage/refresh" => {
+        methods::ACCOUNT_USAGE_HISTORY => {
+            let params = req.params.as_ref();
+            let account_id = params
+                .and_then(|v| v.get("accountId"))
+                .and_then(|v| v.as_str());
+            let since = params.and_then(|v| v.get("since")).and_then(|v| v.as_i64());
+            let limit = params.and_then(|v| v.get("limit")).and_then(|v| v.as_i64());
+            match usage_history::read_usage_history(account_id, since, limit) {
+                Ok(items) => serde_json::to_value(UsageHistoryResult { items })
+                    .unwrap_or(Value::Null),
+                Err(err) => serde_json::json!({ "error": err }),
+            }
+        }
+        methods::ACCOUNT_USAGE_REFRESH => {
             let account_id = req
                 .params
                 .as_ref()
@@ -1,5 +1,8 @@
 use gpttools_core::storage::Storage;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::sync::{Mutex, OnceLock};
 use rand::RngCore;
 use sha2::{Digest, Sha256};
 
@@ -22,6 +25,21 @@ pub(crate) fn account_key(account_id: &str, tags: Option<&str>) -> String {
     parts.join("::")
 }
 
+pub(crate) const WORKSPACE_KEY_SEPARATOR: &str = "::ws::";
+
+pub(crate) fn workspace_account_key(base_key: &str, workspace_id: &str) -> String {
+    // 同一身份下附加 workspace 条目的唯一标识
+    format!("{base_key}{WORKSPACE_KEY_SEPARATOR}{workspace_id}")
+}
+
+pub(crate) fn token_group_key(account_id: &str) -> &str {
+    // 去掉 workspace 后缀，得到共享 token 的身份标识
+    account_id
+        .split_once(WORKSPACE_KEY_SEPARATOR)
+        .map(|(base, _)| base)
+        .unwrap_or(account_id)
+}
+
 pub(crate) fn hash_platform_key(key: &str) -> String {
     // 对平台 Key 做不可逆哈希，避免明文存储
     let mut hasher = Sha256::new();
@@ -56,8 +74,51 @@ pub(crate) fn generate_key_id() -> String {
     out
 }
 
-pub(crate) fn open_storage() -> Option<Storage> {
-    // 读取数据库路径并打开存储
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+static STORAGE_POOL: OnceLock<Mutex<HashMap<String, Vec<Storage>>>> = OnceLock::new();
+
+/// 连接池借出的存储句柄，离开作用域时自动归还空闲连接。
+pub(crate) struct PooledStorage {
+    storage: Option<Storage>,
+    path: String,
+}
+
+impl Deref for PooledStorage {
+    type Target = Storage;
+
+    fn deref(&self) -> &Storage {
+        self.storage.as_ref().expect("pooled storage taken")
+    }
+}
+
+impl DerefMut for PooledStorage {
+    fn deref_mut(&mut self) -> &mut Storage {
+        self.storage.as_mut().expect("pooled storage taken")
+    }
+}
+
+impl Drop for PooledStorage {
+    fn drop(&mut self) {
+        let Some(storage) = self.storage.take() else {
+            return;
+        };
+        let Ok(mut pool) = storage_pool().lock() else {
+            return;
+        };
+        let idle = pool.entry(std::mem::take(&mut self.path)).or_default();
+        if idle.len() < MAX_IDLE_CONNECTIONS {
+            idle.push(storage);
+        }
+    }
+}
+
+fn storage_pool() -> &'static Mutex<HashMap<String, Vec<Storage>>> {
+    STORAGE_POOL.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(crate) fn open_storage() -> Option<PooledStorage> {
+    // 读取数据库路径，优先复用连接池里的空闲连接
     let path = match std::env::var("GPTTOOLS_DB_PATH") {
         Ok(path) => path,
         Err(_) => {
@@ -65,6 +126,17 @@ pub(crate) fn open_storage() -> Option<Storage> {
             return None;
         }
     };
+    // 中文注释：按路径分池；测试与桌面端切换数据目录时会改 GPTTOOLS_DB_PATH，混用连接会把数据写进旧库。
+    let idle = storage_pool()
+        .lock()
+        .ok()
+        .and_then(|mut pool| pool.get_mut(&path).and_then(Vec::pop));
+    if let Some(storage) = idle {
+        return Some(PooledStorage {
+            storage: Some(storage),
+            path,
+        });
+    }
     if !Path::new(&path).exists() {
         log::warn!("storage path missing: {}", path);
     }
@@ -75,7 +147,10 @@ pub(crate) fn open_storage() -> Option<Storage> {
             return None;
         }
     };
-    Some(storage)
+    Some(PooledStorage {
+        storage: Some(storage),
+        path,
+    })
 }
 
 pub(crate) fn initialize_storage() -> Result<(), String> {
@@ -91,3 +166,11 @@ pub(crate) fn initialize_storage() -> Result<(), String> {
         .map_err(|err| format!("storage init failed: {} ({})", path, err))?;
     Ok(())
 }
+
+pub(crate) fn ensure_schema_supported() -> Result<(), String> {
+    // 中文注释：库被更新版本迁移过时直接拒绝启动；只打 warning 继续跑会让旧代码按旧表结构写坏新库。
+    let Some(storage) = open_storage() else {
+        return Ok(());
+    };
+    storage.check_schema_version().map_err(|err| err.to_string())
+}
@@ -0,0 +1,58 @@
+use gpttools_core::rpc::types::{StorageInfoResult, StorageMigrationItem, StorageRollbackResult};
+use gpttools_core::storage::{now_ts, Event, Storage};
+use std::path::Path;
+
+use crate::storage_helpers::open_storage;
+
+pub(crate) fn read_storage_info() -> Result<StorageInfoResult, String> {
+    // 读取当前库的迁移状态，供排查升级/降级问题
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let info = storage.schema_info().map_err(|err| err.to_string())?;
+    Ok(StorageInfoResult {
+        path: info.path,
+        journal_mode: info.journal_mode,
+        current_version: info.current_version,
+        latest_version: info.latest_version,
+        applied: info
+            .applied
+            .into_iter()
+            .map(|item| StorageMigrationItem {
+                version: item.version,
+                applied_at: item.applied_at,
+                reversible: item.reversible,
+            })
+            .collect(),
+        pending: info.pending,
+        unknown: info.unknown,
+    })
+}
+
+pub(crate) fn rollback_storage(
+    db_path: &Path,
+    target: &str,
+) -> Result<StorageRollbackResult, String> {
+    // 离线把库结构回滚到指定迁移版本，供降级到旧版本前使用；不走 init，回滚掉的迁移不会被重新执行
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("missing target".to_string());
+    }
+    if !db_path.exists() {
+        return Err(format!("storage path missing: {}", db_path.display()));
+    }
+    let storage = Storage::open(db_path)
+        .map_err(|err| format!("open storage failed: {} ({})", db_path.display(), err))?;
+    let reverted = storage
+        .rollback_migrations(target)
+        .map_err(|err| err.to_string())?;
+    let info = storage.schema_info().map_err(|err| err.to_string())?;
+    let _ = storage.insert_event(&Event {
+        account_id: None,
+        event_type: "storage_rollback".to_string(),
+        message: format!("target={target} reverted={}", reverted.join(",")),
+        created_at: now_ts(),
+    });
+    Ok(StorageRollbackResult {
+        reverted,
+        current_version: info.current_version,
+    })
+}
@@ -41,12 +41,11 @@ pub(crate) fn build_workspace_map(storage: &Storage) -> HashMap<String, Option<S
 }
 
 pub(crate) fn resolve_workspace_id_for_account(storage: &Storage, account_id: &str) -> Option<String> {
-    storage.list_accounts().ok().and_then(|accounts| {
-        accounts
-            .into_iter()
-            .find(|account| account.id == account_id)
-            .and_then(|account| workspace_header_for_account(&account))
-    })
+    storage
+        .find_account(account_id)
+        .ok()
+        .flatten()
+        .and_then(|account| workspace_header_for_account(&account))
 }
 
 pub(crate) fn derive_account_meta(token: &Token) -> (Option<String>, Option<String>) {
@@ -82,16 +81,30 @@ pub(crate) fn derive_account_meta(token: &Token) -> (Option<String>, Option<Stri
     (chatgpt_account_id, workspace_id)
 }
 
+pub(crate) fn normalize_plan_type(value: Option<String>) -> Option<String> {
+    clean_header_value(value).map(|v| v.to_ascii_lowercase())
+}
+
+pub(crate) fn sync_account_plan_type(storage: &Storage, account_id: &str, plan_type: Option<String>) {
+    // 中文注释：套餐会随升级/降级变化，以用量接口返回的为准；id_token 里的套餐要等下次刷新才更新，不能当真。
+    let Some(plan_type) = normalize_plan_type(plan_type) else {
+        return;
+    };
+    let Ok(Some(account)) = storage.find_account(account_id) else {
+        return;
+    };
+    if account.plan_type.as_deref() != Some(plan_type.as_str()) {
+        let _ = storage.update_account_plan_type(account_id, Some(&plan_type));
+    }
+}
+
 pub(crate) fn patch_account_meta(
     storage: &Storage,
     account_id: &str,
     chatgpt_account_id: Option<String>,
     workspace_id: Option<String>,
 ) {
-    let Ok(accounts) = storage.list_accounts() else {
-        return;
-    };
-    let Some(mut account) = accounts.into_iter().find(|acc| acc.id == account_id) else {
+    let Ok(Some(mut account)) = storage.find_account(account_id) else {
         return;
     };
 
@@ -115,7 +128,10 @@ pub(crate) fn patch_account_meta(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_workspace_map, clean_header_value, resolve_workspace_id_for_account};
+    use super::{
+        build_workspace_map, clean_header_value, resolve_workspace_id_for_account,
+        sync_account_plan_type,
+    };
     use gpttools_core::storage::{now_ts, Account, Storage};
 
     fn build_account(id: &str, workspace_id: Option<&str>, chatgpt_account_id: Option<&str>) -> Account {
@@ -126,6 +142,8 @@ mod tests {
             chatgpt_account_id: chatgpt_account_id.map(|value| value.to_string()),
             workspace_id: workspace_id.map(|value| value.to_string()),
             group_name: None,
+            plan_type: None,
+            proxy_url: None,
             sort: 0,
             status: "active".to_string(),
             created_at: now_ts(),
@@ -162,5 +180,21 @@ mod tests {
         let workspace_map = build_workspace_map(&storage);
         assert_eq!(workspace_map.get("acc-2").cloned(), Some(Some("chatgpt-2".to_string())));
     }
-}
 
+    #[test]
+    fn sync_account_plan_type_overwrites_changed_plan_type() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage.init().expect("init");
+        let mut account = build_account("acc-3", Some("ws"), Some("cg"));
+        account.plan_type = Some("plus".to_string());
+        storage.insert_account(&account).expect("insert");
+
+        sync_account_plan_type(&storage, "acc-3", Some(" Pro ".to_string()));
+        let loaded = storage.list_accounts().expect("list");
+        assert_eq!(loaded[0].plan_type.as_deref(), Some("pro"));
+
+        sync_account_plan_type(&storage, "acc-3", None);
+        let loaded = storage.list_accounts().expect("list");
+        assert_eq!(loaded[0].plan_type.as_deref(), Some("pro"));
+    }
+}
@@ -0,0 +1,192 @@
+use gpttools_core::rpc::types::{UsageHistoryItem, UsageHistoryPoint, UsageWindowTrend};
+use gpttools_core::storage::{now_ts, UsageSnapshotRecord};
+
+use crate::storage_helpers::open_storage;
+
+const DEFAULT_HISTORY_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+const RESET_DRIFT_TOLERANCE_SECS: i64 = 300;
+const MIN_TREND_SPAN_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Copy)]
+struct WindowSample {
+    captured_at: i64,
+    used_percent: f64,
+    resets_at: Option<i64>,
+}
+
+fn current_window_samples(samples: &[WindowSample]) -> &[WindowSample] {
+    // 中文注释：只取“最近一次重置之后”的连续样本计算速率；
+    // 用量回落或 resets_at 明显变化都说明窗口已重置，跨窗口计算会得到负速率或偏低的速率。
+    let Some(mut start) = samples.len().checked_sub(1) else {
+        return samples;
+    };
+    while start > 0 {
+        let prev = samples[start - 1];
+        let current = samples[start];
+        if prev.used_percent > current.used_percent {
+            break;
+        }
+        let reset_changed = match (prev.resets_at, current.resets_at) {
+            (Some(a), Some(b)) => (a - b).abs() > RESET_DRIFT_TOLERANCE_SECS,
+            _ => false,
+        };
+        if reset_changed {
+            break;
+        }
+        start -= 1;
+    }
+    &samples[start..]
+}
+
+fn compute_window_trend(samples: &[WindowSample]) -> UsageWindowTrend {
+    let Some(latest) = samples.last().copied() else {
+        return UsageWindowTrend::default();
+    };
+    let mut trend = UsageWindowTrend {
+        used_percent: Some(latest.used_percent),
+        resets_at: latest.resets_at,
+        ..UsageWindowTrend::default()
+    };
+    if latest.used_percent >= 100.0 {
+        trend.projected_exhausted_at = Some(latest.captured_at);
+        trend.exhausts_before_reset = Some(true);
+        return trend;
+    }
+
+    let window = current_window_samples(samples);
+    let first = window[0];
+    let span_secs = latest.captured_at - first.captured_at;
+    if span_secs < MIN_TREND_SPAN_SECS {
+        return trend;
+    }
+    let burn_rate = (latest.used_percent - first.used_percent) * 3600.0 / span_secs as f64;
+    trend.burn_rate_per_hour = Some(burn_rate);
+    if burn_rate <= 0.0 {
+        trend.exhausts_before_reset = latest.resets_at.map(|_| false);
+        return trend;
+    }
+    let remaining_secs = (100.0 - latest.used_percent) / burn_rate * 3600.0;
+    let projected = latest.captured_at + remaining_secs.round() as i64;
+    trend.projected_exhausted_at = Some(projected);
+    trend.exhausts_before_reset = latest.resets_at.map(|resets_at| projected < resets_at);
+    trend
+}
+
+fn build_history_item(account_id: String, records: &[UsageSnapshotRecord]) -> UsageHistoryItem {
+    let primary: Vec<WindowSample> = records
+        .iter()
+        .filter_map(|record| {
+            record.used_percent.map(|used_percent| WindowSample {
+                captured_at: record.captured_at,
+                used_percent,
+                resets_at: record.resets_at,
+            })
+        })
+        .collect();
+    let secondary: Vec<WindowSample> = records
+        .iter()
+        .filter_map(|record| {
+            record.secondary_used_percent.map(|used_percent| WindowSample {
+                captured_at: record.captured_at,
+                used_percent,
+                resets_at: record.secondary_resets_at,
+            })
+        })
+        .collect();
+    UsageHistoryItem {
+        account_id,
+        points: records
+            .iter()
+            .map(|record| UsageHistoryPoint {
+                captured_at: record.captured_at,
+                used_percent: record.used_percent,
+                secondary_used_percent: record.secondary_used_percent,
+            })
+            .collect(),
+        primary: compute_window_trend(&primary),
+        secondary: compute_window_trend(&secondary),
+    }
+}
+
+pub(crate) fn read_usage_history(
+    account_id: Option<&str>,
+    since: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<UsageHistoryItem>, String> {
+    // 读取用量历史并计算趋势
+    let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
+    let since = since.unwrap_or_else(|| now_ts() - DEFAULT_HISTORY_WINDOW_SECS);
+    let records = storage
+        .list_usage_snapshots(account_id, since, limit.unwrap_or(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut items = Vec::new();
+    let mut start = 0;
+    while start < records.len() {
+        let current_account = records[start].account_id.clone();
+        let end = records[start..]
+            .iter()
+            .position(|record| record.account_id != current_account)
+            .map(|offset| start + offset)
+            .unwrap_or(records.len());
+        items.push(build_history_item(current_account, &records[start..end]));
+        start = end;
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_window_trend, current_window_samples, WindowSample};
+
+    fn sample(captured_at: i64, used_percent: f64, resets_at: Option<i64>) -> WindowSample {
+        WindowSample {
+            captured_at,
+            used_percent,
+            resets_at,
+        }
+    }
+
+    #[test]
+    fn current_window_starts_after_last_reset() {
+        let samples = vec![
+            sample(0, 80.0, Some(10_000)),
+            sample(3_600, 5.0, Some(30_000)),
+            sample(7_200, 15.0, Some(30_000)),
+        ];
+        let window = current_window_samples(&samples);
+        assert_eq!(window.len(), 2);
+        assert_eq!(window[0].captured_at, 3_600);
+    }
+
+    #[test]
+    fn trend_projects_exhaustion_before_reset() {
+        let samples = vec![
+            sample(0, 10.0, Some(100_000)),
+            sample(3_600, 20.0, Some(100_000)),
+            sample(7_200, 30.0, Some(100_000)),
+        ];
+        let trend = compute_window_trend(&samples);
+        let rate = trend.burn_rate_per_hour.expect("burn rate");
+        assert!((rate - 10.0).abs() < 1e-9);
+        assert_eq!(trend.projected_exhausted_at, Some(7_200 + 7 * 3_600));
+        assert_eq!(trend.exhausts_before_reset, Some(true));
+    }
+
+    #[test]
+    fn trend_without_growth_never_exhausts() {
+        let samples = vec![sample(0, 40.0, Some(50_000)), sample(3_600, 40.0, Some(50_000))];
+        let trend = compute_window_trend(&samples);
+        assert_eq!(trend.burn_rate_per_hour, Some(0.0));
+        assert_eq!(trend.projected_exhausted_at, None);
+        assert_eq!(trend.exhausts_before_reset, Some(false));
+    }
+
+    #[test]
+    fn trend_needs_enough_span_for_rate() {
+        let samples = vec![sample(0, 10.0, None), sample(60, 12.0, None)];
+        let trend = compute_window_trend(&samples);
+        assert_eq!(trend.used_percent, Some(12.0));
+        assert_eq!(trend.burn_rate_per_hour, None);
+    }
+}
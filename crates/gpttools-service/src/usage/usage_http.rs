@@ -1,8 +1,11 @@
 use gpttools_core::usage::usage_endpoint;
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, ClientBuilder};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
-static USAGE_HTTP_CLIENT: std::sync::OnceLock<Client> = std::sync::OnceLock::new();
+static USAGE_HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+static PROXIED_USAGE_HTTP_CLIENTS: OnceLock<Mutex<HashMap<String, Client>>> = OnceLock::new();
 
 #[derive(serde::Deserialize)]
 pub(crate) struct RefreshTokenResponse {
@@ -13,26 +16,63 @@ pub(crate) struct RefreshTokenResponse {
     pub(crate) id_token: Option<String>,
 }
 
+fn usage_http_client_builder() -> ClientBuilder {
+    Client::builder()
+        // 中文注释：轮询链路复用连接池可降低握手开销；不复用会在多账号刷新时放大短连接抖动。
+        .connect_timeout(Duration::from_secs(15))
+        .pool_max_idle_per_host(8)
+        .pool_idle_timeout(Some(Duration::from_secs(60)))
+}
+
 pub(crate) fn usage_http_client() -> &'static Client {
     USAGE_HTTP_CLIENT.get_or_init(|| {
-        Client::builder()
-            // 中文注释：轮询链路复用连接池可降低握手开销；不复用会在多账号刷新时放大短连接抖动。
-            .connect_timeout(Duration::from_secs(15))
-            .pool_max_idle_per_host(8)
-            .pool_idle_timeout(Some(Duration::from_secs(60)))
+        usage_http_client_builder()
             .build()
             .unwrap_or_else(|_| Client::new())
     })
 }
 
+pub(crate) fn usage_http_client_for_proxy(proxy_url: Option<&str>) -> Result<Client, String> {
+    // 按代理地址缓存客户端；未配置代理的账号走共享直连客户端
+    let Some(proxy_url) = proxy_url.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(usage_http_client().clone());
+    };
+    let cache = PROXIED_USAGE_HTTP_CLIENTS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut clients = cache
+        .lock()
+        .map_err(|_| "usage proxy client lock poisoned".to_string())?;
+    if let Some(client) = clients.get(proxy_url) {
+        return Ok(client.clone());
+    }
+    // 中文注释：代理配置无效时必须报错，不能回落直连；否则本该隔离出口的账号会暴露真实出口 IP。
+    let proxy =
+        reqwest::Proxy::all(proxy_url).map_err(|err| format!("invalid account proxy: {err}"))?;
+    let client = usage_http_client_builder()
+        .proxy(proxy)
+        .build()
+        .map_err(|err| format!("build proxied client failed: {err}"))?;
+    clients.insert(proxy_url.to_string(), client.clone());
+    Ok(client)
+}
+
+pub(crate) fn evict_proxied_usage_client(proxy_url: &str) {
+    // 丢掉某个代理地址缓存的客户端及其连接池
+    if let Some(cache) = PROXIED_USAGE_HTTP_CLIENTS.get() {
+        if let Ok(mut clients) = cache.lock() {
+            clients.remove(proxy_url.trim());
+        }
+    }
+}
+
 pub(crate) fn fetch_usage_snapshot(
     base_url: &str,
     bearer: &str,
     workspace_id: Option<&str>,
+    proxy_url: Option<&str>,
 ) -> Result<serde_json::Value, String> {
     // 调用上游用量接口
     let url = usage_endpoint(base_url);
-    let client = usage_http_client();
+    let client = usage_http_client_for_proxy(proxy_url)?;
     let mut req = client
         .get(&url)
         .header("Authorization", format!("Bearer {bearer}"));
@@ -50,9 +90,10 @@ pub(crate) fn refresh_access_token(
     issuer: &str,
     client_id: &str,
     refresh_token: &str,
+    proxy_url: Option<&str>,
 ) -> Result<RefreshTokenResponse, String> {
     // 使用 refresh_token 获取新的 access_token
-    let client = usage_http_client();
+    let client = usage_http_client_for_proxy(proxy_url)?;
     let resp = client
         .post(format!("{issuer}/oauth/token"))
         .header("Content-Type", "application/x-www-form-urlencoded")
@@ -64,17 +105,46 @@ pub(crate) fn refresh_access_token(
         .send()
         .map_err(|e| e.to_string())?;
     if !resp.status().is_success() {
-        return Err(format!(
-            "refresh token failed with status {}",
-            resp.status()
-        ));
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        return Err(match refresh_error_code(&body) {
+            Some(code) => format!("refresh token failed with status {status} ({code})"),
+            None => format!("refresh token failed with status {status}"),
+        });
     }
     resp.json().map_err(|e| e.to_string())
 }
 
+fn refresh_error_code(body: &str) -> Option<String> {
+    // 中文注释：上游错误体可能是 {"error":"invalid_grant"} 或 {"error":{"code":"refresh_token_reused"}}，两种都要兼容。
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?;
+    error
+        .as_str()
+        .or_else(|| error.get("code").and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+pub(crate) fn is_refresh_token_revoked(err: &str) -> bool {
+    // 中文注释：只有上游明确给出失效类错误码才判定 refresh_token 被吊销；网络错误或 5xx 仍按可重试处理。
+    [
+        "refresh_token_expired",
+        "refresh_token_reused",
+        "refresh_token_invalidated",
+        "invalid_grant",
+    ]
+    .iter()
+    .any(|code| err.contains(code))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::usage_http_client;
+    use super::{
+        evict_proxied_usage_client, is_refresh_token_revoked, refresh_error_code,
+        usage_http_client, usage_http_client_for_proxy, PROXIED_USAGE_HTTP_CLIENTS,
+    };
 
     #[test]
     fn usage_http_client_reuses_singleton_instance() {
@@ -82,4 +152,50 @@ mod tests {
         let second = usage_http_client() as *const reqwest::blocking::Client;
         assert_eq!(first, second);
     }
+
+    #[test]
+    fn proxied_usage_client_rejects_invalid_proxy_instead_of_going_direct() {
+        assert!(usage_http_client_for_proxy(None).is_ok());
+        assert!(usage_http_client_for_proxy(Some("socks5://127.0.0.1:1080")).is_ok());
+        assert!(usage_http_client_for_proxy(Some("::not a proxy::")).is_err());
+    }
+
+    #[test]
+    fn evicting_proxy_drops_cached_client() {
+        let proxy = "http://127.0.0.1:18089";
+        let cached = || {
+            PROXIED_USAGE_HTTP_CLIENTS
+                .get()
+                .and_then(|cache| cache.lock().ok().map(|clients| clients.contains_key(proxy)))
+                .unwrap_or(false)
+        };
+        usage_http_client_for_proxy(Some(proxy)).expect("proxied client");
+        assert!(cached());
+        evict_proxied_usage_client(proxy);
+        assert!(!cached());
+    }
+
+    #[test]
+    fn refresh_error_code_supports_string_and_object_shapes() {
+        assert_eq!(
+            refresh_error_code(r#"{"error":"invalid_grant"}"#).as_deref(),
+            Some("invalid_grant")
+        );
+        assert_eq!(
+            refresh_error_code(r#"{"error":{"code":"refresh_token_reused","message":"x"}}"#)
+                .as_deref(),
+            Some("refresh_token_reused")
+        );
+        assert_eq!(refresh_error_code("<html>"), None);
+    }
+
+    #[test]
+    fn revoked_detection_ignores_transient_failures() {
+        assert!(is_refresh_token_revoked(
+            "refresh token failed with status 401 Unauthorized (refresh_token_expired)"
+        ));
+        assert!(!is_refresh_token_revoked(
+            "refresh token failed with status 502 Bad Gateway"
+        ));
+    }
 }
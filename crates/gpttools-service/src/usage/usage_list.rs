@@ -1,4 +1,5 @@
 use gpttools_core::rpc::types::UsageSnapshotResult;
+use std::collections::HashMap;
 
 use crate::storage_helpers::open_storage;
 use crate::usage_read::usage_snapshot_result_from_record;
@@ -13,8 +14,20 @@ pub(crate) fn read_usage_snapshots() -> Vec<UsageSnapshotResult> {
         Ok(items) => items,
         Err(_) => return Vec::new(),
     };
+    let plan_map: HashMap<String, Option<String>> = storage
+        .list_accounts()
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|account| (account.id, account.plan_type))
+                .collect()
+        })
+        .unwrap_or_default();
     items
         .into_iter()
-        .map(usage_snapshot_result_from_record)
+        .map(|snap| {
+            let plan_type = plan_map.get(&snap.account_id).cloned().flatten();
+            usage_snapshot_result_from_record(snap, plan_type)
+        })
         .collect()
 }
@@ -1,12 +1,21 @@
 use gpttools_core::rpc::types::UsageSnapshotResult;
 use gpttools_core::storage::UsageSnapshotRecord;
+use gpttools_core::usage::parse_usage_credits;
 
+use crate::account_availability::evaluate_snapshot;
 use crate::storage_helpers::open_storage;
 
 pub(crate) fn usage_snapshot_result_from_record(
     snap: UsageSnapshotRecord,
+    plan_type: Option<String>,
 ) -> UsageSnapshotResult {
     // 将存储记录转换为 API 返回结构
+    let availability = evaluate_snapshot(&snap);
+    let credits = snap
+        .credits_json
+        .as_deref()
+        .and_then(parse_usage_credits)
+        .unwrap_or_default();
     UsageSnapshotResult {
         account_id: Some(snap.account_id),
         used_percent: snap.used_percent,
@@ -16,6 +25,12 @@ pub(crate) fn usage_snapshot_result_from_record(
         secondary_window_minutes: snap.secondary_window_minutes,
         secondary_resets_at: snap.secondary_resets_at,
         credits_json: snap.credits_json,
+        has_credits: credits.has_credits,
+        credits_unlimited: credits.unlimited,
+        credits_balance: credits.balance,
+        plan_type,
+        availability: Some(availability.label().to_string()),
+        availability_reason: availability.reason().map(|v| v.to_string()),
         captured_at: Some(snap.captured_at),
     }
 }
@@ -25,10 +40,15 @@ pub(crate) fn read_usage_snapshot(account_id: Option<&str>) -> Option<UsageSnaps
     let storage = open_storage()?;
     let snap = match account_id {
         Some(account_id) => storage
-            .latest_usage_snapshots_by_account()
+            .latest_usage_snapshot_for_account(account_id)
             .ok()
-            .and_then(|snaps| snaps.into_iter().find(|snap| snap.account_id == account_id)),
+            .flatten(),
         None => storage.latest_usage_snapshot().ok().flatten(),
     }?;
-    Some(usage_snapshot_result_from_record(snap))
+    let plan_type = storage
+        .find_account(&snap.account_id)
+        .ok()
+        .flatten()
+        .and_then(|account| account.plan_type);
+    Some(usage_snapshot_result_from_record(snap, plan_type))
 }
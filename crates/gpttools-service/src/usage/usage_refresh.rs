@@ -1,12 +1,14 @@
 use gpttools_core::auth::{DEFAULT_CLIENT_ID, DEFAULT_ISSUER};
-use gpttools_core::storage::{Storage, Token};
+use gpttools_core::storage::{now_ts, Storage, Token};
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::thread;
 use std::time::Duration;
 
+use crate::account_proxy::account_proxy_url;
 use crate::storage_helpers::open_storage;
 use crate::usage_account_meta::{
-    build_workspace_map, clean_header_value, derive_account_meta, patch_account_meta,
-    resolve_workspace_id_for_account,
+    build_workspace_map, clean_header_value, derive_account_meta,
+    patch_account_meta, resolve_workspace_id_for_account,
 };
 use crate::usage_http::fetch_usage_snapshot;
 use crate::usage_keepalive::{is_keepalive_error_ignorable, run_gateway_keepalive_once};
@@ -22,6 +24,7 @@ mod usage_refresh_errors;
 
 static USAGE_POLLING_STARTED: std::sync::OnceLock<()> = std::sync::OnceLock::new();
 static GATEWAY_KEEPALIVE_STARTED: std::sync::OnceLock<()> = std::sync::OnceLock::new();
+static LAST_USAGE_REFRESH_AT: AtomicI64 = AtomicI64::new(0);
 
 use self::usage_refresh_errors::{
     mark_usage_unreachable_if_needed, record_usage_refresh_failure, should_retry_with_refresh,
@@ -43,6 +46,11 @@ pub(crate) fn ensure_gateway_keepalive() {
     });
 }
 
+/// 最近一次成功拉到账号用量的时间（秒级时间戳），本进程内还没有成功过时为 None。
+pub(crate) fn last_usage_refresh_at() -> Option<i64> {
+    Some(LAST_USAGE_REFRESH_AT.load(Ordering::Relaxed)).filter(|ts| *ts > 0)
+}
+
 fn usage_polling_loop() {
     // 按间隔循环刷新所有账号用量
     let configured = std::env::var("GPTTOOLS_USAGE_POLL_INTERVAL_SECS").ok();
@@ -90,8 +98,9 @@ pub(crate) fn refresh_usage_for_all_accounts() -> Result<(), String> {
         let workspace_id = workspace_map
             .get(&token.account_id)
             .and_then(|value| value.as_deref());
-        if let Err(err) = refresh_usage_for_token(&storage, &token, workspace_id) {
-            record_usage_refresh_failure(&storage, &token.account_id, &err);
+        match refresh_usage_for_token(&storage, &token, workspace_id) {
+            Ok(()) => LAST_USAGE_REFRESH_AT.store(now_ts(), Ordering::Relaxed),
+            Err(err) => record_usage_refresh_failure(&storage, &token.account_id, &err),
         }
     }
     Ok(())
@@ -100,8 +109,7 @@ pub(crate) fn refresh_usage_for_all_accounts() -> Result<(), String> {
 pub(crate) fn refresh_usage_for_account(account_id: &str) -> Result<(), String> {
     // 刷新单个账号用量
     let storage = open_storage().ok_or_else(|| "storage unavailable".to_string())?;
-    let tokens = storage.list_tokens().map_err(|e| e.to_string())?;
-    let token = match tokens.into_iter().find(|token| token.account_id == account_id) {
+    let token = match storage.find_token(account_id).map_err(|e| e.to_string())? {
         Some(token) => token,
         None => return Ok(()),
     };
@@ -112,6 +120,7 @@ pub(crate) fn refresh_usage_for_account(account_id: &str) -> Result<(), String>
         record_usage_refresh_failure(&storage, &token.account_id, &err);
         return Err(err);
     }
+    LAST_USAGE_REFRESH_AT.store(now_ts(), Ordering::Relaxed);
     Ok(())
 }
 
@@ -147,15 +156,26 @@ fn refresh_usage_for_token(
 
     let resolved_workspace_id = clean_header_value(resolved_workspace_id);
     let bearer = current.access_token.clone();
-
-    match fetch_usage_snapshot(&base_url, &bearer, resolved_workspace_id.as_deref()) {
+    let proxy_url = account_proxy_url(storage, &current.account_id);
+
+    match fetch_usage_snapshot(
+        &base_url,
+        &bearer,
+        resolved_workspace_id.as_deref(),
+        proxy_url.as_deref(),
+    ) {
         Ok(value) => store_usage_snapshot(storage, &current.account_id, value),
         Err(err) if should_retry_with_refresh(&err) => {
             // 中文注释：token 刷新与持久化独立封装，避免轮询流程继续膨胀；
             // 不下沉会让后续 async 迁移时刷新链路与业务编排强耦合，回归范围扩大。
             refresh_and_persist_access_token(storage, &mut current, &issuer, &client_id)?;
             let bearer = current.access_token.clone();
-            match fetch_usage_snapshot(&base_url, &bearer, resolved_workspace_id.as_deref()) {
+            match fetch_usage_snapshot(
+                &base_url,
+                &bearer,
+                resolved_workspace_id.as_deref(),
+                proxy_url.as_deref(),
+            ) {
                 Ok(value) => store_usage_snapshot(storage, &current.account_id, value),
                 Err(err) => {
                     mark_usage_unreachable_if_needed(storage, &current.account_id, &err);
@@ -1,5 +1,6 @@
 use crate::account_availability::{evaluate_snapshot, Availability};
 use crate::account_status::set_account_status;
+use crate::usage_account_meta::sync_account_plan_type;
 use gpttools_core::storage::{now_ts, Storage, UsageSnapshotRecord};
 use gpttools_core::usage::parse_usage_snapshot;
 
@@ -12,6 +13,10 @@ pub(crate) fn apply_status_from_snapshot(
         Availability::Available => {
             set_account_status(storage, &record.account_id, "active", "usage_ok");
         }
+        // 中文注释：未知用量和软阈值都不代表账号坏了，状态保持 active，由选路阶段决定是否接新请求。
+        Availability::Unknown(reason) | Availability::SoftLimited(reason) => {
+            set_account_status(storage, &record.account_id, "active", reason);
+        }
         Availability::Unavailable(reason) => {
             set_account_status(storage, &record.account_id, "inactive", reason);
         }
@@ -40,6 +45,7 @@ pub(crate) fn store_usage_snapshot(
     storage
         .insert_usage_snapshot(&record)
         .map_err(|e| e.to_string())?;
+    sync_account_plan_type(storage, account_id, parsed.plan_type);
     let _ = apply_status_from_snapshot(storage, &record);
     Ok(())
 }
@@ -1,5 +1,6 @@
 use gpttools_core::storage::{now_ts, Storage, Token};
 
+use crate::account_proxy::account_proxy_url;
 use crate::auth_tokens::obtain_api_key;
 use crate::usage_http::refresh_access_token;
 
@@ -9,7 +10,11 @@ pub(crate) fn refresh_and_persist_access_token(
     issuer: &str,
     client_id: &str,
 ) -> Result<(), String> {
-    let refreshed = refresh_access_token(issuer, client_id, &token.refresh_token)?;
+    // 中文注释：刷新与兑换都要走账号自己的出站代理；直连刷新会让同一账号在上游看到两个出口 IP。
+    let proxy_url = account_proxy_url(storage, &token.account_id);
+    let refreshed =
+        refresh_access_token(issuer, client_id, &token.refresh_token, proxy_url.as_deref())?;
+    let previous_refresh_token = token.refresh_token.clone();
     token.access_token = refreshed.access_token;
 
     if let Some(refresh_token) = refreshed.refresh_token {
@@ -18,12 +23,15 @@ pub(crate) fn refresh_and_persist_access_token(
 
     if let Some(id_token) = refreshed.id_token {
         token.id_token = id_token.clone();
-        if let Ok(api_key) = obtain_api_key(issuer, client_id, &id_token) {
+        if let Ok(api_key) = obtain_api_key(issuer, client_id, &id_token, proxy_url.as_deref()) {
             token.api_key_access_token = Some(api_key);
         }
     }
 
     token.last_refresh = now_ts();
     storage.insert_token(token).map_err(|err| err.to_string())?;
+    storage
+        .propagate_refreshed_token(token, &previous_refresh_token)
+        .map_err(|err| err.to_string())?;
     Ok(())
 }
@@ -0,0 +1,146 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+fn post_rpc_as(token: &str, method: &str, params: serde_json::Value) -> (u16, serde_json::Value) {
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let body = serde_json::json!({ "id": 1, "method": method, "params": params }).to_string();
+    let addr = server.addr.as_str();
+    let mut stream = TcpStream::connect(addr).expect("connect server");
+    let request = format!(
+        "POST /rpc HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nX-Gpttools-Rpc-Token: {token}\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).expect("write");
+    stream.shutdown(std::net::Shutdown::Write).ok();
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).expect("read");
+    server.join();
+    let status = buf
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|value| value.parse::<u16>().ok())
+        .expect("status");
+    let body = buf.split("\r\n\r\n").nth(1).unwrap_or("").trim();
+    let value = serde_json::from_str(body).unwrap_or(serde_json::Value::Null);
+    (status, value)
+}
+
+fn create_token(name: &str, scope: &str) -> (String, String) {
+    let master = gpttools_service::rpc_auth_token();
+    let (status, value) = post_rpc_as(
+        master,
+        "admin/token/create",
+        serde_json::json!({ "name": name, "scope": scope }),
+    );
+    assert_eq!(status, 200, "{value}");
+    let result = &value["result"];
+    assert_eq!(result["scope"], scope);
+    let token = result["token"].as_str().expect("token").to_string();
+    assert!(token.starts_with("gta_"));
+    (result["id"].as_str().expect("id").to_string(), token)
+}
+
+#[test]
+fn scoped_admin_tokens_are_enforced_per_method_and_audited() {
+    let dir = std::env::temp_dir().join(format!("gpttools-admin-tokens-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    std::env::set_var("GPTTOOLS_DB_PATH", dir.join("gpttools.db"));
+
+    let (reader_id, reader) = create_token("dashboard", "read_only");
+    let (_, key_admin) = create_token("ci", "key_management");
+
+    let (status, value) = post_rpc_as(&reader, "account/list", serde_json::json!({}));
+    assert_eq!(status, 200, "{value}");
+    assert!(value["result"]["items"].is_array());
+
+    // 只读 token 能握手，但不会触发迁移或写 initialize 事件
+    let (status, value) = post_rpc_as(&reader, "initialize", serde_json::json!({}));
+    assert_eq!(status, 200, "{value}");
+    assert_eq!(value["result"]["server_name"], "gpttools-service");
+    let master = gpttools_service::rpc_auth_token();
+    let (_, value) = post_rpc_as(
+        master,
+        "events/list",
+        serde_json::json!({ "type": "initialize" }),
+    );
+    assert_eq!(value["result"]["items"], serde_json::json!([]), "{value}");
+
+    let (status, value) = post_rpc_as(&reader, "apikey/create", serde_json::json!({}));
+    assert_eq!(status, 403);
+    assert_eq!(value["requiredScope"], "key_management");
+    let (status, _) = post_rpc_as(&reader, "account/export", serde_json::json!({}));
+    assert_eq!(status, 403);
+
+    let (status, value) = post_rpc_as(&key_admin, "apikey/create", serde_json::json!({}));
+    assert_eq!(status, 200, "{value}");
+    assert!(value["result"]["key"].is_string());
+    let (status, value) = post_rpc_as(
+        &key_admin,
+        "account/delete",
+        serde_json::json!({ "accountId": "acc-x" }),
+    );
+    assert_eq!(status, 403);
+    assert_eq!(value["requiredScope"], "account_management");
+    let (status, _) = post_rpc_as(&key_admin, "admin/token/list", serde_json::json!({}));
+    assert_eq!(status, 403);
+
+    // 列表不返回明文或哈希
+    let (_, value) = post_rpc_as(master, "admin/token/list", serde_json::json!({}));
+    let items = value["result"]["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2);
+    assert!(!value.to_string().contains(&reader));
+    assert!(items.iter().all(|item| item.get("tokenHash").is_none()));
+
+    // 每个变更调用（包括被拒绝的）都有审计事件
+    let (_, value) = post_rpc_as(
+        master,
+        "events/list",
+        serde_json::json!({ "type": "admin_audit" }),
+    );
+    let messages: Vec<String> = value["result"]["items"]
+        .as_array()
+        .expect("events")
+        .iter()
+        .map(|item| item["message"].as_str().unwrap_or("").to_string())
+        .collect();
+    assert!(messages.iter().any(|m| m.contains("method=apikey/create")
+        && m.contains("caller=ci")
+        && m.contains("result=ok")));
+    assert!(messages.iter().any(|m| m.contains("method=apikey/create")
+        && m.contains("caller=dashboard")
+        && m.contains("result=denied")));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("method=admin/token/create") && m.contains("caller=local")));
+    assert!(!messages.iter().any(|m| m.contains("method=account/list")));
+
+    let (status, value) = post_rpc_as(
+        master,
+        "admin/token/revoke",
+        serde_json::json!({ "id": reader_id }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(value["result"]["ok"], true);
+    let (status, _) = post_rpc_as(&reader, "account/list", serde_json::json!({}));
+    assert_eq!(status, 401);
+    let (status, _) = post_rpc_as(
+        "gta_not-a-real-token",
+        "account/list",
+        serde_json::json!({}),
+    );
+    assert_eq!(status, 401);
+
+    let (_, value) = post_rpc_as(
+        master,
+        "admin/token/create",
+        serde_json::json!({ "name": "bad", "scope": "root" }),
+    );
+    assert!(value["result"]["error"]
+        .as_str()
+        .unwrap_or("")
+        .contains("invalid scope"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
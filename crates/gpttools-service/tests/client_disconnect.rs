@@ -0,0 +1,139 @@
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{
+    free_local_addr, platform_key, read_until_contains, seed_storage, wait_until_listening,
+};
+
+const SUFFIX: &str = "disconnect";
+const FIRST_EVENT: &str = "event: response.output_text.delta\n\
+data: {\"type\":\"response.output_text.delta\",\"delta\":\"first-delta\"}\n\n";
+
+/// 上游推一个增量后就挂着不结束，直到网关断开连接；断开时通过通道报告。
+fn start_hanging_upstream() -> (String, Receiver<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock upstream");
+    let addr = listener
+        .local_addr()
+        .expect("mock upstream addr")
+        .to_string();
+    let (closed_tx, closed_rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(3)));
+            let mut raw = Vec::new();
+            read_until_contains(&mut stream, &mut raw, "\r\n\r\n");
+            // 中文注释：服务启动后的网关保活也会打到这里，只有真正的 responses 请求才挂起推流。
+            if !String::from_utf8_lossy(&raw).starts_with("POST ") {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                );
+                continue;
+            }
+            let closed_tx = closed_tx.clone();
+            thread::spawn(move || {
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+                    )
+                    .expect("write upstream headers");
+                stream
+                    .write_all(FIRST_EVENT.as_bytes())
+                    .expect("write first event");
+                let _ = stream.flush();
+                // 请求体已读完，之后读到 EOF 只可能是网关关掉了上游连接
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+                let mut buf = [0u8; 1024];
+                if matches!(stream.read(&mut buf), Ok(0)) {
+                    let _ = closed_tx.send(());
+                }
+            });
+        }
+    });
+    (addr, closed_rx)
+}
+
+fn account_inflight_total(addr: &str) -> usize {
+    let mut stream = TcpStream::connect(addr).expect("connect server");
+    let request = format!("GET /metrics HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .expect("write metrics request");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("read metrics");
+    response
+        .lines()
+        .find_map(|line| line.strip_prefix("gpttools_gateway_account_inflight_total "))
+        .and_then(|value| value.trim().parse().ok())
+        .expect("account inflight metric")
+}
+
+fn wait_for_account_inflight(addr: &str, expected: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while account_inflight_total(addr) != expected {
+        assert!(
+            Instant::now() < deadline,
+            "account inflight never reached {expected}"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn client_disconnect_mid_stream_cancels_upstream_and_releases_account() {
+    gpttools_service::clear_shutdown_flag();
+    let dir = std::env::temp_dir().join(format!("gpttools-disconnect-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let db_path = dir.join("gpttools.db");
+    seed_storage(&db_path, SUFFIX);
+    let (upstream_addr, upstream_closed) = start_hanging_upstream();
+    std::env::set_var("GPTTOOLS_DB_PATH", &db_path);
+    std::env::set_var("GPTTOOLS_DISABLE_POLLING", "1");
+    std::env::set_var(
+        "GPTTOOLS_UPSTREAM_BASE_URL",
+        format!("http://{upstream_addr}/backend-api/codex"),
+    );
+
+    let addr = free_local_addr();
+    let server_addr = addr.clone();
+    let server = thread::spawn(move || gpttools_service::start_server(&server_addr));
+    wait_until_listening(&addr);
+
+    let mut stream = TcpStream::connect(&addr).expect("connect server");
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let platform_key = platform_key(SUFFIX);
+    let body = r#"{"model":"gpt-5.3-codex","input":"hello","stream":true}"#;
+    let request = format!(
+        "POST /v1/responses HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nAccept: text/event-stream\r\n\
+         Authorization: Bearer {platform_key}\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).expect("write request");
+    let mut received = Vec::new();
+    read_until_contains(&mut stream, &mut received, "first-delta");
+    assert_eq!(account_inflight_total(&addr), 1);
+
+    // 客户端在流中途断开：上游连接要随之关闭，账号占用也要释放
+    let _ = stream.shutdown(Shutdown::Both);
+    drop(stream);
+    upstream_closed
+        .recv_timeout(Duration::from_secs(5))
+        .expect("upstream request cancelled after client disconnect");
+    wait_for_account_inflight(&addr, 0);
+    assert_eq!(gpttools_service::drain_status().in_flight_requests, 0);
+
+    gpttools_service::request_shutdown(&addr);
+    let result = server.join().expect("server thread");
+    assert!(result.is_ok(), "{result:?}");
+    gpttools_service::clear_shutdown_flag();
+    let _ = std::fs::remove_dir_all(&dir);
+}
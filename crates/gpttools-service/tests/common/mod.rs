@@ -0,0 +1,96 @@
+// 中文注释：每个集成测试是独立的 crate，只用到其中一部分辅助函数，剩下的会被报成未使用。
+#![allow(dead_code)]
+
+use gpttools_core::storage::{now_ts, Account, ApiKey, Storage, Token};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub fn hash_platform_key_for_test(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn free_local_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind probe");
+    listener.local_addr().expect("probe addr").to_string()
+}
+
+pub fn wait_until_listening(addr: &str) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while TcpStream::connect(addr).is_err() {
+        assert!(Instant::now() < deadline, "{addr} never started listening");
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+pub fn read_until_contains(stream: &mut TcpStream, raw: &mut Vec<u8>, needle: &str) {
+    let mut buf = [0u8; 4096];
+    while !String::from_utf8_lossy(raw).contains(needle) {
+        let read = stream.read(&mut buf).expect("read");
+        assert!(
+            read > 0,
+            "connection closed before {needle:?}: {}",
+            String::from_utf8_lossy(raw)
+        );
+        raw.extend_from_slice(&buf[..read]);
+    }
+}
+
+/// `seed_storage` 为该后缀写入的平台 Key。
+pub fn platform_key(suffix: &str) -> String {
+    format!("pk_{suffix}")
+}
+
+/// 建库并写入一个可路由账号、它的 token 和一个平台 Key，各 id 都带上 `suffix`。
+pub fn seed_storage(db_path: &Path, suffix: &str) {
+    let storage = Storage::open(db_path).expect("open db");
+    storage.init().expect("init db");
+    let now = now_ts();
+    storage
+        .insert_account(&Account {
+            id: format!("acc_{suffix}"),
+            label: suffix.to_string(),
+            issuer: "https://auth.openai.com".to_string(),
+            chatgpt_account_id: Some(format!("chatgpt_acc_{suffix}")),
+            workspace_id: None,
+            group_name: None,
+            plan_type: None,
+            proxy_url: None,
+            sort: 0,
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .expect("insert account");
+    storage
+        .insert_token(&Token {
+            account_id: format!("acc_{suffix}"),
+            id_token: String::new(),
+            access_token: format!("access_token_{suffix}"),
+            refresh_token: String::new(),
+            api_key_access_token: Some(format!("api_access_token_{suffix}")),
+            last_refresh: now,
+        })
+        .expect("insert token");
+    storage
+        .insert_api_key(&ApiKey {
+            id: format!("gk_{suffix}"),
+            name: Some(suffix.to_string()),
+            model_slug: None,
+            reasoning_effort: None,
+            client_type: "codex".to_string(),
+            protocol_type: "openai_compat".to_string(),
+            auth_scheme: "authorization_bearer".to_string(),
+            upstream_base_url: None,
+            static_headers_json: None,
+            key_hash: hash_platform_key_for_test(&platform_key(suffix)),
+            status: "active".to_string(),
+            created_at: now,
+            last_used_at: None,
+        })
+        .expect("insert api key");
+}
@@ -0,0 +1,113 @@
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{free_local_addr, wait_until_listening};
+
+fn wait_for(what: &str, mut check: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !check() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn send_signal(child: &Child, signal: &str) {
+    let status = Command::new("kill")
+        .arg(format!("-{signal}"))
+        .arg(child.id().to_string())
+        .status()
+        .expect("run kill");
+    assert!(status.success());
+}
+
+fn log_messages(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).expect("json log line");
+            value["msg"].as_str().unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn daemon_writes_pidfile_reloads_on_sighup_and_stops_on_sigterm() {
+    let dir = std::env::temp_dir().join(format!("gpttools-daemon-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let addr = free_local_addr();
+    let config_path = dir.join("service.toml");
+    let write_config = |extra: &str| {
+        let config = format!(
+            "addr = \"{addr}\"\npid_file = \"run/gpttools.pid\"\nshutdown_timeout_secs = 5\n\n\
+             [log]\nfile = \"logs/service.log\"\n\n\
+             [env]\nGPTTOOLS_DB_PATH = \"{}\"\nGPTTOOLS_DISABLE_POLLING = \"1\"\n{extra}",
+            dir.join("gpttools.db").display()
+        );
+        std::fs::write(&config_path, config).expect("write config");
+    };
+    write_config("");
+    let pid_path = dir.join("run/gpttools.pid");
+    let log_path = dir.join("logs/service.log");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gpttools-service"))
+        .arg("--config")
+        .arg(&config_path)
+        .env_remove("GPTTOOLS_SERVICE_ADDR")
+        .spawn()
+        .expect("spawn daemon");
+    wait_until_listening(&addr);
+    let pid = std::fs::read_to_string(&pid_path).expect("pidfile");
+    assert_eq!(pid.trim(), child.id().to_string());
+
+    // 第二个实例看到存活的 pidfile 后拒绝启动
+    let second = Command::new(env!("CARGO_BIN_EXE_gpttools-service"))
+        .arg("--config")
+        .arg(&config_path)
+        .output()
+        .expect("spawn second instance");
+    assert!(!second.status.success());
+    assert!(String::from_utf8_lossy(&second.stderr).contains("already running"));
+
+    // SIGHUP 重新读取配置；只在启动时生效的改动会提示重启
+    write_config("GPTTOOLS_RPC_TOKEN = \"changed\"\nGPTTOOLS_RESERVE_GROUPS = \"vip\"\n");
+    send_signal(&child, "HUP");
+    wait_for("reload log", || {
+        log_messages(&log_path)
+            .iter()
+            .any(|msg| msg.starts_with("config reloaded"))
+    });
+    assert!(log_messages(&log_path)
+        .iter()
+        .any(|msg| msg.contains("need a restart") && msg.contains("env.GPTTOOLS_RPC_TOKEN")));
+
+    send_signal(&child, "TERM");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().expect("wait daemon") {
+            break status;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "daemon did not stop after SIGTERM"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert!(status.success(), "daemon exit status: {status}");
+    assert!(!pid_path.exists());
+    let messages = log_messages(&log_path);
+    assert!(messages
+        .iter()
+        .any(|msg| msg.starts_with("received SIGTERM")));
+    assert_eq!(
+        messages.last().map(String::as_str),
+        Some("gpttools-service stopped")
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
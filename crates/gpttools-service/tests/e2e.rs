@@ -1,9 +1,12 @@
 use gpttools_core::rpc::types::JsonRpcRequest;
-use gpttools_core::storage::Storage;
+use gpttools_core::storage::{now_ts, Account, Storage, Token, UsageSnapshotRecord};
 use std::fs;
 use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::net::{TcpListener, TcpStream};
 use std::path::PathBuf;
+use std::sync::Mutex;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
 
 struct EnvGuard {
     key: &'static str,
@@ -43,8 +46,44 @@ fn post_rpc(addr: &str, body: &str) -> String {
     buf
 }
 
+fn rpc_result(addr: &str, id: u64, method: &str, params: serde_json::Value) -> serde_json::Value {
+    let req = JsonRpcRequest {
+        id,
+        method: method.to_string(),
+        params: Some(params),
+    };
+    let json = serde_json::to_string(&req).expect("serialize");
+    let buf = post_rpc(addr, &json);
+    let body = buf.split("\r\n\r\n").nth(1).unwrap_or("");
+    let value: serde_json::Value = serde_json::from_str(body).expect("parse response");
+    value["result"].clone()
+}
+
+/// 模拟 accounts/check：对任何请求都返回同一份 workspace 列表。
+fn start_accounts_check_upstream(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock upstream");
+    let addr = listener.local_addr().expect("mock addr").to_string();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    addr
+}
+
 #[test]
 fn e2e_initialize_writes_event() {
+    let _lock = ENV_LOCK.lock().expect("lock env");
     let mut dir = std::env::temp_dir();
     dir.push(format!("gpttools-e2e-{}", std::process::id()));
     let _ = fs::create_dir_all(&dir);
@@ -67,3 +106,345 @@ fn e2e_initialize_writes_event() {
     let count = storage.event_count().expect("count events");
     assert!(count >= 1);
 }
+
+#[test]
+fn e2e_storage_info_reports_applied_migrations() {
+    let _lock = ENV_LOCK.lock().expect("lock env");
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-e2e-storage-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init schema");
+
+    let _guard = EnvGuard::set("GPTTOOLS_DB_PATH", db_path.to_string_lossy().as_ref());
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let req = JsonRpcRequest {
+        id: 2,
+        method: "storage/info".to_string(),
+        params: None,
+    };
+    let json = serde_json::to_string(&req).expect("serialize");
+    let buf = post_rpc(&server.addr, &json);
+    let body = buf.split("\r\n\r\n").nth(1).unwrap_or("");
+    let value: serde_json::Value = serde_json::from_str(body).expect("parse response");
+    let result = value.get("result").expect("result");
+    let latest = result
+        .get("latestVersion")
+        .and_then(|v| v.as_str())
+        .expect("latest");
+    assert_eq!(
+        result.get("currentVersion").and_then(|v| v.as_str()),
+        Some(latest)
+    );
+    assert_eq!(
+        result.get("journalMode").and_then(|v| v.as_str()),
+        Some("wal")
+    );
+    assert!(result
+        .get("pending")
+        .and_then(|v| v.as_array())
+        .expect("pending")
+        .is_empty());
+    let applied = result
+        .get("applied")
+        .and_then(|v| v.as_array())
+        .expect("applied");
+    assert!(applied
+        .iter()
+        .any(|item| item.get("version").and_then(|v| v.as_str()) == Some("001_init")));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn e2e_usage_history_limits_points_per_account() {
+    let _lock = ENV_LOCK.lock().expect("lock env");
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-e2e-usage-history-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init schema");
+    let now = now_ts();
+    // 忙碌账号连续刷新 10 次，安静账号只有 2 个采样点
+    let samples = (0..10)
+        .map(|idx| ("acc-busy", 10.0 + idx as f64, now - 3600 + idx * 300))
+        .chain([
+            ("acc-quiet", 5.0, now - 7200),
+            ("acc-quiet", 8.0, now - 1800),
+        ]);
+    for (account_id, used, captured_at) in samples {
+        storage
+            .insert_usage_snapshot(&UsageSnapshotRecord {
+                account_id: account_id.to_string(),
+                used_percent: Some(used),
+                window_minutes: Some(300),
+                resets_at: Some(now + 3600),
+                secondary_used_percent: None,
+                secondary_window_minutes: None,
+                secondary_resets_at: None,
+                credits_json: None,
+                captured_at,
+            })
+            .expect("insert snapshot");
+    }
+
+    let _guard = EnvGuard::set("GPTTOOLS_DB_PATH", db_path.to_string_lossy().as_ref());
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let history = rpc_result(
+        &server.addr,
+        3,
+        "account/usage/history",
+        serde_json::json!({ "limit": 3 }),
+    );
+    let items = history["items"].as_array().expect("items");
+    let points = |account_id: &str| -> Vec<f64> {
+        items
+            .iter()
+            .find(|item| item["accountId"] == account_id)
+            .and_then(|item| item["points"].as_array())
+            .expect("history item")
+            .iter()
+            .filter_map(|point| point["usedPercent"].as_f64())
+            .collect()
+    };
+    assert_eq!(points("acc-busy"), vec![17.0, 18.0, 19.0]);
+    assert_eq!(points("acc-quiet"), vec![5.0, 8.0]);
+    let busy = items
+        .iter()
+        .find(|item| item["accountId"] == "acc-busy")
+        .expect("busy item");
+    assert!(busy["primary"]["burnRatePerHour"]
+        .as_f64()
+        .is_some_and(|rate| rate > 0.0));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn e2e_account_workspace_list_and_add() {
+    let _lock = ENV_LOCK.lock().expect("lock env");
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-e2e-workspaces-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init schema");
+    let now = now_ts();
+    storage
+        .insert_account(&Account {
+            id: "user-1".to_string(),
+            label: "one@example.com".to_string(),
+            issuer: "https://auth.openai.com".to_string(),
+            chatgpt_account_id: Some("acc-personal".to_string()),
+            workspace_id: Some("acc-personal".to_string()),
+            group_name: Some("team".to_string()),
+            plan_type: Some("plus".to_string()),
+            proxy_url: None,
+            sort: 3,
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .expect("insert account");
+    storage
+        .insert_token(&Token {
+            account_id: "user-1".to_string(),
+            id_token: "id".to_string(),
+            access_token: "access-1".to_string(),
+            refresh_token: "refresh-1".to_string(),
+            api_key_access_token: None,
+            last_refresh: now,
+        })
+        .expect("insert token");
+
+    let upstream = start_accounts_check_upstream(
+        r#"{"accounts":{"acc-personal":{"account":{"account_id":"acc-personal","structure":"personal"}},"org-team":{"account":{"account_id":"org-team","name":"Team","plan_type":"team"}}},"account_ordering":["acc-personal","org-team"]}"#,
+    );
+    let _db = EnvGuard::set("GPTTOOLS_DB_PATH", db_path.to_string_lossy().as_ref());
+    let _base = EnvGuard::set("GPTTOOLS_USAGE_BASE_URL", &format!("http://{upstream}"));
+
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let listed = rpc_result(
+        &server.addr,
+        1,
+        "account/workspace/list",
+        serde_json::json!({ "accountId": "user-1" }),
+    );
+    let items = listed["items"].as_array().expect("items");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["workspaceId"], "acc-personal");
+    assert_eq!(items[0]["isPersonal"], true);
+    assert_eq!(items[0]["accountId"], "user-1");
+    assert_eq!(items[1]["workspaceId"], "org-team");
+    assert!(items[1]["accountId"].is_null());
+
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let added = rpc_result(
+        &server.addr,
+        2,
+        "account/workspace/add",
+        serde_json::json!({ "accountId": "user-1", "workspaceIds": ["org-team"] }),
+    );
+    let items = added["items"].as_array().expect("items");
+    assert_eq!(items[1]["accountId"], "user-1::ws::org-team");
+
+    let entry = storage
+        .find_account("user-1::ws::org-team")
+        .expect("find entry")
+        .expect("entry exists");
+    assert_eq!(entry.chatgpt_account_id.as_deref(), Some("org-team"));
+    assert_eq!(entry.plan_type.as_deref(), Some("team"));
+    assert_eq!(entry.group_name.as_deref(), Some("team"));
+    let token = storage
+        .find_token("user-1::ws::org-team")
+        .expect("find token")
+        .expect("token exists");
+    assert_eq!(token.refresh_token, "refresh-1");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn e2e_account_workspace_list_goes_through_account_proxy() {
+    let _lock = ENV_LOCK.lock().expect("lock env");
+    let mut dir = std::env::temp_dir();
+    dir.push(format!(
+        "gpttools-e2e-workspaces-proxy-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init schema");
+
+    // 中文注释：mock 同时充当 HTTP 代理；上游地址故意不可解析，只有经账号代理出站才能拿到列表。
+    let proxy = start_accounts_check_upstream(
+        r#"{"accounts":{"acc-personal":{"account":{"account_id":"acc-personal","structure":"personal"}}}}"#,
+    );
+    let now = now_ts();
+    storage
+        .insert_account(&Account {
+            id: "user-proxy".to_string(),
+            label: "proxy@example.com".to_string(),
+            issuer: "https://auth.openai.com".to_string(),
+            chatgpt_account_id: Some("acc-personal".to_string()),
+            workspace_id: Some("acc-personal".to_string()),
+            group_name: None,
+            plan_type: None,
+            proxy_url: Some(format!("http://{proxy}")),
+            sort: 0,
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .expect("insert account");
+    storage
+        .insert_token(&Token {
+            account_id: "user-proxy".to_string(),
+            id_token: "id".to_string(),
+            access_token: "access-proxy".to_string(),
+            refresh_token: "refresh-proxy".to_string(),
+            api_key_access_token: None,
+            last_refresh: now,
+        })
+        .expect("insert token");
+    let _db = EnvGuard::set("GPTTOOLS_DB_PATH", db_path.to_string_lossy().as_ref());
+    let _base = EnvGuard::set("GPTTOOLS_USAGE_BASE_URL", "http://accounts-check.invalid");
+
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let listed = rpc_result(
+        &server.addr,
+        1,
+        "account/workspace/list",
+        serde_json::json!({ "accountId": "user-proxy" }),
+    );
+    let items = listed["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "{listed}");
+    assert_eq!(items[0]["workspaceId"], "acc-personal");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn e2e_offline_rollback_across_proxy_migration_stays_reverted() {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("gpttools-e2e-rollback-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init schema");
+    let latest = storage.schema_info().expect("schema info").latest_version;
+    drop(storage);
+
+    let probe = TcpListener::bind("127.0.0.1:0").expect("probe addr");
+    let addr = probe.local_addr().expect("probe addr").to_string();
+    let config_path = dir.join("service.toml");
+    fs::write(
+        &config_path,
+        format!(
+            "addr = \"{addr}\"\n\n[env]\nGPTTOOLS_DB_PATH = \"{}\"\n",
+            db_path.display()
+        ),
+    )
+    .expect("write config");
+    let rollback = |target: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_gpttools-service"))
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--rollback-to")
+            .arg(target)
+            .output()
+            .expect("run rollback")
+    };
+
+    // 服务还在监听时拒绝回滚，库不动
+    let refused = rollback("021_tokens_refresh_token_index");
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("still listening"));
+    let info = Storage::open(&db_path)
+        .and_then(|storage| storage.schema_info())
+        .expect("schema info");
+    assert_eq!(info.current_version.as_deref(), Some(latest.as_str()));
+    drop(probe);
+
+    // 回滚跨过 022（删除 accounts.proxy_url）后进程直接退出，不会把迁移重新执行回去
+    let output = rollback("021_tokens_refresh_token_index");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("022_account_proxy_url"), "{stdout}");
+    assert!(
+        stdout.contains("schema: 021_tokens_refresh_token_index"),
+        "{stdout}"
+    );
+
+    let storage = Storage::open(&db_path).expect("reopen db");
+    let info = storage.schema_info().expect("schema info");
+    assert_eq!(
+        info.current_version.as_deref(),
+        Some("021_tokens_refresh_token_index")
+    );
+    assert_eq!(
+        info.pending,
+        vec!["022_account_proxy_url".to_string(), latest.clone()]
+    );
+    // 中文注释：当前代码查询账号会带上 proxy_url，查询失败说明列确实被删掉了。
+    assert!(storage.list_accounts().is_err());
+    drop(storage);
+
+    // 不可逆的迁移挡住回滚时整体拒绝
+    let refused = rollback("001_init");
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("cannot be rolled back"));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -11,6 +11,8 @@ fn resolve_openai_bearer_token_uses_cached_storage_value() {
         chatgpt_account_id: None,
         workspace_id: None,
         group_name: None,
+        plan_type: None,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
@@ -56,6 +58,8 @@ fn resolve_openai_bearer_token_falls_back_to_access_token_when_exchange_fails()
         chatgpt_account_id: None,
         workspace_id: None,
         group_name: None,
+        plan_type: None,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
@@ -11,6 +11,8 @@ fn failover_on_missing_usage() {
         chatgpt_account_id: None,
         workspace_id: None,
         group_name: None,
+        plan_type: None,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
@@ -202,6 +202,31 @@ fn start_mock_upstream_sequence(
     (addr.to_string(), rx, join)
 }
 
+fn start_mock_upstream_sse_sequence(
+    bodies: Vec<String>,
+) -> (String, Receiver<CapturedUpstreamRequest>, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock upstream");
+    let addr = listener.local_addr().expect("mock upstream addr");
+    let (tx, rx) = mpsc::channel();
+
+    let join = thread::spawn(move || {
+        for body in bodies {
+            let (mut stream, _) = listener.accept().expect("accept upstream");
+            let captured = read_http_request_once(&mut stream);
+            let _ = tx.send(captured);
+
+            let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n";
+            stream.write_all(header.as_bytes()).expect("write upstream status");
+            stream
+                .write_all(body.as_bytes())
+                .expect("write upstream response body");
+            let _ = stream.flush();
+        }
+    });
+
+    (addr.to_string(), rx, join)
+}
+
 struct TestServer {
     addr: String,
     join: Option<thread::JoinHandle<()>>,
@@ -385,6 +410,8 @@ fn gateway_claude_protocol_end_to_end_uses_codex_headers() {
             chatgpt_account_id: Some("chatgpt_acc_test".to_string()),
             workspace_id: None,
             group_name: None,
+            plan_type: None,
+            proxy_url: None,
             sort: 0,
             status: "active".to_string(),
             created_at: now,
@@ -556,6 +583,8 @@ fn gateway_request_log_keeps_only_final_result_for_multi_attempt_flow() {
                 chatgpt_account_id: Some(format!("chatgpt_acc_final_{index}")),
                 workspace_id: None,
                 group_name: None,
+                plan_type: None,
+                proxy_url: None,
                 sort: index,
                 status: "active".to_string(),
                 created_at: now,
@@ -638,3 +667,163 @@ fn gateway_request_log_keeps_only_final_result_for_multi_attempt_flow() {
     assert!(trace_text.contains("event=ATTEMPT_RESULT"));
     assert!(trace_text.contains("event=REQUEST_FINAL"));
 }
+
+#[test]
+fn gateway_fails_over_when_stream_fails_before_first_token() {
+    let _lock = ENV_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut dir = std::env::temp_dir();
+    dir.push(format!(
+        "gpttools-gateway-stream-failover-{}",
+        std::process::id()
+    ));
+    let _ = fs::create_dir_all(&dir);
+    let db_path: PathBuf = dir.join("gpttools.db");
+    let trace_log_path: PathBuf = dir.join("gateway-trace.log");
+    let _ = fs::remove_file(&trace_log_path);
+
+    let _db_guard = EnvGuard::set("GPTTOOLS_DB_PATH", db_path.to_string_lossy().as_ref());
+
+    let failed_stream = concat!(
+        "event: response.created\n",
+        "data: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_failed\"}}\n\n",
+        "event: response.failed\n",
+        "data: {\"type\":\"response.failed\",\"response\":{\"error\":{\"message\":\"server overloaded\"}}}\n\n",
+    );
+    let ok_stream = concat!(
+        "event: response.output_text.delta\n",
+        "data: {\"type\":\"response.output_text.delta\",\"delta\":\"second-account\"}\n\n",
+        "event: response.completed\n",
+        "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_ok\"}}\n\n",
+    );
+    let (upstream_addr, upstream_rx, upstream_join) = start_mock_upstream_sse_sequence(vec![
+        failed_stream.to_string(),
+        ok_stream.to_string(),
+    ]);
+    let upstream_base = format!("http://{upstream_addr}/backend-api/codex");
+    let _upstream_guard = EnvGuard::set("GPTTOOLS_UPSTREAM_BASE_URL", &upstream_base);
+
+    let storage = Storage::open(&db_path).expect("open db");
+    storage.init().expect("init db");
+    let now = now_ts();
+
+    for index in 1..=2 {
+        storage
+            .insert_account(&Account {
+                id: format!("acc_stream_{index}"),
+                label: format!("stream-{index}"),
+                issuer: "https://auth.openai.com".to_string(),
+                chatgpt_account_id: Some(format!("chatgpt_acc_stream_{index}")),
+                workspace_id: None,
+                group_name: None,
+                plan_type: None,
+                proxy_url: None,
+                sort: index,
+                status: "active".to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .expect("insert account");
+        storage
+            .insert_token(&Token {
+                account_id: format!("acc_stream_{index}"),
+                id_token: String::new(),
+                access_token: format!("access_token_{index}"),
+                refresh_token: String::new(),
+                api_key_access_token: Some(format!("api_access_token_{index}")),
+                last_refresh: now,
+            })
+            .expect("insert token");
+    }
+
+    let platform_key = "pk_stream_failover";
+    storage
+        .insert_api_key(&ApiKey {
+            id: "gk_stream_failover".to_string(),
+            name: Some("stream-failover".to_string()),
+            model_slug: None,
+            reasoning_effort: None,
+            client_type: "codex".to_string(),
+            protocol_type: "openai_compat".to_string(),
+            auth_scheme: "authorization_bearer".to_string(),
+            upstream_base_url: None,
+            static_headers_json: None,
+            key_hash: hash_platform_key_for_test(platform_key),
+            status: "active".to_string(),
+            created_at: now,
+            last_used_at: None,
+        })
+        .expect("insert api key");
+
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+    let body = r#"{"model":"gpt-5.3-codex","input":"hello","stream":true}"#;
+    let auth = format!("Bearer {platform_key}");
+    let (status, response_body) = post_http_raw(
+        &server.addr,
+        "/v1/responses",
+        body,
+        &[
+            ("Content-Type", "application/json"),
+            ("Accept", "text/event-stream"),
+            ("Authorization", auth.as_str()),
+        ],
+    );
+    server.join();
+    assert_eq!(status, 200, "gateway response: {response_body}");
+    assert!(response_body.contains("second-account"), "{response_body}");
+    assert!(!response_body.contains("response.failed"), "{response_body}");
+
+    let first = upstream_rx
+        .recv_timeout(Duration::from_secs(2))
+        .expect("receive first upstream request");
+    let second = upstream_rx
+        .recv_timeout(Duration::from_secs(2))
+        .expect("receive second upstream request");
+    upstream_join.join().expect("join upstream");
+    assert_eq!(
+        first.headers.get("chatgpt-account-id").map(String::as_str),
+        Some("chatgpt_acc_stream_1")
+    );
+    assert_eq!(
+        second.headers.get("chatgpt-account-id").map(String::as_str),
+        Some("chatgpt_acc_stream_2")
+    );
+
+    // 预读失败的那次尝试只记一条 502，不会先记 200；整个请求也只落一条最终日志
+    let logs = storage
+        .list_request_logs(Some("key:gk_stream_failover"), 20)
+        .expect("list logs");
+    let final_logs = logs
+        .iter()
+        .filter(|item| item.request_path == "/v1/responses")
+        .collect::<Vec<_>>();
+    assert_eq!(final_logs.len(), 1, "logs: {final_logs:#?}");
+    assert_eq!(final_logs[0].status_code, Some(200));
+
+    let trace_text = fs::read_to_string(&trace_log_path).expect("read trace log");
+    let first_attempts = trace_text
+        .lines()
+        .filter(|line| {
+            line.contains("event=ATTEMPT_RESULT") && line.contains("account_id=acc_stream_1")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(first_attempts.len(), 1, "{trace_text}");
+    assert!(
+        first_attempts[0].contains("status=502")
+            && first_attempts[0].contains("failed before first token"),
+        "{trace_text}"
+    );
+    assert_eq!(
+        trace_text
+            .lines()
+            .filter(|line| line.contains("event=REQUEST_FINAL"))
+            .count(),
+        1,
+        "{trace_text}"
+    );
+    assert!(
+        trace_text.lines().any(|line| line.contains("event=ATTEMPT_RESULT")
+            && line.contains("account_id=acc_stream_2")
+            && line.contains("status=200")),
+        "{trace_text}"
+    );
+}
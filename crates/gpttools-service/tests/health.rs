@@ -0,0 +1,86 @@
+use gpttools_core::storage::{now_ts, Account, Storage, Token};
+
+mod common;
+
+use common::{free_local_addr, wait_until_listening};
+
+fn get_json(url: &str) -> (u16, serde_json::Value) {
+    let resp = reqwest::blocking::get(url).expect("probe request");
+    let status = resp.status().as_u16();
+    (status, resp.json().expect("probe json"))
+}
+
+#[test]
+fn health_and_readiness_probes_report_storage_and_accounts() {
+    let dir = std::env::temp_dir().join(format!("gpttools-health-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let db_path = dir.join("gpttools.db");
+    let control_addr = free_local_addr();
+    let gateway_addr = free_local_addr();
+    std::env::set_var("GPTTOOLS_DB_PATH", &db_path);
+    std::env::set_var("GPTTOOLS_DISABLE_POLLING", "1");
+    std::env::set_var("GPTTOOLS_GATEWAY_ADDR", &gateway_addr);
+
+    let server_addr = control_addr.clone();
+    let _server = std::thread::spawn(move || gpttools_service::start_server(&server_addr));
+    wait_until_listening(&control_addr);
+    wait_until_listening(&gateway_addr);
+
+    let (status, body) = get_json(&format!("http://{control_addr}/healthz"));
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+
+    // 库与迁移正常，但还没有可路由账号时不就绪
+    let (status, body) = get_json(&format!("http://{control_addr}/readyz"));
+    assert_eq!(status, 503, "{body}");
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["checks"]["storage"]["ok"], true);
+    assert_eq!(body["checks"]["migrations"]["ok"], true);
+    assert_eq!(body["checks"]["accounts"]["ok"], false);
+    assert_eq!(body["routableAccounts"], 0);
+    assert_eq!(body["cooldownAccounts"], 0);
+    assert!(body["lastUsagePollAt"].is_null());
+    assert!(body["schemaVersion"].is_string());
+
+    let storage = Storage::open(&db_path).expect("open db");
+    let now = now_ts();
+    storage
+        .insert_account(&Account {
+            id: "acc_ready".to_string(),
+            label: "ready".to_string(),
+            issuer: "https://auth.openai.com".to_string(),
+            chatgpt_account_id: None,
+            workspace_id: None,
+            group_name: None,
+            plan_type: None,
+            proxy_url: None,
+            sort: 0,
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .expect("insert account");
+    storage
+        .insert_token(&Token {
+            account_id: "acc_ready".to_string(),
+            id_token: String::new(),
+            access_token: "access".to_string(),
+            refresh_token: String::new(),
+            api_key_access_token: None,
+            last_refresh: now,
+        })
+        .expect("insert token");
+
+    // 负载均衡从对外网关端口探测，无需平台 key
+    let (status, body) = get_json(&format!("http://{gateway_addr}/readyz"));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["shuttingDown"], false);
+    assert_eq!(body["routableAccounts"], 1);
+    let (status, _) = get_json(&format!("http://{gateway_addr}/healthz"));
+    assert_eq!(status, 200);
+
+    gpttools_service::request_shutdown(&control_addr);
+    let _ = std::fs::remove_dir_all(&dir);
+}
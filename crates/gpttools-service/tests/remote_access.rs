@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod common;
+
+use common::{free_local_addr, wait_until_listening};
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn remote_gateway_listener_serves_tls_and_keeps_rpc_off_the_wire() {
+    let dir = std::env::temp_dir().join(format!("gpttools-remote-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let control_addr = free_local_addr();
+    let gateway_addr = free_local_addr();
+    std::env::set_var("GPTTOOLS_DB_PATH", dir.join("gpttools.db"));
+    std::env::set_var("GPTTOOLS_GATEWAY_ADDR", &gateway_addr);
+    std::env::set_var("GPTTOOLS_TLS_CERT", fixture("cert.pem"));
+    std::env::set_var("GPTTOOLS_TLS_KEY", fixture("key.pem"));
+
+    let server_addr = control_addr.clone();
+    let server = std::thread::spawn(move || gpttools_service::start_server(&server_addr));
+    wait_until_listening(&control_addr);
+    wait_until_listening(&gateway_addr);
+
+    let client = reqwest::blocking::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("client");
+
+    // 网关端口走 TLS，网关路由正常受理（无平台 key 时由鉴权拒绝，而不是 404）
+    let gateway = client
+        .get(format!("https://{gateway_addr}/v1/models"))
+        .send()
+        .expect("tls gateway request");
+    assert_ne!(gateway.status().as_u16(), 404);
+
+    // RPC 与停服入口不在对外端口上暴露
+    let rpc = client
+        .post(format!("https://{gateway_addr}/rpc"))
+        .header("X-Gpttools-Rpc-Token", gpttools_service::rpc_auth_token())
+        .body(r#"{"id":1,"method":"initialize"}"#)
+        .send()
+        .expect("rpc over gateway listener");
+    assert_eq!(rpc.status().as_u16(), 404);
+    let shutdown = client
+        .get(format!("https://{gateway_addr}/__shutdown"))
+        .send()
+        .expect("shutdown over gateway listener");
+    assert_eq!(shutdown.status().as_u16(), 404);
+    assert!(!gpttools_service::shutdown_requested());
+
+    // 明文请求打到 TLS 端口不会被当作 HTTP 处理
+    assert!(reqwest::blocking::get(format!("http://{gateway_addr}/v1/models")).is_err());
+
+    // 控制端口仍是回环明文，RPC 照常可用
+    let rpc = reqwest::blocking::Client::new()
+        .post(format!("http://{control_addr}/rpc"))
+        .header("Content-Type", "application/json")
+        .header("X-Gpttools-Rpc-Token", gpttools_service::rpc_auth_token())
+        .body(r#"{"id":1,"method":"initialize"}"#)
+        .send()
+        .expect("rpc over control listener");
+    assert_eq!(rpc.status().as_u16(), 200);
+
+    gpttools_service::request_shutdown(&control_addr);
+    let result = server.join().expect("server thread");
+    assert!(result.is_ok(), "{result:?}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn rpc_refuses_non_loopback_bind_by_default() {
+    let err = gpttools_service::start_server("0.0.0.0:0").expect_err("remote rpc bind rejected");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("GPTTOOLS_GATEWAY_ADDR"), "{err}");
+}
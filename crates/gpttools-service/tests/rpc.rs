@@ -120,6 +120,25 @@ fn rpc_login_status_pending() {
     assert!(result.get("status").is_some());
 }
 
+#[test]
+fn rpc_login_cancel_requires_login_id() {
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+
+    let req = JsonRpcRequest {
+        id: 5,
+        method: "account/login/cancel".to_string(),
+        params: Some(serde_json::json!({})),
+    };
+    let json = serde_json::to_string(&req).expect("serialize");
+    let v = post_rpc(&server.addr, &json);
+    let result = v.get("result").expect("result");
+    assert_eq!(result.get("ok").and_then(|v| v.as_bool()), Some(false));
+    assert_eq!(
+        result.get("error").and_then(|v| v.as_str()),
+        Some("missing loginId")
+    );
+}
+
 #[test]
 fn rpc_usage_list_empty() {
     let server = gpttools_service::start_one_shot_server().expect("start server");
@@ -136,6 +155,22 @@ fn rpc_usage_list_empty() {
     assert!(items.is_empty());
 }
 
+#[test]
+fn rpc_events_list_returns_items() {
+    let server = gpttools_service::start_one_shot_server().expect("start server");
+
+    let req = JsonRpcRequest {
+        id: 7,
+        method: "events/list".to_string(),
+        params: Some(serde_json::json!({ "accountId": "missing-account", "limit": 10 })),
+    };
+    let json = serde_json::to_string(&req).expect("serialize");
+    let v = post_rpc(&server.addr, &json);
+    let result = v.get("result").expect("result");
+    let items = result.get("items").expect("items").as_array().unwrap();
+    assert!(items.is_empty());
+}
+
 #[test]
 fn rpc_rejects_missing_token() {
     let server = gpttools_service::start_one_shot_server().expect("start server");
@@ -0,0 +1,210 @@
+use gpttools_core::storage::{RequestLog, Storage};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{
+    free_local_addr, platform_key, read_until_contains, seed_storage, wait_until_listening,
+};
+
+static SERVER_LOCK: Mutex<()> = Mutex::new(());
+
+const SUFFIX: &str = "drain";
+const FIRST_EVENT: &str = "event: response.output_text.delta\n\
+data: {\"type\":\"response.output_text.delta\",\"delta\":\"first-delta\"}\n\n";
+const LAST_EVENT: &str = "event: response.completed\n\
+data: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_drain\"}}\n\n";
+
+/// 上游先推一个增量，收到放行信号后再推结束事件；用来模拟停服时仍在推流的长响应。
+fn start_held_upstream() -> (String, Sender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock upstream");
+    let addr = listener
+        .local_addr()
+        .expect("mock upstream addr")
+        .to_string();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    thread::spawn(move || {
+        let mut release_rx = Some(release_rx);
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(3)));
+            let mut raw = Vec::new();
+            read_until_contains(&mut stream, &mut raw, "\r\n\r\n");
+            // 中文注释：服务启动后的网关保活也会打到这里，只有真正的 responses 请求才挂起推流。
+            let is_stream_request = String::from_utf8_lossy(&raw).starts_with("POST ");
+            let Some(release_rx) = release_rx.take_if(|_| is_stream_request) else {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                );
+                continue;
+            };
+            thread::spawn(move || {
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+                    )
+                    .expect("write upstream headers");
+                stream
+                    .write_all(FIRST_EVENT.as_bytes())
+                    .expect("write first event");
+                let _ = stream.flush();
+                let _ = release_rx.recv_timeout(Duration::from_secs(10));
+                let _ = stream.write_all(LAST_EVENT.as_bytes());
+                let _ = stream.flush();
+            });
+        }
+    });
+    (addr, release_tx)
+}
+
+fn send_stream_request(addr: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).expect("connect server");
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let platform_key = platform_key(SUFFIX);
+    let body = r#"{"model":"gpt-5.3-codex","input":"hello","stream":true}"#;
+    let request = format!(
+        "POST /v1/responses HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nAccept: text/event-stream\r\n\
+         Authorization: Bearer {platform_key}\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).expect("write request");
+    stream
+}
+
+struct DrainFixture {
+    dir: PathBuf,
+    db_path: PathBuf,
+    addr: String,
+    release_upstream: Sender<()>,
+    server: thread::JoinHandle<std::io::Result<()>>,
+    stream: TcpStream,
+    received: Vec<u8>,
+}
+
+impl DrainFixture {
+    fn start(name: &str) -> Self {
+        gpttools_service::clear_shutdown_flag();
+        let dir = std::env::temp_dir().join(format!("gpttools-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let db_path = dir.join("gpttools.db");
+        seed_storage(&db_path, SUFFIX);
+        let (upstream_addr, release_upstream) = start_held_upstream();
+        std::env::set_var("GPTTOOLS_DB_PATH", &db_path);
+        std::env::set_var("GPTTOOLS_DISABLE_POLLING", "1");
+        std::env::set_var(
+            "GPTTOOLS_UPSTREAM_BASE_URL",
+            format!("http://{upstream_addr}/backend-api/codex"),
+        );
+
+        let addr = free_local_addr();
+        let server_addr = addr.clone();
+        let server = thread::spawn(move || gpttools_service::start_server(&server_addr));
+        wait_until_listening(&addr);
+
+        // 等到首个增量已推给客户端，说明请求正在推流中
+        let mut stream = send_stream_request(&addr);
+        let mut received = Vec::new();
+        read_until_contains(&mut stream, &mut received, "first-delta");
+        Self {
+            dir,
+            db_path,
+            addr,
+            release_upstream,
+            server,
+            stream,
+            received,
+        }
+    }
+}
+
+fn request_logs(db_path: &Path) -> Vec<RequestLog> {
+    Storage::open(db_path)
+        .expect("open db")
+        .list_request_logs(None, 100)
+        .expect("list request logs")
+}
+
+#[test]
+fn shutdown_waits_for_in_flight_stream_and_logs_completion() {
+    let _lock = SERVER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut fixture = DrainFixture::start("drain-complete");
+
+    let status = gpttools_service::request_shutdown_within(&fixture.addr, Duration::from_secs(10));
+    assert!(status.draining);
+    assert_eq!(status.in_flight_requests, 1);
+    assert!(!status.deadline_exceeded);
+
+    // 排空期间新的网关请求直接 503
+    let mut rejected = send_stream_request(&fixture.addr);
+    let mut response = String::new();
+    let _ = rejected.read_to_string(&mut response);
+    assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+
+    // 在途流不受影响，上游推完后客户端拿到完整响应，服务随即退出
+    thread::sleep(Duration::from_millis(200));
+    assert!(!fixture.server.is_finished());
+    fixture.release_upstream.send(()).expect("release upstream");
+    let mut rest = Vec::new();
+    fixture.stream.read_to_end(&mut rest).expect("read rest");
+    fixture.received.extend_from_slice(&rest);
+    assert!(String::from_utf8_lossy(&fixture.received).contains("response.completed"));
+    let result = fixture.server.join().expect("server thread");
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(gpttools_service::drain_status().in_flight_requests, 0);
+
+    let logs = request_logs(&fixture.db_path);
+    let log = logs
+        .iter()
+        .find(|item| item.request_path == "/v1/responses" && item.status_code == Some(200))
+        .expect("final log for drained stream");
+    assert_eq!(log.error, None);
+    gpttools_service::clear_shutdown_flag();
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn shutdown_cuts_off_streams_at_deadline_and_logs_interruption() {
+    let _lock = SERVER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut fixture = DrainFixture::start("drain-deadline");
+
+    let started = Instant::now();
+    gpttools_service::request_shutdown_within(&fixture.addr, Duration::from_secs(1));
+    let result = fixture.server.join().expect("server thread");
+    assert!(result.is_ok(), "{result:?}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(gpttools_service::drain_status().deadline_exceeded);
+
+    // 客户端的连接随服务退出被断开，拿不到结束事件
+    let mut rest = Vec::new();
+    let _ = fixture.stream.read_to_end(&mut rest);
+    fixture.received.extend_from_slice(&rest);
+    assert!(!String::from_utf8_lossy(&fixture.received).contains("response.completed"));
+    let _ = fixture.release_upstream.send(());
+
+    let logs = request_logs(&fixture.db_path);
+    let log = logs
+        .iter()
+        .find(|item| item.request_path == "/v1/responses")
+        .expect("final log for interrupted stream");
+    assert_eq!(log.status_code, Some(200));
+    assert_eq!(
+        log.error.as_deref(),
+        Some("stream interrupted by service shutdown")
+    );
+    gpttools_service::clear_shutdown_flag();
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
@@ -1,10 +1,10 @@
 use super::{mark_usage_unreachable_if_needed, should_retry_with_refresh};
 use crate::account_availability::Availability;
-use crate::usage_snapshot_store::apply_status_from_snapshot;
+use crate::usage_snapshot_store::{apply_status_from_snapshot, store_usage_snapshot};
 use gpttools_core::storage::{now_ts, Account, Storage, UsageSnapshotRecord};
 
 #[test]
-fn apply_status_marks_inactive_on_missing() {
+fn apply_status_keeps_active_on_unknown_usage() {
     let storage = Storage::open_in_memory().expect("open");
     storage.init().expect("init");
     let account = Account {
@@ -14,6 +14,8 @@ fn apply_status_marks_inactive_on_missing() {
         chatgpt_account_id: None,
         workspace_id: None,
         group_name: None,
+        plan_type: None,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
@@ -34,6 +36,20 @@ fn apply_status_marks_inactive_on_missing() {
     };
 
     let availability = apply_status_from_snapshot(&storage, &record);
+    assert!(matches!(availability, Availability::Unknown(_)));
+    let loaded = storage
+        .list_accounts()
+        .expect("list")
+        .into_iter()
+        .find(|acc| acc.id == "acc-1")
+        .expect("exists");
+    assert_eq!(loaded.status, "active");
+
+    let exhausted = UsageSnapshotRecord {
+        used_percent: Some(100.0),
+        ..record
+    };
+    let availability = apply_status_from_snapshot(&storage, &exhausted);
     assert!(matches!(availability, Availability::Unavailable(_)));
     let loaded = storage
         .list_accounts()
@@ -55,6 +71,8 @@ fn mark_usage_unreachable_only_for_usage_status_error() {
         chatgpt_account_id: None,
         workspace_id: None,
         group_name: None,
+        plan_type: None,
+        proxy_url: None,
         sort: 0,
         status: "active".to_string(),
         created_at: now_ts(),
@@ -92,3 +110,45 @@ fn refresh_retry_filter_matches_auth_failures() {
     assert!(!should_retry_with_refresh("usage endpoint status 429"));
 }
 
+#[test]
+fn store_usage_snapshot_persists_plan_type_from_usage_response() {
+    let storage = Storage::open_in_memory().expect("open");
+    storage.init().expect("init");
+    let account = Account {
+        id: "acc-plan".to_string(),
+        label: "main".to_string(),
+        issuer: "issuer".to_string(),
+        chatgpt_account_id: None,
+        workspace_id: None,
+        group_name: None,
+        plan_type: Some("plus".to_string()),
+        proxy_url: None,
+        sort: 0,
+        status: "active".to_string(),
+        created_at: now_ts(),
+        updated_at: now_ts(),
+    };
+    storage.insert_account(&account).expect("insert");
+
+    let payload = serde_json::json!({
+        "plan_type": "Pro",
+        "rate_limit": { "primary_window": { "used_percent": 10.0, "limit_window_seconds": 18000 } }
+    });
+    store_usage_snapshot(&storage, "acc-plan", payload).expect("store");
+    let loaded = storage
+        .find_account("acc-plan")
+        .expect("find")
+        .expect("exists");
+    assert_eq!(loaded.plan_type.as_deref(), Some("pro"));
+
+    // 用量响应缺少套餐字段时保留已有值
+    let payload = serde_json::json!({
+        "rate_limit": { "primary_window": { "used_percent": 12.0, "limit_window_seconds": 18000 } }
+    });
+    store_usage_snapshot(&storage, "acc-plan", payload).expect("store");
+    let loaded = storage
+        .find_account("acc-plan")
+        .expect("find")
+        .expect("exists");
+    assert_eq!(loaded.plan_type.as_deref(), Some("pro"));
+}